@@ -1,8 +1,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
-use nipart::{NetworkState, NipartClient, NipartNoDaemon, NipartstateApplyOption};
+use nipart::{NetworkState, NipartNoDaemon, NipartstateApplyOption};
 
-use super::{CliError, state::state_from_file};
+use super::{CliError, new_client, state::state_from_file};
 
 pub(crate) struct CommandApply;
 
@@ -29,6 +29,37 @@ impl CommandApply {
                          disable rollback to previous state.",
                     ),
             )
+            .arg(
+                clap::Arg::new("NO_COMMIT")
+                    .long("no-commit")
+                    .action(clap::ArgAction::SetTrue)
+                    .conflicts_with("NO_DAEMON")
+                    .help(
+                        "Do not commit the state. Daemon will rollback to \
+                         previous state if `npt commit` not invoked before \
+                         timeout.",
+                    ),
+            )
+            .arg(
+                clap::Arg::new("TIMEOUT")
+                    .long("timeout")
+                    .requires("NO_COMMIT")
+                    .value_parser(clap::value_parser!(u32))
+                    .help(
+                        "Seconds to wait for commit before automatic \
+                         rollback, default to 60",
+                    ),
+            )
+            .arg(
+                clap::Arg::new("DRY_RUN")
+                    .long("dry-run")
+                    .action(clap::ArgAction::SetTrue)
+                    .conflicts_with("NO_COMMIT")
+                    .help(
+                        "Print operations planned for applying the state \
+                         without changing anything",
+                    ),
+            )
             .arg(
                 clap::Arg::new("NO_DAEMON")
                     .long("no-daemon")
@@ -43,6 +74,8 @@ impl CommandApply {
     ) -> Result<(), CliError> {
         let mut opt = NipartstateApplyOption::default();
         opt.no_verify = matches.get_flag("NO_VERIFY");
+        opt.no_commit = matches.get_flag("NO_COMMIT");
+        opt.rollback_timeout = matches.get_one::<u32>("TIMEOUT").copied();
 
         let desired_state = if let Some(file_paths) =
             matches.get_many::<String>("STATE_FILE")
@@ -57,11 +90,29 @@ impl CommandApply {
             state_from_file("-")?
         };
 
+        if matches.get_flag("DRY_RUN") {
+            let plan = if matches.get_flag("NO_DAEMON") {
+                NipartNoDaemon::plan_network_state(desired_state, opt).await?
+            } else {
+                let mut cli = new_client().await?;
+                cli.plan_network_state(desired_state, opt).await?
+            };
+            if plan.is_empty() {
+                println!("Nothing to change");
+            } else {
+                println!(
+                    "Planned operations:\n---\n{}",
+                    serde_yaml::to_string(&plan)?
+                );
+            }
+            return Ok(());
+        }
+
         let mut diff_net_state = if matches.get_flag("NO_DAEMON") {
             opt.dhcp_in_no_daemon = true;
             NipartNoDaemon::apply_network_state(desired_state, opt).await?
         } else {
-            let mut cli = NipartClient::new().await?;
+            let mut cli = new_client().await?;
             cli.apply_network_state(desired_state, opt).await?
         };
 
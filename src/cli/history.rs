@@ -0,0 +1,59 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::str::FromStr;
+
+use nipart::{NipartHistoryEntry, NipartUuid};
+
+use crate::{CliError, new_client};
+
+pub(crate) struct CommandHistory;
+
+impl CommandHistory {
+    pub(crate) const CMD: &str = "history";
+
+    pub(crate) fn new_cmd() -> clap::Command {
+        clap::Command::new("history")
+            .about("List apply transactions recorded by daemon")
+            .subcommand(
+                clap::Command::new("show")
+                    .about("Show details of specified transaction")
+                    .arg(
+                        clap::Arg::new("ID")
+                            .required(true)
+                            .index(1)
+                            .help("Transaction ID"),
+                    ),
+            )
+    }
+
+    pub(crate) async fn handle(
+        matches: &clap::ArgMatches,
+    ) -> Result<(), CliError> {
+        let mut cli = new_client().await?;
+        if let Some(matches) = matches.subcommand_matches("show") {
+            // It is safe to unwrap because of clap `required: true`
+            let id =
+                NipartUuid::from_str(matches.get_one::<String>("ID").unwrap())?;
+            let entry = cli.query_history_entry(id).await?;
+            println!("{}", serde_yaml::to_string(&entry)?);
+        } else {
+            for entry in cli.query_history().await? {
+                println!("{}", history_summary(&entry));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn history_summary(entry: &NipartHistoryEntry) -> String {
+    let peer = match (entry.peer_pid, entry.peer_uid) {
+        (Some(pid), Some(uid)) => format!("PID {pid} UID {uid}"),
+        _ => "daemon".to_string(),
+    };
+    let outcome = entry.outcome.to_string();
+    format!(
+        "{} {} {outcome:<9} {peer}",
+        entry.id,
+        entry.time.format("%F %T")
+    )
+}
@@ -3,17 +3,21 @@
 mod apply;
 mod diff;
 mod error;
+mod history;
 mod merge;
+mod restore;
 mod show;
 mod state;
+mod wait_online;
 mod wifi;
 
 use nipart::NipartClient;
 
 pub(crate) use self::error::CliError;
 use self::{
-    apply::CommandApply, diff::CommandDiff, merge::CommandMerge,
-    show::CommandShow, wifi::CommandWifi,
+    apply::CommandApply, diff::CommandDiff, history::CommandHistory,
+    merge::CommandMerge, restore::CommandRestore, show::CommandShow,
+    wait_online::CommandWaitOnline, wifi::CommandWifi,
 };
 
 #[tokio::main(flavor = "current_thread")]
@@ -37,19 +41,37 @@ async fn main() -> Result<(), CliError> {
                 .global(true),
         )
         .subcommand(clap::Command::new("ping").about("Check daemon connection"))
+        .subcommand(
+            clap::Command::new("commit")
+                .about("Commit the state applied with `--no-commit`"),
+        )
+        .subcommand(
+            clap::Command::new("rollback")
+                .about("Rollback the state applied with `--no-commit`"),
+        )
+        .subcommand(
+            clap::Command::new("daemon-conf")
+                .about("Show configuration in use by daemon"),
+        )
         .subcommand(CommandShow::new_cmd())
         .subcommand(CommandApply::new_cmd())
         .subcommand(CommandWifi::new_cmd())
         .subcommand(CommandDiff::new_cmd())
-        .subcommand(CommandMerge::new_cmd());
+        .subcommand(CommandMerge::new_cmd())
+        .subcommand(CommandWaitOnline::new_cmd())
+        .subcommand(CommandHistory::new_cmd())
+        .subcommand(CommandRestore::new_cmd());
 
     let matches = cli_cmd.get_matches_mut();
 
     let (log_groups, log_level) = match matches.get_count("verbose") {
-        0 => (vec!["nm", "nmstate"], log::LevelFilter::Info),
-        1 => (vec!["nm", "nmstate"], log::LevelFilter::Debug),
-        2 => (vec!["nm", "nmstate"], log::LevelFilter::Trace),
-        3 => (vec!["nm", "nmstate", "nispor"], log::LevelFilter::Trace),
+        0 => (vec!["nm", "nmstate", "nipart"], log::LevelFilter::Info),
+        1 => (vec!["nm", "nmstate", "nipart"], log::LevelFilter::Debug),
+        2 => (vec!["nm", "nmstate", "nipart"], log::LevelFilter::Trace),
+        3 => (
+            vec!["nm", "nmstate", "nipart", "nispor"],
+            log::LevelFilter::Trace,
+        ),
         _ => (vec![""], log::LevelFilter::Trace),
     };
 
@@ -75,11 +97,34 @@ async fn main() -> Result<(), CliError> {
     Ok(())
 }
 
+/// Create client connection to daemon requesting logs of daemon and plugins
+/// up to the local logging level.
+pub(crate) async fn new_client() -> Result<NipartClient, CliError> {
+    let mut cli = NipartClient::new().await?;
+    cli.set_log_level(log::max_level().into()).await?;
+    Ok(cli)
+}
+
 async fn call_subcommand(matches: &clap::ArgMatches) -> Result<(), CliError> {
     if matches.subcommand_matches("ping").is_some() {
-        let mut cli = NipartClient::new().await?;
+        let mut cli = new_client().await?;
         println!("{}", cli.ping().await?);
         Ok(())
+    } else if matches.subcommand_matches("commit").is_some() {
+        let mut cli = new_client().await?;
+        cli.commit().await?;
+        Ok(())
+    } else if matches.subcommand_matches("rollback").is_some() {
+        let mut cli = new_client().await?;
+        cli.rollback().await?;
+        Ok(())
+    } else if matches.subcommand_matches("daemon-conf").is_some() {
+        let mut cli = new_client().await?;
+        println!(
+            "{}",
+            serde_yaml::to_string(&cli.query_daemon_conf().await?)?
+        );
+        Ok(())
     } else if let Some(matches) = matches.subcommand_matches(CommandShow::CMD) {
         CommandShow::handle(matches).await?;
         Ok(())
@@ -97,6 +142,21 @@ async fn call_subcommand(matches: &clap::ArgMatches) -> Result<(), CliError> {
     } else if let Some(matches) = matches.subcommand_matches(CommandDiff::CMD) {
         CommandDiff::handle(matches).await?;
         Ok(())
+    } else if let Some(matches) =
+        matches.subcommand_matches(CommandWaitOnline::CMD)
+    {
+        CommandWaitOnline::handle(matches).await?;
+        Ok(())
+    } else if let Some(matches) =
+        matches.subcommand_matches(CommandHistory::CMD)
+    {
+        CommandHistory::handle(matches).await?;
+        Ok(())
+    } else if let Some(matches) =
+        matches.subcommand_matches(CommandRestore::CMD)
+    {
+        CommandRestore::handle(matches).await?;
+        Ok(())
     } else {
         Err(CliError::from("Unknown command"))
     }
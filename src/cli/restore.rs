@@ -0,0 +1,44 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{CliError, new_client};
+
+pub(crate) struct CommandRestore;
+
+impl CommandRestore {
+    pub(crate) const CMD: &str = "restore";
+
+    pub(crate) fn new_cmd() -> clap::Command {
+        clap::Command::new("restore")
+            .about("Apply previous generation of saved state")
+            .arg(
+                clap::Arg::new("GENERATION")
+                    .required(true)
+                    .index(1)
+                    .value_parser(clap::value_parser!(u64))
+                    .help(
+                        "Generation number of saved state, logged on every \
+                         apply",
+                    ),
+            )
+    }
+
+    pub(crate) async fn handle(
+        matches: &clap::ArgMatches,
+    ) -> Result<(), CliError> {
+        // It is safe to unwrap because of clap `required: true`
+        let generation = *matches.get_one::<u64>("GENERATION").unwrap();
+        let mut cli = new_client().await?;
+        let mut diff_net_state = cli.restore_generation(generation).await?;
+
+        diff_net_state.hide_secrets();
+        if diff_net_state.is_empty() {
+            println!("Nothing changed");
+        } else {
+            println!(
+                "Changed state:\n---\n{}",
+                serde_yaml::to_string(&diff_net_state)?
+            );
+        }
+        Ok(())
+    }
+}
@@ -1,10 +1,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use nipart::{
-    NetworkState, NipartClient, NipartNoDaemon, NipartstateInterface, NipartstateQueryOption,
+    NetworkState, NipartNoDaemon, NipartstateInterface, NipartstateQueryOption,
 };
 
-use crate::CliError;
+use crate::{CliError, new_client};
 
 pub(crate) struct CommandShow;
 
@@ -36,20 +36,46 @@ impl CommandShow {
                     .action(clap::ArgAction::SetTrue)
                     .help("Show the daemon saved state only"),
             )
+            .arg(
+                clap::Arg::new("DRIFT")
+                    .long("drift")
+                    .short('d')
+                    .action(clap::ArgAction::SetTrue)
+                    .conflicts_with("SAVED")
+                    .help(
+                        "Show how running state drifted from daemon saved \
+                         state: saved interfaces not found in kernel, \
+                         interfaces and routes not in saved state and \
+                         saved properties not matching running state",
+                    ),
+            )
     }
 
     pub(crate) async fn handle(
         matches: &clap::ArgMatches,
     ) -> Result<(), CliError> {
         let net_state = if matches.get_flag("NO_DAEMON") {
-            if matches.get_flag("SAVED") {
+            if matches.get_flag("SAVED") || matches.get_flag("DRIFT") {
                 return Err("--no-daemon or --kernel cannot be used with \
-                            --saved argument"
+                            --saved or --drift argument"
                     .into());
             }
             NipartNoDaemon::query_network_state(Default::default()).await?
         } else {
-            let mut cli = NipartClient::new().await?;
+            let mut cli = new_client().await?;
+            if matches.get_flag("DRIFT") {
+                let mut report = cli.query_drift().await?;
+                if let Some(ifname) = matches.get_one::<String>("IFNAME") {
+                    report.missing.retain(|i| i.name == *ifname);
+                    report.unmanaged.retain(|i| i.name == *ifname);
+                    report
+                        .unmanaged_routes
+                        .retain(|r| r.next_hop_iface.as_ref() == Some(ifname));
+                    report.changed = filter_net_state(&report.changed, ifname);
+                }
+                println!("{}", serde_yaml::to_string(&report)?);
+                return Ok(());
+            }
             let opt = if matches.get_flag("SAVED") {
                 NipartstateQueryOption::saved()
             } else {
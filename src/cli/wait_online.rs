@@ -0,0 +1,88 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use nipart::{
+    NipartWaitOnlineCondition, NipartWaitOnlineIface, NipartWaitOnlineOption,
+};
+
+use super::{CliError, new_client};
+
+pub(crate) struct CommandWaitOnline;
+
+impl CommandWaitOnline {
+    pub(crate) const CMD: &str = "wait-online";
+
+    pub(crate) fn new_cmd() -> clap::Command {
+        clap::Command::new("wait-online")
+            .about(
+                "Wait network to be online. By default, wait all interfaces \
+                 in saved state to be up and got IP addresses",
+            )
+            .arg(
+                clap::Arg::new("DEFAULT_ROUTE")
+                    .long("default-route")
+                    .action(clap::ArgAction::SetTrue)
+                    .conflicts_with("IFACE")
+                    .help("Wait any interface to have default route"),
+            )
+            .arg(
+                clap::Arg::new("IFACE")
+                    .long("iface")
+                    .short('i')
+                    .action(clap::ArgAction::Append)
+                    .help(
+                        "Wait specified interface to be up, could be used \
+                         multiple times",
+                    ),
+            )
+            .arg(
+                clap::Arg::new("IPV4")
+                    .long("ipv4")
+                    .action(clap::ArgAction::SetTrue)
+                    .requires("IFACE")
+                    .help("Require specified interfaces to have IPv4 address"),
+            )
+            .arg(
+                clap::Arg::new("IPV6")
+                    .long("ipv6")
+                    .action(clap::ArgAction::SetTrue)
+                    .requires("IFACE")
+                    .help(
+                        "Require specified interfaces to have IPv6 address \
+                         other than link-local",
+                    ),
+            )
+            .arg(
+                clap::Arg::new("TIMEOUT")
+                    .long("timeout")
+                    .short('t')
+                    .value_parser(clap::value_parser!(u32))
+                    .help("Seconds to wait before failing, default to 30"),
+            )
+    }
+
+    pub(crate) async fn handle(
+        matches: &clap::ArgMatches,
+    ) -> Result<(), CliError> {
+        let mut opt = NipartWaitOnlineOption::new();
+        if let Some(timeout) = matches.get_one::<u32>("TIMEOUT") {
+            opt = opt.timeout(*timeout);
+        }
+        if matches.get_flag("DEFAULT_ROUTE") {
+            opt = opt.condition(NipartWaitOnlineCondition::DefaultRoute);
+        } else if let Some(iface_names) = matches.get_many::<String>("IFACE") {
+            let ipv4 = matches.get_flag("IPV4");
+            let ipv6 = matches.get_flag("IPV6");
+            opt = opt.condition(NipartWaitOnlineCondition::Interfaces(
+                iface_names
+                    .map(|n| {
+                        NipartWaitOnlineIface::new(n.to_string(), ipv4, ipv6)
+                    })
+                    .collect(),
+            ));
+        }
+
+        let mut cli = new_client().await?;
+        cli.wait_online(opt).await?;
+        Ok(())
+    }
+}
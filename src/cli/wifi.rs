@@ -3,9 +3,9 @@
 use std::io::{IsTerminal, Write, stdin, stdout};
 
 use nix::sys::termios::{LocalFlags, SetArg, tcgetattr, tcsetattr};
-use nipart::{NetworkState, NipartClient, NipartNoDaemon};
+use nipart::{NetworkState, NipartNoDaemon};
 
-use crate::CliError;
+use crate::{CliError, new_client};
 
 pub(crate) struct CommandWifi;
 
@@ -101,7 +101,7 @@ impl CommandWifi {
                 "Applying desire state:\n{}",
                 serde_yaml::to_string(&desired_state_to_show)?
             );
-            let mut cli = NipartClient::new().await?;
+            let mut cli = new_client().await?;
             cli.apply_network_state(desired_state, Default::default())
                 .await?;
         }
@@ -1,8 +1,16 @@
 // SPDX-License-Identifier: Apache-2.0
 
-use nipart::{ErrorKind, NetworkState, NipartClientCmd, NipartError, NipartIpcConnection};
+use futures_channel::mpsc::UnboundedReceiver;
+use futures_util::StreamExt;
+use nipart::{
+    ErrorKind, NetworkState, NipartClientCmd, NipartError, NipartEvent,
+    NipartIpcConnection,
+};
 
-use crate::{commander::NipartCommander, lock::NipartLockManager, log_debug, log_info};
+use crate::{
+    commander::NipartCommander, daemon_conf::NipartDaemonConfManager,
+    lock::NipartLockManager, log_debug, log_info,
+};
 
 pub(crate) async fn process_api_connection(
     mut conn: NipartIpcConnection,
@@ -41,6 +49,14 @@ pub(crate) async fn process_api_connection(
                     commander.query_network_state(Some(&mut conn), *opt).await;
                 conn.send(result).await?;
             }
+            NipartClientCmd::ApplyNetworkState(opt) if opt.1.dry_run => {
+                // Dry-run changes nothing, no lock required
+                let (desired_state, opt) = *opt;
+                let result = commander
+                    .plan_network_state(Some(&mut conn), desired_state, opt)
+                    .await;
+                conn.send(result).await?;
+            }
             NipartClientCmd::ApplyNetworkState(opt) => {
                 log_info(
                     Some(&mut conn),
@@ -68,7 +84,12 @@ pub(crate) async fn process_api_connection(
                 .await;
                 let (desired_state, opt) = *opt;
                 let result = commander
-                    .apply_network_state(Some(&mut conn), desired_state, opt)
+                    .apply_network_state(
+                        Some(&mut conn),
+                        desired_state,
+                        opt,
+                        Some((peer_uid, peer_pid)),
+                    )
                     .await;
                 log_info(
                     Some(&mut conn),
@@ -78,6 +99,75 @@ pub(crate) async fn process_api_connection(
                 drop(lock);
                 conn.send(result).await?;
             }
+            NipartClientCmd::RestoreGeneration(generation) => {
+                let lock = NipartLockManager::lock(peer_pid).await;
+                let result = commander
+                    .restore_generation(
+                        Some(&mut conn),
+                        generation,
+                        Some((peer_uid, peer_pid)),
+                    )
+                    .await;
+                drop(lock);
+                conn.send(result).await?;
+            }
+            NipartClientCmd::Commit => {
+                let lock = NipartLockManager::lock(peer_pid).await;
+                let result = commander.commit(Some(&mut conn)).await;
+                drop(lock);
+                conn.send(result).await?;
+            }
+            NipartClientCmd::Rollback => {
+                let lock = NipartLockManager::lock(peer_pid).await;
+                let result =
+                    commander.rollback_checkpoint(Some(&mut conn)).await;
+                drop(lock);
+                conn.send(result).await?;
+            }
+            NipartClientCmd::Subscribe => {
+                log_debug(
+                    Some(&mut conn),
+                    format!("Client process {peer_pid} subscribed to events"),
+                )
+                .await;
+                let mut receiver = commander.event_emitter.subscribe();
+                commander.monitor_manager.set_emit_all(true).await?;
+                conn.send(Ok(())).await?;
+                let result = forward_events(&mut conn, &mut receiver).await;
+                drop(receiver);
+                if !commander.event_emitter.has_subscriber() {
+                    commander.monitor_manager.set_emit_all(false).await?;
+                }
+                log::debug!("Client process {peer_pid} unsubscribed");
+                // The connection is dedicated to events after subscribed
+                break result;
+            }
+            NipartClientCmd::WifiScan(iface_name) => {
+                let result = commander
+                    .wifi_scan(Some(&mut conn), iface_name.as_deref())
+                    .await;
+                conn.send(result).await?;
+            }
+            NipartClientCmd::WaitOnline(opt) => {
+                let result = commander.wait_online(Some(&mut conn), *opt).await;
+                conn.send(result).await?;
+            }
+            NipartClientCmd::QueryDaemonConf => {
+                conn.send(Ok(NipartDaemonConfManager::get().as_ref().clone()))
+                    .await?;
+            }
+            NipartClientCmd::SetLogLevel(log_level) => {
+                conn.set_log_level(log_level);
+                conn.send(Ok(())).await?;
+            }
+            NipartClientCmd::QueryHistory(id) => {
+                let result = commander.conf_manager.query_history(id).await;
+                conn.send(result).await?;
+            }
+            NipartClientCmd::QueryDrift => {
+                let result = commander.query_drift(Some(&mut conn)).await;
+                conn.send(result).await?;
+            }
             _ => {
                 conn.send::<Result<NetworkState, NipartError>>(Err(NipartError::new(
                     ErrorKind::NoSupport,
@@ -89,6 +179,38 @@ pub(crate) async fn process_api_connection(
     }
 }
 
+/// Send events to client until connection closed
+async fn forward_events(
+    conn: &mut NipartIpcConnection,
+    receiver: &mut UnboundedReceiver<NipartEvent>,
+) -> Result<(), NipartError> {
+    loop {
+        tokio::select! {
+            event = receiver.next() => {
+                if let Some(event) = event {
+                    conn.send(Ok(event)).await?;
+                } else {
+                    return Ok(());
+                }
+            }
+            // Client is not expected to send anything after subscribed,
+            // we are only waiting connection closed here.
+            result = conn.recv::<NipartClientCmd>() => {
+                match result {
+                    Err(e) if e.kind == ErrorKind::IpcClosed => return Ok(()),
+                    Err(e) if e.kind == ErrorKind::Timeout => (),
+                    Err(e) => return Err(e),
+                    Ok(cmd) => {
+                        log::debug!(
+                            "Ignoring command {cmd:?} from event subscriber"
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
 // Once https://github.com/rust-lang/rust/issues/76915 goes stable and shipped
 // to most distributions, we should use `std::os::unix::net::SocketCred`
 //
@@ -108,7 +230,7 @@ fn get_peer_info(conn: &NipartIpcConnection) -> Result<(u32, i32), NipartError>
     Ok((credential.uid(), credential.pid()))
 }
 
-fn permission_check(
+pub(crate) fn permission_check(
     command: &NipartClientCmd,
     peer_uid: u32,
 ) -> Result<(), NipartError> {
@@ -116,7 +238,14 @@ fn permission_check(
         Ok(())
     } else {
         match command {
-            NipartClientCmd::Ping => Ok(()),
+            NipartClientCmd::Ping
+            | NipartClientCmd::Subscribe
+            | NipartClientCmd::WaitOnline(_)
+            | NipartClientCmd::QueryDaemonConf
+            | NipartClientCmd::QueryDrift
+            | NipartClientCmd::SetLogLevel(_) => Ok(()),
+            // Dry-run changes nothing and secrets are hidden in the plan
+            NipartClientCmd::ApplyNetworkState(cmd) if cmd.1.dry_run => Ok(()),
             NipartClientCmd::QueryNetworkState(s) => {
                 if s.include_secrets {
                     Err(NipartError::new(
@@ -128,6 +257,11 @@ fn permission_check(
                     Ok(())
                 }
             }
+            // Active scan disrupts on-going WiFi traffic
+            NipartClientCmd::WifiScan(_) => Err(NipartError::new(
+                ErrorKind::PermissionDeny,
+                "WiFi scan requires root permission".into(),
+            )),
             _ => Err(NipartError::new(
                 ErrorKind::PermissionDeny,
                 "Command {command} need to root permission".into(),
@@ -1,24 +1,135 @@
 // SPDX-License-Identifier: Apache-2.0
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use nipart::{
-    Interface, InterfaceType, MergedInterfaces, MergedNetworkState,
-    NetworkState, NipartError, NipartIpcConnection, NipartNoDaemon, NipartstateApplyOption,
-    NipartstateInterface,
+    ErrorKind, Interface, InterfaceType, MergedInterfaces, MergedNetworkState,
+    NetworkState, NipartError, NipartEvent, NipartHistoryEntry,
+    NipartIpcConnection, NipartNoDaemon, NipartUuid, NipartstateApplyOption,
+    NipartstateApplyPlan, NipartstateInterface,
 };
 
-use super::commander::NipartCommander;
+use super::{
+    checkpoint::NipartCheckpoint, commander::NipartCommander,
+    daemon_conf::NipartDaemonConfManager,
+};
 use crate::{log_debug, log_error, log_info, log_trace, log_warn};
 
-const RETRY_COUNT: usize = 10;
-const RETRY_INTERVAL_MS: u64 = 500;
+struct MergedDesiredState {
+    merged_state: MergedNetworkState,
+    // Saved state before merging applied state
+    state_to_save: NetworkState,
+    pre_apply_current_state: NetworkState,
+    // Logical interface names indexed by kernel interface names
+    iface_names: HashMap<String, String>,
+}
 
 impl NipartCommander {
+    /// Apply desired state and record the transaction into history.
+    /// The `peer` is (uid, pid) of client process, None when applied by
+    /// daemon itself.
     pub(crate) async fn apply_network_state(
         &mut self,
         mut conn: Option<&mut NipartIpcConnection>,
-        mut desired_state: NetworkState,
+        desired_state: NetworkState,
+        opt: NipartstateApplyOption,
+        peer: Option<(u32, i32)>,
+    ) -> Result<NetworkState, NipartError> {
+        if opt.dry_run {
+            return Err(NipartError::new(
+                ErrorKind::InvalidArgument,
+                "Please use plan_network_state() for dry-run apply".to_string(),
+            ));
+        }
+        // Only reject client requests, daemon internal applies (e.g. reload,
+        // new NIC) should still work with checkpoint pending.
+        if peer.is_some()
+            && let Some(id) = self.checkpoint.pending_id()?
+        {
+            return Err(NipartError::new(
+                ErrorKind::InvalidArgument,
+                format!(
+                    "Checkpoint {id} is pending, please commit or rollback \
+                     it before applying new state"
+                ),
+            ));
+        }
+        let transaction_id = NipartUuid::new();
+        let time = chrono::Utc::now();
+        log_info(
+            conn.as_deref_mut(),
+            format!("Starting transaction {transaction_id}"),
+        )
+        .await;
+
+        let result = self
+            .apply_transaction(
+                conn.as_deref_mut(),
+                transaction_id,
+                desired_state.clone(),
+                opt,
+            )
+            .await;
+
+        let mut entry = NipartHistoryEntry::new(
+            transaction_id,
+            time,
+            desired_state,
+            result.clone(),
+        );
+        if let Some((uid, pid)) = peer {
+            entry.peer_uid = Some(uid);
+            entry.peer_pid = Some(pid);
+        }
+        if let Err(e) = self.conf_manager.save_history(entry).await {
+            log_warn(
+                conn,
+                format!(
+                    "Failed to save transaction {transaction_id} to \
+                     history: {e}"
+                ),
+            )
+            .await;
+        }
+        result
+    }
+
+    /// Emit [NipartEvent::ApplyFinished] on success or
+    /// [NipartEvent::ApplyFailed] on failure after
+    /// [NipartEvent::ApplyStarted].
+    async fn apply_transaction(
+        &mut self,
+        conn: Option<&mut NipartIpcConnection>,
+        transaction_id: NipartUuid,
+        desired_state: NetworkState,
+        opt: NipartstateApplyOption,
+    ) -> Result<NetworkState, NipartError> {
+        self.event_emitter
+            .emit(NipartEvent::ApplyStarted(transaction_id));
+        match self
+            .do_apply_transaction(conn, transaction_id, desired_state, opt)
+            .await
+        {
+            Ok(diff_state) => {
+                self.event_emitter
+                    .emit(NipartEvent::ApplyFinished(transaction_id));
+                Ok(diff_state)
+            }
+            Err(e) => {
+                self.event_emitter.emit(NipartEvent::ApplyFailed(
+                    transaction_id,
+                    e.to_string(),
+                ));
+                Err(e)
+            }
+        }
+    }
+
+    async fn do_apply_transaction(
+        &mut self,
+        mut conn: Option<&mut NipartIpcConnection>,
+        transaction_id: NipartUuid,
+        desired_state: NetworkState,
         opt: NipartstateApplyOption,
     ) -> Result<NetworkState, NipartError> {
         if desired_state.is_empty() {
@@ -34,52 +145,98 @@ impl NipartCommander {
         )
         .await;
 
-        desired_state.ifaces.unify_veth_and_ethernet();
-
-        let mut state_to_save = self.conf_manager.query_state().await?;
-        let mut state_to_apply = state_to_save.clone();
-        state_to_apply.merge(&desired_state)?;
-        remove_undesired_ifaces(&mut state_to_apply, &desired_state);
-
-        log_info(
-            conn.as_deref_mut(),
-            format!(
-                "Merged desired with previous saved state, state to apply \
-                 {state_to_apply}"
-            ),
-        )
-        .await;
-        let mut pre_apply_current_state = self
-            .query_network_state(conn.as_deref_mut(), Default::default())
+        let MergedDesiredState {
+            merged_state,
+            mut state_to_save,
+            pre_apply_current_state,
+            iface_names,
+        } = self
+            .merge_desired_state(conn.as_deref_mut(), desired_state, &opt)
             .await?;
 
-        pre_apply_current_state.ifaces.unify_veth_and_ethernet();
-
-        log_debug(
-            conn.as_deref_mut(),
-            format!("Pre-apply current state {pre_apply_current_state}"),
-        )
-        .await;
-
-        let merged_state = MergedNetworkState::new(
-            state_to_apply,
-            pre_apply_current_state.clone(),
-            opt.clone(),
-        )?;
-
         let state_to_apply = merged_state.gen_state_for_apply();
 
-        state_to_save.merge(&state_to_apply)?;
+        // Save interfaces using logical names
+        let mut state_to_apply_logical = state_to_apply.clone();
+        state_to_apply_logical.restore_iface_identifiers(&iface_names);
+        state_to_save.merge(&state_to_apply_logical)?;
 
         let revert_state =
             state_to_apply.generate_revert(&pre_apply_current_state)?;
 
         // TODO(Gris Ge): discard auto IPs
 
+        self.plugin_manager
+            .pre_apply(conn.as_deref_mut(), &state_to_apply)
+            .await?;
+
         // Suppress the monitor during applying
         self.monitor_manager.pause().await?;
+        let checkpoint = NipartCheckpoint {
+            id: transaction_id,
+            revert_state,
+            state_to_merge: state_to_apply_logical,
+        };
+        let result = self
+            .apply_with_monitor_paused(
+                conn.as_deref_mut(),
+                &merged_state,
+                checkpoint,
+                state_to_save,
+                &opt,
+            )
+            .await;
+        // Resume the monitor even on failure, otherwise carrier and hotplug
+        // events are lost until next successful apply.
+        if let Err(e) = self.monitor_manager.resume().await {
+            if result.is_ok() {
+                return Err(e);
+            }
+            log_error(
+                conn.as_deref_mut(),
+                format!("Failed to resume interface monitor: {e}"),
+            )
+            .await;
+        }
+        if let Err(e) = result {
+            self.post_apply(conn, &state_to_apply, Err(e.clone())).await;
+            return Err(e);
+        }
+
+        self.post_apply(conn.as_deref_mut(), &state_to_apply, Ok(()))
+            .await;
+
+        let mut diff_state = match merged_state
+            .gen_state_for_apply()
+            .gen_diff(&pre_apply_current_state)
+        {
+            Ok(s) => s,
+            Err(e) => {
+                log_warn(
+                    conn,
+                    format!("Returning full state instead of diff state: {e}"),
+                )
+                .await;
+                merged_state.gen_state_for_apply()
+            }
+        };
+        diff_state.hide_secrets();
+
+        Ok(diff_state)
+    }
+
+    /// Apply merged state, create checkpoint or persist the state, then update
+    /// the interface monitor list. Rollback on apply failure.
+    async fn apply_with_monitor_paused(
+        &mut self,
+        mut conn: Option<&mut NipartIpcConnection>,
+        merged_state: &MergedNetworkState,
+        checkpoint: NipartCheckpoint,
+        state_to_save: NetworkState,
+        opt: &NipartstateApplyOption,
+    ) -> Result<(), NipartError> {
         if let Err(e) = self
-            .apply_merged_state(conn.as_deref_mut(), &merged_state, &opt)
+            .apply_merged_state(conn.as_deref_mut(), merged_state, opt)
             .await
         {
             log_warn(
@@ -99,32 +256,64 @@ impl NipartCommander {
             .await;
             log_trace(
                 conn.as_deref_mut(),
-                format!("Rollback to state before apply {revert_state}"),
+                format!(
+                    "Rollback to state before apply {}",
+                    checkpoint.revert_state
+                ),
             )
             .await;
-            if let Err(e) =
-                self.rollback(conn.as_deref_mut(), revert_state).await
+            if let Err(e) = self
+                .rollback(conn.as_deref_mut(), checkpoint.revert_state)
+                .await
             {
                 log_error(
                     conn.as_deref_mut(),
                     format!("Failed to rollback: {e}"),
                 )
                 .await;
+            } else {
+                self.event_emitter
+                    .emit(NipartEvent::ApplyRolledBack(checkpoint.id));
             }
             return Err(e);
         }
 
-        if let Err(e) =
-            self.conf_manager.save_state(state_to_save.clone()).await
-        {
-            log_warn(
+        if opt.no_commit {
+            let id = checkpoint.id;
+            let timeout = opt
+                .rollback_timeout
+                .unwrap_or(NipartstateApplyOption::DEFAULT_ROLLBACK_TIMEOUT);
+            self.checkpoint.create(checkpoint)?;
+            self.start_checkpoint_timer(id, timeout);
+            log_info(
                 conn.as_deref_mut(),
                 format!(
-                    "BUG: Failed to persistent desired state {state_to_save}: \
-                     {e}"
+                    "Checkpoint {id} created, will rollback in {timeout} \
+                     seconds if not committed"
                 ),
             )
             .await;
+        } else {
+            match self.conf_manager.save_state(state_to_save.clone()).await {
+                Ok(Some(generation)) => {
+                    log_info(
+                        conn.as_deref_mut(),
+                        format!("Saved state as generation {generation}"),
+                    )
+                    .await;
+                }
+                Ok(None) => (),
+                Err(e) => {
+                    log_warn(
+                        conn.as_deref_mut(),
+                        format!(
+                            "BUG: Failed to persistent desired state \
+                             {state_to_save}: {e}"
+                        ),
+                    )
+                    .await;
+                }
+            }
         }
 
         let (mut ifaces_start_monitor, mut ifaces_stop_monitor) =
@@ -151,28 +340,112 @@ impl NipartCommander {
                 .await?;
         }
 
-        self.monitor_manager.resume().await?;
+        Ok(())
+    }
 
-        let mut diff_state = match merged_state
-            .gen_state_for_apply()
-            .gen_diff(&pre_apply_current_state)
+    /// Generate operations planned for applying desired state without
+    /// touching anything.
+    pub(crate) async fn plan_network_state(
+        &mut self,
+        mut conn: Option<&mut NipartIpcConnection>,
+        desired_state: NetworkState,
+        opt: NipartstateApplyOption,
+    ) -> Result<NipartstateApplyPlan, NipartError> {
+        log_trace(
+            conn.as_deref_mut(),
+            format!("Plan {desired_state} with option {opt}"),
+        )
+        .await;
+        self.merge_desired_state(conn, desired_state, &opt)
+            .await?
+            .merged_state
+            .gen_plan()
+    }
+
+    /// Merge desired state with saved state and pre-apply current state.
+    async fn merge_desired_state(
+        &mut self,
+        mut conn: Option<&mut NipartIpcConnection>,
+        mut desired_state: NetworkState,
+        opt: &NipartstateApplyOption,
+    ) -> Result<MergedDesiredState, NipartError> {
+        desired_state.ifaces.unify_veth_and_ethernet();
+
+        // Plugins may reject the desired state before any change happens
+        self.plugin_manager
+            .validate_network_state(conn.as_deref_mut(), &desired_state)
+            .await?;
+
+        let state_to_save = self.conf_manager.query_state().await?;
+        let mut state_to_apply = state_to_save.clone();
+        state_to_apply.merge(&desired_state)?;
+        remove_undesired_ifaces(&mut state_to_apply, &desired_state);
+
+        log_info(
+            conn.as_deref_mut(),
+            format!(
+                "Merged desired with previous saved state, state to apply \
+                 {state_to_apply}"
+            ),
+        )
+        .await;
+        let mut pre_apply_current_state = self
+            .query_network_state(conn.as_deref_mut(), Default::default())
+            .await?;
+
+        pre_apply_current_state.ifaces.unify_veth_and_ethernet();
+
+        // Bind interfaces to kernel interfaces matching their identifiers
+        let iface_names = state_to_apply
+            .resolve_iface_identifiers(&pre_apply_current_state)?;
+        for (kernel_name, logical_name) in iface_names.iter() {
+            log_info(
+                conn.as_deref_mut(),
+                format!(
+                    "Interface {logical_name} is bound to kernel interface \
+                     {kernel_name}"
+                ),
+            )
+            .await;
+        }
+
+        log_debug(
+            conn.as_deref_mut(),
+            format!("Pre-apply current state {pre_apply_current_state}"),
+        )
+        .await;
+
+        let merged_state = MergedNetworkState::new(
+            state_to_apply,
+            pre_apply_current_state.clone(),
+            opt.clone(),
+        )?;
+
+        Ok(MergedDesiredState {
+            merged_state,
+            state_to_save,
+            pre_apply_current_state,
+            iface_names,
+        })
+    }
+
+    async fn post_apply(
+        &mut self,
+        mut conn: Option<&mut NipartIpcConnection>,
+        applied_state: &NetworkState,
+        result: Result<(), NipartError>,
+    ) {
+        if let Err(e) = self
+            .plugin_manager
+            .post_apply(conn.as_deref_mut(), applied_state, &result)
+            .await
         {
-            Ok(s) => s,
-            Err(e) => {
-                log_warn(
-                    conn,
-                    format!("Returning full state instead of diff state: {e}"),
-                )
+            log_warn(conn, format!("Failed to invoke plugin post-apply: {e}"))
                 .await;
-                merged_state.gen_state_for_apply()
-            }
-        };
-        diff_state.hide_secrets();
-
-        Ok(diff_state)
+        }
     }
 
-    async fn rollback(
+    pub(crate) async fn rollback(
         &mut self,
         mut conn: Option<&mut NipartIpcConnection>,
         revert_state: NetworkState,
@@ -190,7 +463,7 @@ impl NipartCommander {
 
         NipartNoDaemon::apply_merged_state(&merged_state).await?;
         self.plugin_manager
-            .apply_network_state(&apply_state, &opt)
+            .apply_network_state(conn.as_deref_mut(), &apply_state, &opt)
             .await?;
 
         self.dhcpv4_manager
@@ -236,7 +509,7 @@ impl NipartCommander {
         Ok(())
     }
 
-    async fn apply_merged_state(
+    pub(crate) async fn apply_merged_state(
         &mut self,
         mut conn: Option<&mut NipartIpcConnection>,
         merged_state: &MergedNetworkState,
@@ -249,7 +522,7 @@ impl NipartCommander {
 
         NipartNoDaemon::apply_merged_state(merged_state).await?;
         self.plugin_manager
-            .apply_network_state(&apply_state, opt)
+            .apply_network_state(conn.as_deref_mut(), &apply_state, opt)
             .await?;
 
         self.dhcpv4_manager
@@ -258,19 +531,21 @@ impl NipartCommander {
 
         let mut result: Result<(), NipartError> = Ok(());
         if !opt.no_verify {
-            for cur_retry_count in 1..(RETRY_COUNT + 1) {
+            let conf = NipartDaemonConfManager::get();
+            let retry_count = conf.apply.verify_retry_count;
+            for cur_retry_count in 1..(retry_count + 1) {
                 result = self.verify(conn.as_deref_mut(), merged_state).await;
                 if let Err(e) = &result {
                     log_info(
                         conn.as_deref_mut(),
                         format!(
-                            "Retrying({cur_retry_count}/{RETRY_COUNT}) on \
+                            "Retrying({cur_retry_count}/{retry_count}) on \
                              verification error: {e}"
                         ),
                     )
                     .await;
                     tokio::time::sleep(std::time::Duration::from_millis(
-                        RETRY_INTERVAL_MS,
+                        conf.apply.verify_retry_interval_ms,
                     ))
                     .await;
                 } else {
@@ -0,0 +1,169 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::{Arc, Mutex};
+
+use nipart::{
+    ErrorKind, NetworkState, NipartError, NipartEvent, NipartIpcConnection,
+    NipartUuid,
+};
+
+use crate::{
+    commander::NipartCommander, lock::NipartLockManager, log_info, log_warn,
+};
+
+/// State applied without commit.
+#[derive(Debug, Clone)]
+pub(crate) struct NipartCheckpoint {
+    pub(crate) id: NipartUuid,
+    /// State to apply when rollback
+    pub(crate) revert_state: NetworkState,
+    /// Desired state using logical interface names, merged onto the saved
+    /// state when commit. Storing the full state to save here would
+    /// overwrite states saved after this checkpoint created.
+    pub(crate) state_to_merge: NetworkState,
+}
+
+/// Holding the only pending checkpoint.
+/// The checkpoint is not persisted, hence daemon restart discards it and
+/// applies the last saved state.
+/// This struct is safe to clone and move to threads.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct NipartCheckpointManager {
+    pending: Arc<Mutex<Option<NipartCheckpoint>>>,
+}
+
+impl NipartCheckpointManager {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(
+        &self,
+    ) -> Result<std::sync::MutexGuard<'_, Option<NipartCheckpoint>>, NipartError>
+    {
+        self.pending.lock().map_err(|e| {
+            NipartError::new(
+                ErrorKind::Bug,
+                format!("Failed to lock pending checkpoint: {e}"),
+            )
+        })
+    }
+
+    pub(crate) fn pending_id(&self) -> Result<Option<NipartUuid>, NipartError> {
+        Ok(self.lock()?.as_ref().map(|c| c.id))
+    }
+
+    pub(crate) fn create(
+        &self,
+        checkpoint: NipartCheckpoint,
+    ) -> Result<(), NipartError> {
+        let mut pending = self.lock()?;
+        if let Some(cur) = pending.as_ref() {
+            return Err(NipartError::new(
+                ErrorKind::Bug,
+                format!("Checkpoint {} is already pending", cur.id),
+            ));
+        }
+        *pending = Some(checkpoint);
+        Ok(())
+    }
+
+    /// Take pending checkpoint out. When `id` defined, only take the pending
+    /// checkpoint with the same ID.
+    pub(crate) fn take(
+        &self,
+        id: Option<NipartUuid>,
+    ) -> Result<Option<NipartCheckpoint>, NipartError> {
+        let mut pending = self.lock()?;
+        if id.is_some() && pending.as_ref().map(|c| c.id) != id {
+            Ok(None)
+        } else {
+            Ok(pending.take())
+        }
+    }
+}
+
+impl NipartCommander {
+    /// Persist the state of pending checkpoint.
+    pub(crate) async fn commit(
+        &mut self,
+        mut conn: Option<&mut NipartIpcConnection>,
+    ) -> Result<(), NipartError> {
+        let Some(checkpoint) = self.checkpoint.take(None)? else {
+            return Err(NipartError::new(
+                ErrorKind::InvalidArgument,
+                "No pending checkpoint to commit".to_string(),
+            ));
+        };
+        let mut state_to_save = self.conf_manager.query_state().await?;
+        state_to_save.merge(&checkpoint.state_to_merge)?;
+        let generation = self.conf_manager.save_state(state_to_save).await?;
+        log_info(
+            conn.as_deref_mut(),
+            format!("Checkpoint {} committed", checkpoint.id),
+        )
+        .await;
+        if let Some(generation) = generation {
+            log_info(conn, format!("Saved state as generation {generation}"))
+                .await;
+        }
+        Ok(())
+    }
+
+    /// Rollback to the state before pending checkpoint created.
+    pub(crate) async fn rollback_checkpoint(
+        &mut self,
+        mut conn: Option<&mut NipartIpcConnection>,
+    ) -> Result<(), NipartError> {
+        let Some(checkpoint) = self.checkpoint.take(None)? else {
+            return Err(NipartError::new(
+                ErrorKind::InvalidArgument,
+                "No pending checkpoint to rollback".to_string(),
+            ));
+        };
+        log_info(
+            conn.as_deref_mut(),
+            format!("Rolling back checkpoint {}", checkpoint.id),
+        )
+        .await;
+        self.rollback(conn, checkpoint.revert_state).await?;
+        self.event_emitter
+            .emit(NipartEvent::ApplyRolledBack(checkpoint.id));
+        Ok(())
+    }
+
+    /// Rollback the specified checkpoint if still pending after timeout.
+    pub(crate) fn start_checkpoint_timer(&self, id: NipartUuid, timeout: u32) {
+        let mut commander = self.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_secs(timeout.into()))
+                .await;
+            // Wait on-going transaction
+            let lock = NipartLockManager::lock(std::process::id() as i32).await;
+            match commander.checkpoint.take(Some(id)) {
+                Ok(Some(checkpoint)) => {
+                    log_warn(
+                        None,
+                        format!(
+                            "Checkpoint {id} not committed in {timeout} \
+                             seconds, rolling back"
+                        ),
+                    )
+                    .await;
+                    if let Err(e) =
+                        commander.rollback(None, checkpoint.revert_state).await
+                    {
+                        log::error!("Failed to rollback checkpoint {id}: {e}");
+                    } else {
+                        commander
+                            .event_emitter
+                            .emit(NipartEvent::ApplyRolledBack(id));
+                    }
+                }
+                Ok(None) => (),
+                Err(e) => log::error!("{e}"),
+            }
+            drop(lock);
+        });
+    }
+}
@@ -3,24 +3,20 @@
 use std::collections::HashSet;
 
 use futures_channel::mpsc::UnboundedSender;
+use futures_util::StreamExt;
 use nipart::{
     InterfaceType, NetworkState, NipartError, NipartNoDaemon, NipartstateInterface,
     NipartstateQueryOption,
 };
 
 use super::{
-    conf::NipartConfManager, daemon::NipartManagerCmd, dhcp::NipartDhcpV4Manager,
+    checkpoint::NipartCheckpointManager, conf::NipartConfManager,
+    daemon::NipartManagerCmd, daemon_conf::NipartDaemonConfManager,
+    dhcp::NipartDhcpV4Manager, emitter::NipartEventEmitter,
     monitor::NipartMonitorManager, plugin::NipartPluginManager,
-    udev::udev_net_device_is_initialized,
+    reconcile::NipartReconcileManager, udev::udev_net_device_is_initialized,
 };
 
-const BOOTUP_NIC_CHECK_MAX_COUNT: u64 = 30;
-const BOOTUP_NIC_CHECK_MAX_QUICK: u64 = 10;
-// During quick retry, we retry every 0.5 second.
-const BOOTUP_NIC_CHECK_INTERVAL_MS_QUICK: u64 = 500;
-// After quick retry, we only retry every 10 seconds.
-const BOOTUP_NIC_CHECK_INTERVAL_SEC_SLOW: u64 = 10;
-
 /// Commander manages all the task managers.
 /// This struct is safe to clone and move to threads
 #[derive(Debug, Clone)]
@@ -29,17 +25,29 @@ pub(crate) struct NipartCommander {
     pub(crate) monitor_manager: NipartMonitorManager,
     pub(crate) conf_manager: NipartConfManager,
     pub(crate) plugin_manager: NipartPluginManager,
+    pub(crate) reconcile_manager: NipartReconcileManager,
+    pub(crate) checkpoint: NipartCheckpointManager,
+    pub(crate) event_emitter: NipartEventEmitter,
 }
 
 impl NipartCommander {
     pub(crate) async fn new(
         sender: UnboundedSender<NipartManagerCmd>,
     ) -> Result<Self, NipartError> {
+        let event_emitter = NipartEventEmitter::new();
         Ok(Self {
-            dhcpv4_manager: NipartDhcpV4Manager::new().await?,
-            monitor_manager: NipartMonitorManager::new(sender.clone()).await?,
+            dhcpv4_manager: NipartDhcpV4Manager::new(event_emitter.clone())
+                .await?,
+            monitor_manager: NipartMonitorManager::new(
+                sender.clone(),
+                event_emitter.clone(),
+            )
+            .await?,
             conf_manager: NipartConfManager::new().await?,
             plugin_manager: NipartPluginManager::new().await?,
+            reconcile_manager: NipartReconcileManager::new(sender).await?,
+            checkpoint: NipartCheckpointManager::new(),
+            event_emitter,
         })
     }
 
@@ -54,7 +62,8 @@ impl NipartCommander {
             log::info!("Saved state is empty");
         } else {
             log::trace!("Loading saved state: {saved_state}");
-            for retry_count in 0..BOOTUP_NIC_CHECK_MAX_COUNT {
+            let conf = NipartDaemonConfManager::get();
+            for retry_count in 0..conf.bootup.nic_check_max_count {
                 let iface_names = get_initialized_nics(&saved_state).await?;
 
                 let nic_ready_state =
@@ -73,6 +82,7 @@ impl NipartCommander {
                         None,
                         nic_ready_state,
                         Default::default(),
+                        None,
                     )
                     .await?;
                     log::debug!("Remaining saved state: {saved_state}");
@@ -82,14 +92,15 @@ impl NipartCommander {
                     break;
                 }
 
-                if retry_count < BOOTUP_NIC_CHECK_MAX_QUICK {
+                // Retry quickly first, then slow down
+                if retry_count < conf.bootup.nic_check_max_quick {
                     tokio::time::sleep(std::time::Duration::from_millis(
-                        BOOTUP_NIC_CHECK_INTERVAL_MS_QUICK,
+                        conf.bootup.nic_check_interval_ms_quick,
                     ))
                     .await;
                 } else {
                     tokio::time::sleep(std::time::Duration::from_secs(
-                        BOOTUP_NIC_CHECK_INTERVAL_SEC_SLOW,
+                        conf.bootup.nic_check_interval_sec_slow,
                     ))
                     .await;
                 }
@@ -97,9 +108,151 @@ impl NipartCommander {
         }
         Ok(())
     }
+
+    /// Reload saved state from file and apply it.
+    /// Caller should hold the [crate::lock::NipartLockManager] lock.
+    pub(crate) async fn reload(&mut self) -> Result<(), NipartError> {
+        self.start_reconcile().await;
+        self.conf_manager.reload().await?;
+        let saved_state = self.conf_manager.query_state().await?;
+        if saved_state.is_empty() {
+            log::info!("Reloaded saved state is empty");
+        } else {
+            log::info!("Applying reloaded saved state");
+            self.apply_network_state(
+                None,
+                saved_state,
+                Default::default(),
+                None,
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Apply saved state to hotplugged NIC matching the saved interface
+    /// identifier.
+    /// Caller should hold the [crate::lock::NipartLockManager] lock.
+    pub(crate) async fn handle_nic_added(
+        &mut self,
+        iface_index: u32,
+    ) -> Result<(), NipartError> {
+        let conf = NipartDaemonConfManager::get();
+        let mut initialized = false;
+        for _ in 0..conf.bootup.nic_check_max_quick.max(1) {
+            if udev_net_device_is_initialized(iface_index) {
+                initialized = true;
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(
+                conf.bootup.nic_check_interval_ms_quick,
+            ))
+            .await;
+        }
+        if !initialized {
+            log::info!(
+                "Hotplugged NIC with interface index {iface_index} is not \
+                 initialized by udev, ignoring"
+            );
+            return Ok(());
+        }
+
+        let mut saved_state = self.conf_manager.query_state().await?;
+        if saved_state.is_empty() {
+            return Ok(());
+        }
+        let cur_state = NipartNoDaemon::query_network_state(
+            NipartstateQueryOption::running(),
+        )
+        .await?;
+        let Some(cur_iface) = cur_state
+            .ifaces
+            .kernel_ifaces
+            .values()
+            .find(|i| i.base_iface().iface_index == Some(iface_index))
+        else {
+            log::debug!(
+                "Hotplugged NIC with interface index {iface_index} is gone"
+            );
+            return Ok(());
+        };
+        let Some(logical_name) = saved_state
+            .ifaces
+            .kernel_ifaces
+            .values()
+            .find(|iface| {
+                !iface.is_virtual()
+                    && iface
+                        .base_iface()
+                        .match_kernel_iface(cur_iface.base_iface())
+            })
+            .map(|iface| iface.name().to_string())
+        else {
+            log::debug!(
+                "Hotplugged NIC {} is not mentioned in saved state",
+                cur_iface.name()
+            );
+            return Ok(());
+        };
+        log::info!(
+            "Applying saved state of interface {logical_name} to hotplugged \
+             NIC {}",
+            cur_iface.name()
+        );
+        let nic_ready_state =
+            remove_ready_state(&mut saved_state, &[logical_name]);
+        self.apply_network_state(
+            None,
+            nic_ready_state,
+            Default::default(),
+            None,
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Forward daemon events to plugins with `capabilities.events` set.
+    /// Do nothing if no plugin wants daemon events.
+    pub(crate) async fn start_plugin_events(
+        &mut self,
+    ) -> Result<(), NipartError> {
+        if !self.plugin_manager.has_event_listener().await? {
+            log::debug!("No plugin wants daemon events");
+            return Ok(());
+        }
+        let mut receiver = self.event_emitter.subscribe();
+        self.monitor_manager.set_emit_all(true).await?;
+        let mut plugin_manager = self.plugin_manager.clone();
+        tokio::spawn(async move {
+            while let Some(event) = receiver.next().await {
+                if let Err(e) = plugin_manager.notify_event(event).await {
+                    log::warn!("Failed to notify plugins on event: {e}");
+                }
+            }
+        });
+        Ok(())
+    }
+
+    /// Stop DHCP threads without releasing leases and request all plugins to
+    /// quit. Caller should hold the [crate::lock::NipartLockManager] lock to
+    /// make sure no on-going transaction.
+    pub(crate) async fn shutdown(&mut self) {
+        if let Ok(Some(id)) = self.checkpoint.pending_id() {
+            log::warn!(
+                "Checkpoint {id} is not committed, the saved state will be \
+                 used on next start"
+            );
+        }
+        if let Err(e) = self.dhcpv4_manager.shutdown().await {
+            log::error!("Failed to stop DHCP: {e}");
+        }
+        if let Err(e) = self.plugin_manager.quit().await {
+            log::error!("Failed to request plugins to quit: {e}");
+        }
+    }
 }
 
-async fn get_initialized_nics(
+pub(crate) async fn get_initialized_nics(
     saved_state: &NetworkState,
 ) -> Result<Vec<String>, NipartError> {
     let cur_state =
@@ -107,15 +260,21 @@ async fn get_initialized_nics(
 
     let mut ret = Vec::new();
 
-    // TODO: Handle [InterfaceIdentifier]
-    for iface_name in saved_state
+    for iface in saved_state
         .ifaces
         .kernel_ifaces
         .values()
         .filter(|i| !i.is_virtual())
-        .map(|i| i.name())
     {
-        if let Some(cur_iface) = cur_state.ifaces.kernel_ifaces.get(iface_name)
+        let iface_name = iface.name();
+        if let Some(cur_iface) =
+            cur_state.ifaces.kernel_ifaces.values().find(|cur_iface| {
+                (iface.base_iface().identifier.is_name()
+                    || !cur_iface.is_virtual())
+                    && iface
+                        .base_iface()
+                        .match_kernel_iface(cur_iface.base_iface())
+            })
             && let Some(cur_iface_index) = cur_iface.base_iface().iface_index
             && udev_net_device_is_initialized(cur_iface_index)
         {
@@ -130,11 +289,14 @@ async fn get_initialized_nics(
     Ok(ret)
 }
 
-fn remove_ready_state(
+pub(crate) fn remove_ready_state(
     state: &mut NetworkState,
     ready_iface_names: &[String],
 ) -> NetworkState {
     let mut ret = NetworkState::default();
+    // Global settings does not depend on any NIC
+    ret.ip_forwarding = state.ip_forwarding.take();
+    ret.plugins = std::mem::take(&mut state.plugins);
     // HashSet of `(iface_name, iface_type)`.
     let mut pending_ifaces: HashSet<(String, Option<InterfaceType>)> =
         HashSet::new();
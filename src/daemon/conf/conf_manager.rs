@@ -1,6 +1,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
-use nipart::{ErrorKind, NetworkState, NipartError, NipartstateInterface};
+use nipart::{
+    ErrorKind, NetworkState, NipartError, NipartHistoryEntry, NipartUuid,
+    NipartstateInterface,
+};
 
 use super::{NipartConfCmd, NipartConfReply, NipartConfWorker};
 use crate::TaskManager;
@@ -17,17 +20,31 @@ impl NipartConfManager {
         })
     }
 
-    /// Override saved state
+    /// Override saved state, return the generation number if generations
+    /// enabled.
     pub(crate) async fn save_state(
         &mut self,
         mut state: NetworkState,
-    ) -> Result<(), NipartError> {
+    ) -> Result<Option<u64>, NipartError> {
         // Should remove interface index
         for iface in state.ifaces.kernel_ifaces.values_mut() {
             iface.base_iface_mut().iface_index = None;
         }
 
-        self.mgr.exec(NipartConfCmd::SaveState(Box::new(state))).await?;
+        let reply = self
+            .mgr
+            .exec(NipartConfCmd::SaveState(Box::new(state)))
+            .await?;
+        if let NipartConfReply::Generation(generation) = reply {
+            Ok(Some(generation))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Reload saved state from file
+    pub(crate) async fn reload(&mut self) -> Result<(), NipartError> {
+        self.mgr.exec(NipartConfCmd::Reload).await?;
         Ok(())
     }
 
@@ -47,4 +64,56 @@ impl NipartConfManager {
             ))
         }
     }
+
+    /// Query specified generation of saved state
+    pub(crate) async fn query_generation(
+        &mut self,
+        generation: u64,
+    ) -> Result<NetworkState, NipartError> {
+        let reply = self
+            .mgr
+            .exec(NipartConfCmd::QueryGeneration(generation))
+            .await?;
+        if let NipartConfReply::State(s) = reply {
+            Ok(*s)
+        } else {
+            Err(NipartError::new(
+                ErrorKind::Bug,
+                format!(
+                    "NipartConfCmd::QueryGeneration is not replying with \
+                     NipartConfReply::State, but {reply:?}"
+                ),
+            ))
+        }
+    }
+
+    /// Store apply transaction into history
+    pub(crate) async fn save_history(
+        &mut self,
+        entry: NipartHistoryEntry,
+    ) -> Result<(), NipartError> {
+        self.mgr
+            .exec(NipartConfCmd::SaveHistory(Box::new(entry)))
+            .await?;
+        Ok(())
+    }
+
+    /// Query all history entries oldest first, or only the specified one
+    pub(crate) async fn query_history(
+        &mut self,
+        id: Option<NipartUuid>,
+    ) -> Result<Vec<NipartHistoryEntry>, NipartError> {
+        let reply = self.mgr.exec(NipartConfCmd::QueryHistory(id)).await?;
+        if let NipartConfReply::History(entries) = reply {
+            Ok(entries)
+        } else {
+            Err(NipartError::new(
+                ErrorKind::Bug,
+                format!(
+                    "NipartConfCmd::QueryHistory is not replying with \
+                     NipartConfReply::History, but {reply:?}"
+                ),
+            ))
+        }
+    }
 }
@@ -3,16 +3,28 @@
 use std::os::unix::fs::PermissionsExt;
 
 use futures_channel::{mpsc::UnboundedReceiver, oneshot::Sender};
-use nipart::{ErrorKind, InterfaceType, NetworkState, NipartError, NipartstateInterface};
+use nipart::{
+    ErrorKind, InterfaceType, NetworkState, NipartError, NipartHistoryEntry,
+    NipartUuid, NipartstateInterface,
+};
 use tokio::{fs::File, io::AsyncWriteExt};
 
-use crate::TaskWorker;
+use crate::{TaskWorker, daemon_conf::NipartDaemonConfManager};
 
 #[derive(Debug, Clone, PartialEq)]
 pub(crate) enum NipartConfCmd {
-    /// Override saved network state
+    /// Override saved network state and store it as new generation
     SaveState(Box<NetworkState>),
     QueryState,
+    /// Discard cached saved state and read it from file again
+    Reload,
+    /// Store apply transaction into history and remove oldest entries
+    /// exceeding `apply.history-size`.
+    SaveHistory(Box<NipartHistoryEntry>),
+    /// Query all history entries or only the specified one
+    QueryHistory(Option<NipartUuid>),
+    /// Query specified generation of saved state
+    QueryGeneration(u64),
 }
 
 impl std::fmt::Display for NipartConfCmd {
@@ -24,6 +36,21 @@ impl std::fmt::Display for NipartConfCmd {
             Self::QueryState => {
                 write!(f, "query-state")
             }
+            Self::Reload => {
+                write!(f, "reload")
+            }
+            Self::SaveHistory(entry) => {
+                write!(f, "save-history:{}", entry.id)
+            }
+            Self::QueryHistory(Some(id)) => {
+                write!(f, "query-history:{id}")
+            }
+            Self::QueryHistory(None) => {
+                write!(f, "query-history")
+            }
+            Self::QueryGeneration(generation) => {
+                write!(f, "query-generation:{generation}")
+            }
         }
     }
 }
@@ -32,15 +59,17 @@ impl std::fmt::Display for NipartConfCmd {
 pub(crate) enum NipartConfReply {
     None,
     State(Box<NetworkState>),
+    History(Vec<NipartHistoryEntry>),
+    /// Generation of saved state
+    Generation(u64),
 }
 
 type FromManager = (NipartConfCmd, Sender<Result<NipartConfReply, NipartError>>);
 
-const INTERNAL_STATE_DIR: &str = "/etc/nipart/states/internal";
-const APPLIED_STATE_PATH: &str =
-    "/etc/nipart/states/internal/applied.yml";
-const APPLIED_SECRETS_PATH: &str =
-    "/etc/nipart/states/internal/applied.secrets.yml";
+const APPLIED_STATE_FILE_NAME: &str = "applied.yml";
+const APPLIED_SECRETS_FILE_NAME: &str = "applied.secrets.yml";
+const HISTORY_DIR_NAME: &str = "history";
+const GENERATION_DIR_NAME: &str = "generations";
 
 #[derive(Debug)]
 pub(crate) struct NipartConfWorker {
@@ -74,44 +103,91 @@ impl TaskWorker for NipartConfWorker {
             NipartConfCmd::SaveState(mut state) => {
                 discard_absent_iface(&mut state);
                 save_state_to_file(&state).await?;
+                // Reapplying saved state on boot, reload or NIC hotplug
+                // changes nothing, storing it would evict real undo points.
+                // The saved state is already persisted, failure of storing
+                // generation should not fail the apply.
+                let generation = if *state == self.saved_state {
+                    log::debug!("Saved state unchanged, no new generation");
+                    None
+                } else {
+                    match save_generation_to_file(&state).await {
+                        Ok(g) => g,
+                        Err(e) => {
+                            log::warn!(
+                                "Failed to store generation of saved state: \
+                                 {e}"
+                            );
+                            None
+                        }
+                    }
+                };
                 self.saved_state = *state;
-                Ok(NipartConfReply::None)
+                Ok(match generation {
+                    Some(g) => NipartConfReply::Generation(g),
+                    None => NipartConfReply::None,
+                })
             }
             NipartConfCmd::QueryState => {
                 Ok(NipartConfReply::State(Box::new(self.saved_state.clone())))
             }
+            NipartConfCmd::Reload => {
+                self.saved_state = read_state_from_file()?;
+                Ok(NipartConfReply::None)
+            }
+            NipartConfCmd::SaveHistory(entry) => {
+                save_history_to_file(&entry).await?;
+                Ok(NipartConfReply::None)
+            }
+            NipartConfCmd::QueryHistory(id) => {
+                Ok(NipartConfReply::History(read_history_from_file(id)?))
+            }
+            NipartConfCmd::QueryGeneration(generation) => {
+                let state = read_generation_from_file(generation)?;
+                Ok(NipartConfReply::State(Box::new(state)))
+            }
         }
     }
 }
 
+// Return (state_path, secrets_path) in `state-dir` of daemon configuration
+fn applied_state_paths() -> (String, String) {
+    let state_dir = NipartDaemonConfManager::get().state_dir.clone();
+    (
+        format!("{state_dir}/{APPLIED_STATE_FILE_NAME}"),
+        format!("{state_dir}/{APPLIED_SECRETS_FILE_NAME}"),
+    )
+}
+
 fn read_state_from_file() -> Result<NetworkState, NipartError> {
-    let content = if std::path::Path::new(APPLIED_STATE_PATH).exists() {
-        match std::fs::read_to_string(APPLIED_STATE_PATH) {
+    let (state_path, secrets_path) = applied_state_paths();
+    let content = if std::path::Path::new(&state_path).exists() {
+        match std::fs::read_to_string(&state_path) {
             Ok(s) => s,
             Err(e) => {
                 log::debug!(
-                    "Failed to load saved state from {APPLIED_STATE_PATH}: {e}"
+                    "Failed to load saved state from {state_path}: {e}"
                 );
                 return Ok(NetworkState::default());
             }
         }
     } else {
-        log::debug!("Saved state file {APPLIED_STATE_PATH} does not exist");
+        log::debug!("Saved state file {state_path} does not exist");
         return Ok(NetworkState::default());
     };
     let mut state = match serde_yaml::from_str::<NetworkState>(&content) {
         Ok(s) => s,
         Err(e) => {
             log::debug!(
-                "Deleting corrupted saved state file {APPLIED_STATE_PATH}: {e}"
+                "Deleting corrupted saved state file {state_path}: {e}"
             );
-            std::fs::remove_file(APPLIED_STATE_PATH).ok();
+            std::fs::remove_file(&state_path).ok();
             NetworkState::default()
         }
     };
 
-    if std::path::Path::new(APPLIED_SECRETS_PATH).exists() {
-        if let Ok(secrets) = std::fs::read_to_string(APPLIED_SECRETS_PATH) {
+    if std::path::Path::new(&secrets_path).exists() {
+        if let Ok(secrets) = std::fs::read_to_string(&secrets_path) {
             match serde_yaml::from_str::<NetworkState>(&secrets) {
                 Ok(s) => {
                     if let Err(e) = state.merge(&s) {
@@ -125,9 +201,9 @@ fn read_state_from_file() -> Result<NetworkState, NipartError> {
                 Err(e) => {
                     log::debug!(
                         "Deleting corrupted saved secrets file \
-                         {APPLIED_SECRETS_PATH}: {e}"
+                         {secrets_path}: {e}"
                     );
-                    std::fs::remove_file(APPLIED_SECRETS_PATH).ok();
+                    std::fs::remove_file(&secrets_path).ok();
                 }
             };
         }
@@ -138,8 +214,16 @@ fn read_state_from_file() -> Result<NetworkState, NipartError> {
 
 async fn save_state_to_file(net_state: &NetworkState) -> Result<(), NipartError> {
     create_instal_state_dir()?;
+    let (state_path, secrets_path) = applied_state_paths();
     log::trace!("Saving state {net_state}");
+    write_state_files(net_state, &state_path, &secrets_path).await
+}
 
+async fn write_state_files(
+    net_state: &NetworkState,
+    state_path: &str,
+    secrets_path: &str,
+) -> Result<(), NipartError> {
     let mut state = net_state.clone();
     let secret_state = state.hide_secrets();
 
@@ -157,22 +241,238 @@ async fn save_state_to_file(net_state: &NetworkState) -> Result<(), NipartError>
             )
         })?;
 
-    let mut fd = File::create(APPLIED_STATE_PATH).await?;
+    let mut fd = File::create(state_path).await?;
     fd.set_permissions(PermissionsExt::from_mode(0o644)).await?;
     fd.write_all(state_yaml_str.as_bytes()).await?;
 
     // We should remove the file first to make sure newly created
-    // `APPLIED_SECRETS_PATH` is own by daemon uid.
-    std::fs::remove_file(APPLIED_SECRETS_PATH).ok();
-    let mut fd = File::create(APPLIED_SECRETS_PATH).await?;
+    // `secrets_path` is own by daemon uid.
+    std::fs::remove_file(secrets_path).ok();
+    let mut fd = File::create(secrets_path).await?;
     fd.set_permissions(PermissionsExt::from_mode(0o600)).await?;
     fd.write_all(secret_yaml_str.as_bytes()).await?;
 
     Ok(())
 }
 
+// Return (state_path, secrets_path) of specified generation
+fn generation_paths(generation: u64) -> (String, String) {
+    let dir = generation_dir();
+    (
+        format!("{dir}/{generation}.yml"),
+        format!("{dir}/{generation}.secrets.yml"),
+    )
+}
+
+fn generation_dir() -> String {
+    format!(
+        "{}/{GENERATION_DIR_NAME}",
+        NipartDaemonConfManager::get().state_dir
+    )
+}
+
+// Sorted from oldest to newest
+fn generations() -> Result<Vec<u64>, NipartError> {
+    let dir = generation_dir();
+    if !std::path::Path::new(&dir).exists() {
+        return Ok(Vec::new());
+    }
+    let mut ret: Vec<u64> = std::fs::read_dir(&dir)
+        .map_err(|e| {
+            NipartError::new(
+                ErrorKind::DaemonFailure,
+                format!("Failed to read dir {dir}: {e}"),
+            )
+        })?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter_map(|name| {
+            name.strip_suffix(".yml")
+                .and_then(|g| g.parse::<u64>().ok())
+        })
+        .collect();
+    ret.sort_unstable();
+    Ok(ret)
+}
+
+// Store saved state as new generation and remove oldest generations
+// exceeding `apply.generation-size`. Return None if generation disabled.
+async fn save_generation_to_file(
+    net_state: &NetworkState,
+) -> Result<Option<u64>, NipartError> {
+    let generation_size = NipartDaemonConfManager::get().apply.generation_size;
+    if generation_size == 0 {
+        return Ok(None);
+    }
+    let dir = generation_dir();
+    std::fs::create_dir_all(&dir).map_err(|e| {
+        NipartError::new(
+            ErrorKind::DaemonFailure,
+            format!("Failed to create dir {dir}: {e}"),
+        )
+    })?;
+    let mut generations = generations()?;
+    let generation = generations.last().copied().unwrap_or_default() + 1;
+    let (state_path, secrets_path) = generation_paths(generation);
+    write_state_files(net_state, &state_path, &secrets_path).await?;
+    generations.push(generation);
+
+    if generations.len() > generation_size {
+        for old in &generations[..generations.len() - generation_size] {
+            log::debug!("Removing expired generation {old} of saved state");
+            let (state_path, secrets_path) = generation_paths(*old);
+            std::fs::remove_file(state_path).ok();
+            std::fs::remove_file(secrets_path).ok();
+        }
+    }
+    Ok(Some(generation))
+}
+
+// Unlike `read_state_from_file()`, failure is not ignored because restoring
+// an incomplete state would remove interfaces.
+fn read_generation_from_file(
+    generation: u64,
+) -> Result<NetworkState, NipartError> {
+    let (state_path, secrets_path) = generation_paths(generation);
+    if !std::path::Path::new(&state_path).exists() {
+        return Err(NipartError::new(
+            ErrorKind::InvalidArgument,
+            format!("Generation {generation} of saved state not found"),
+        ));
+    }
+    let read_file = |path: &str| -> Result<NetworkState, NipartError> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            NipartError::new(
+                ErrorKind::DaemonFailure,
+                format!("Failed to read {path}: {e}"),
+            )
+        })?;
+        serde_yaml::from_str(&content).map_err(|e| {
+            NipartError::new(
+                ErrorKind::DaemonFailure,
+                format!("Corrupted saved state file {path}: {e}"),
+            )
+        })
+    };
+    let mut state = read_file(&state_path)?;
+    if std::path::Path::new(&secrets_path).exists() {
+        state.merge(&read_file(&secrets_path)?)?;
+    }
+    Ok(state)
+}
+
+fn history_dir() -> String {
+    format!(
+        "{}/{HISTORY_DIR_NAME}",
+        NipartDaemonConfManager::get().state_dir
+    )
+}
+
+// UUID v7 string sort in creation order, hence file names sort from oldest
+// to newest.
+fn history_file_names() -> Result<Vec<String>, NipartError> {
+    let dir = history_dir();
+    if !std::path::Path::new(&dir).exists() {
+        return Ok(Vec::new());
+    }
+    let mut ret: Vec<String> = std::fs::read_dir(&dir)
+        .map_err(|e| {
+            NipartError::new(
+                ErrorKind::DaemonFailure,
+                format!("Failed to read dir {dir}: {e}"),
+            )
+        })?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| name.ends_with(".yml"))
+        .collect();
+    ret.sort_unstable();
+    Ok(ret)
+}
+
+async fn save_history_to_file(
+    entry: &NipartHistoryEntry,
+) -> Result<(), NipartError> {
+    let history_size = NipartDaemonConfManager::get().apply.history_size;
+    if history_size == 0 {
+        return Ok(());
+    }
+    create_instal_state_dir()?;
+    let dir = history_dir();
+    std::fs::create_dir_all(&dir).map_err(|e| {
+        NipartError::new(
+            ErrorKind::DaemonFailure,
+            format!("Failed to create dir {dir}: {e}"),
+        )
+    })?;
+
+    // Secrets are hidden from states, but error message might still quote
+    // desired or current values, hence only root could read it.
+    let yaml_str = serde_yaml::to_string(entry).map_err(|e| {
+        NipartError::new(
+            ErrorKind::Bug,
+            format!("Failed to generate YAML for {entry}: {e}"),
+        )
+    })?;
+    let mut fd = File::create(format!("{dir}/{}.yml", entry.id)).await?;
+    fd.set_permissions(PermissionsExt::from_mode(0o600)).await?;
+    fd.write_all(yaml_str.as_bytes()).await?;
+
+    let file_names = history_file_names()?;
+    if file_names.len() > history_size {
+        for file_name in &file_names[..file_names.len() - history_size] {
+            log::debug!("Removing expired history {dir}/{file_name}");
+            std::fs::remove_file(format!("{dir}/{file_name}")).ok();
+        }
+    }
+    Ok(())
+}
+
+fn read_history_from_file(
+    id: Option<NipartUuid>,
+) -> Result<Vec<NipartHistoryEntry>, NipartError> {
+    let dir = history_dir();
+    let file_names = if let Some(id) = id {
+        let file_name = format!("{id}.yml");
+        if !std::path::Path::new(&format!("{dir}/{file_name}")).exists() {
+            return Err(NipartError::new(
+                ErrorKind::InvalidArgument,
+                format!("Transaction {id} not found in history"),
+            ));
+        }
+        vec![file_name]
+    } else {
+        history_file_names()?
+    };
+
+    let mut ret = Vec::new();
+    for file_name in file_names {
+        let path = format!("{dir}/{file_name}");
+        let entry = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| {
+                serde_yaml::from_str::<NipartHistoryEntry>(&content)
+                    .map_err(|e| e.to_string())
+            });
+        match entry {
+            Ok(entry) => ret.push(entry),
+            Err(e) if id.is_some() => {
+                return Err(NipartError::new(
+                    ErrorKind::DaemonFailure,
+                    format!("Corrupted history file {path}: {e}"),
+                ));
+            }
+            Err(e) => {
+                log::debug!("Ignoring corrupted history file {path}: {e}");
+            }
+        }
+    }
+    Ok(ret)
+}
+
 fn create_instal_state_dir() -> Result<(), NipartError> {
-    let dir_path = std::path::Path::new(INTERNAL_STATE_DIR);
+    let state_dir = NipartDaemonConfManager::get().state_dir.clone();
+    let dir_path = std::path::Path::new(&state_dir);
     if !dir_path.exists() {
         log::debug!("Creating dir {}", dir_path.display());
         std::fs::create_dir_all(dir_path).map_err(|e| {
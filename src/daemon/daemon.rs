@@ -4,24 +4,37 @@ use std::{fs::Permissions, os::unix::fs::PermissionsExt};
 
 use futures_channel::mpsc::{UnboundedReceiver, unbounded};
 use futures_util::stream::StreamExt;
-use nipart::{
-    ErrorKind, NipartClient, NipartError, NipartIpcConnection,
-    NipartIpcListener,
+use nipart::{ErrorKind, NipartError, NipartIpcConnection, NipartIpcListener};
+use tokio::{
+    signal::unix::{Signal, SignalKind, signal},
+    time::Interval,
 };
 
 use super::{
-    api::process_api_connection, commander::NipartCommander,
+    api::process_api_connection,
+    commander::NipartCommander,
+    daemon_conf::NipartDaemonConfManager,
+    dbus::setup_dbus_service,
     event::NipartLinkEvent,
+    lock::NipartLockManager,
+    systemd::{sd_listen_socket, sd_notify, sd_watchdog_interval},
 };
 
 #[derive(Debug, Clone)]
 pub(crate) enum NipartManagerCmd {
     LinkEvent(Box<NipartLinkEvent>),
+    /// Check and fix drift of network state from saved state
+    Reconcile,
+    /// New NIC with specified interface index showing up
+    NicAdded(u32),
 }
 
 #[derive(Debug)]
 pub(crate) struct NipartDaemon {
     api_ipc: NipartIpcListener,
+    api_socket_path: String,
+    // Whether `api_ipc` is passed in by systemd socket activation
+    socket_activated: bool,
     managers_ipc: UnboundedReceiver<NipartManagerCmd>,
     // Daemon will fork(tokio is controlling maximum threads) new thread for
     // each client connection, this commander will be cloned and move to all
@@ -31,28 +44,38 @@ pub(crate) struct NipartDaemon {
 
 impl NipartDaemon {
     pub(crate) async fn new() -> Result<Self, NipartError> {
-        let api_ipc =
-            NipartIpcListener::new(NipartClient::DEFAULT_SOCKET_PATH)?;
-        // Make the API IPC globally read and writable for non-root user to
-        // query and ping
-        std::fs::set_permissions(
-            NipartClient::DEFAULT_SOCKET_PATH,
-            Permissions::from_mode(0o0666),
-        )
-        .map_err(|e| {
-            NipartError::new(
-                ErrorKind::Bug,
-                format!(
-                    "Failed to set permission of {} to 0666: {e}",
-                    NipartClient::DEFAULT_SOCKET_PATH
+        let daemon_conf = NipartDaemonConfManager::load()?;
+        let (api_ipc, api_socket_path, socket_activated) =
+            match sd_listen_socket()? {
+                Some(listener) => {
+                    log::info!("Using API socket passed in by systemd");
+                    let path = listener
+                        .local_addr()
+                        .ok()
+                        .and_then(|a| {
+                            a.as_pathname().map(|p| p.display().to_string())
+                        })
+                        .unwrap_or_else(|| daemon_conf.api.socket_path.clone());
+                    (NipartIpcListener::from_std(&path, listener)?, path, true)
+                }
+                None => (
+                    create_api_ipc(&daemon_conf.api.socket_path)?,
+                    daemon_conf.api.socket_path.clone(),
+                    false,
                 ),
-            )
-        })?;
+            };
 
         let (sender, receiver) = unbounded::<NipartManagerCmd>();
 
         let commander = NipartCommander::new(sender).await?;
+        if let Err(e) = setup_dbus_service(commander.clone()).await {
+            log::error!("Failed to start D-Bus service: {e}");
+        }
+        if let Err(e) = commander.clone().start_plugin_events().await {
+            log::error!("Failed to forward daemon events to plugins: {e}");
+        }
         // Start a thread to load saved state instead of hanging
+        sd_notify("STATUS=Loading saved state");
         let mut new_commander = commander.clone();
         tokio::spawn(async move {
             if let Err(e) = new_commander.load_saved_state().await {
@@ -61,10 +84,21 @@ impl NipartDaemon {
                      state"
                 );
             }
+            // Start reconciler after saved state loaded to avoid fixing
+            // drift while bootup apply still in progress
+            new_commander.start_reconcile().await;
+            if let Err(e) =
+                new_commander.monitor_manager.set_hotplug(true).await
+            {
+                log::error!("Failed to start NIC hotplug monitor: {e}");
+            }
+            sd_notify("READY=1\nSTATUS=Running");
         });
 
         Ok(Self {
             api_ipc,
+            api_socket_path,
+            socket_activated,
             commander,
             managers_ipc: receiver,
         })
@@ -72,6 +106,14 @@ impl NipartDaemon {
 
     /// Please run this function in a thread
     pub(crate) async fn run(&mut self) {
+        // Keep serving API even without signal handling
+        let mut sigterm = signal(SignalKind::terminate())
+            .map_err(|e| log::error!("Failed to listen on SIGTERM: {e}"))
+            .ok();
+        let mut sighup = signal(SignalKind::hangup())
+            .map_err(|e| log::error!("Failed to listen on SIGHUP: {e}"))
+            .ok();
+        let mut watchdog = sd_watchdog_interval().map(tokio::time::interval);
         loop {
             tokio::select! {
                 result = self.api_ipc.accept() => {
@@ -85,19 +127,113 @@ impl NipartDaemon {
                         }
                     }
                 },
-                // TODO(Gris Ge): Handle TERM signal here:
-                //  * Request plugin to quit
+                _ = watchdog_tick(&mut watchdog) => {
+                    sd_notify("WATCHDOG=1");
+                },
+                _ = signal_recv(&mut sigterm) => {
+                    log::info!("Got SIGTERM, shutting down");
+                    self.shutdown().await;
+                    break;
+                },
+                _ = signal_recv(&mut sighup) => {
+                    log::info!("Got SIGHUP, reloading");
+                    self.reload();
+                },
                 else => break,
             }
         }
     }
 
+    async fn shutdown(&mut self) {
+        // Wait on-going transaction to finish and hold the lock till process
+        // exit to block new transactions.
+        sd_notify("STOPPING=1\nSTATUS=Waiting on-going transaction");
+        let lock = NipartLockManager::lock(std::process::id() as i32).await;
+        sd_notify("STATUS=Shutting down");
+        self.commander.shutdown().await;
+        // The socket passed in by systemd should be removed by systemd
+        if !self.socket_activated
+            && let Err(e) = std::fs::remove_file(&self.api_socket_path)
+        {
+            log::warn!("Failed to remove socket {}: {e}", self.api_socket_path);
+        }
+        // Intentionally leak the lock as daemon is quitting.
+        std::mem::forget(lock);
+    }
+
+    // Reload in a thread to keep accepting API connections
+    fn reload(&self) {
+        let mut commander = self.commander.clone();
+        let api_socket_path = self.api_socket_path.clone();
+        tokio::spawn(async move {
+            sd_notify("RELOADING=1\nSTATUS=Reloading");
+            let lock = NipartLockManager::lock(std::process::id() as i32).await;
+            // Keep using previous configuration on failure
+            match NipartDaemonConfManager::load() {
+                Ok(conf) => {
+                    if conf.api.socket_path != api_socket_path {
+                        log::warn!(
+                            "Daemon restart is required to change API socket \
+                             path from {api_socket_path} to {}",
+                            conf.api.socket_path
+                        );
+                    }
+                }
+                Err(e) => {
+                    log::error!(
+                        "Failed to reload daemon configuration, keep using \
+                         previous one: {e}"
+                    );
+                }
+            }
+            if let Err(e) = commander.reload().await {
+                log::error!("Failed to reload: {e}");
+            }
+            if let Err(e) = setup_dbus_service(commander.clone()).await {
+                log::error!("Failed to reload D-Bus service: {e}");
+            }
+            drop(lock);
+            sd_notify("READY=1\nSTATUS=Running");
+        });
+    }
+
+    // Reconcile in a thread to keep accepting API connections
+    fn reconcile(&self) {
+        let mut commander = self.commander.clone();
+        tokio::spawn(async move {
+            let lock = NipartLockManager::lock(std::process::id() as i32).await;
+            if let Err(e) = commander.reconcile().await {
+                log::error!("Failed to reconcile: {e}");
+            }
+            drop(lock);
+        });
+    }
+
+    // Apply saved state to hotplugged NIC in a thread to keep accepting API
+    // connections
+    fn handle_nic_added(&self, iface_index: u32) {
+        let mut commander = self.commander.clone();
+        tokio::spawn(async move {
+            let lock = NipartLockManager::lock(std::process::id() as i32).await;
+            if let Err(e) = commander.handle_nic_added(iface_index).await {
+                log::error!(
+                    "Failed to apply saved state to hotplugged NIC with \
+                     interface index {iface_index}: {e}"
+                );
+            }
+            drop(lock);
+        });
+    }
+
     async fn handle_api_connection(
         &mut self,
         result: Result<NipartIpcConnection, NipartError>,
     ) {
         match result {
-            Ok(conn) => {
+            Ok(mut conn) => {
+                conn.set_max_size(
+                    NipartDaemonConfManager::get().api.ipc_max_size,
+                );
                 let commander = self.commander.clone();
                 tokio::spawn(async move {
                     process_api_connection(conn, commander).await
@@ -117,7 +253,46 @@ impl NipartDaemon {
             NipartManagerCmd::LinkEvent(event) => {
                 self.commander.handle_link_event(*event).await?
             }
+            NipartManagerCmd::Reconcile => self.reconcile(),
+            NipartManagerCmd::NicAdded(iface_index) => {
+                self.handle_nic_added(iface_index)
+            }
         }
         Ok(())
     }
 }
+
+fn create_api_ipc(path: &str) -> Result<NipartIpcListener, NipartError> {
+    let api_ipc = NipartIpcListener::new(path)?;
+    // Make the API IPC globally read and writable for non-root user to
+    // query and ping
+    std::fs::set_permissions(path, Permissions::from_mode(0o0666)).map_err(
+        |e| {
+            NipartError::new(
+                ErrorKind::Bug,
+                format!("Failed to set permission of {path} to 0666: {e}"),
+            )
+        },
+    )?;
+    Ok(api_ipc)
+}
+
+// Never complete when failed to listen on the signal
+async fn signal_recv(signal: &mut Option<Signal>) {
+    match signal {
+        Some(signal) => {
+            signal.recv().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+// Never complete when systemd watchdog is disabled
+async fn watchdog_tick(watchdog: &mut Option<Interval>) {
+    match watchdog {
+        Some(interval) => {
+            interval.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
@@ -0,0 +1,370 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+};
+
+use nipart::{ErrorKind, NipartDaemonConf, NipartDaemonDbusBus, NipartError};
+
+const DROP_IN_FILE_EXTENSION: &str = "conf";
+// Smaller than this could not even hold the reply of ping
+const IPC_MIN_SIZE: usize = 1024;
+const DBUS_SESSION_BUS_ADDRESS_ENV: &str = "DBUS_SESSION_BUS_ADDRESS";
+
+static DAEMON_CONF: RwLock<Option<Arc<NipartDaemonConf>>> = RwLock::new(None);
+
+#[derive(Debug, Clone)]
+pub(crate) struct NipartDaemonConfManager;
+
+impl NipartDaemonConfManager {
+    /// Daemon configuration currently in use, default configuration if
+    /// not loaded yet.
+    pub(crate) fn get() -> Arc<NipartDaemonConf> {
+        match DAEMON_CONF.read() {
+            Ok(conf) => conf.clone().unwrap_or_default(),
+            Err(e) => e.into_inner().clone().unwrap_or_default(),
+        }
+    }
+
+    /// Read and validate daemon configuration files, only replace the
+    /// configuration in use when succeeded.
+    pub(crate) fn load() -> Result<Arc<NipartDaemonConf>, NipartError> {
+        let conf = Arc::new(read_conf_files(
+            Path::new(NipartDaemonConf::PATH),
+            Path::new(NipartDaemonConf::DROP_IN_DIR),
+        )?);
+        validate_conf(&conf)?;
+        log::debug!("Loaded daemon configuration {conf}");
+        match DAEMON_CONF.write() {
+            Ok(mut cur) => *cur = Some(conf.clone()),
+            Err(e) => *e.into_inner() = Some(conf.clone()),
+        }
+        Ok(conf)
+    }
+}
+
+/// The drop-in files are merged into main configuration file in alphabetical
+/// order of file name. Mappings are merged recursively, other values
+/// are overridden.
+fn read_conf_files(
+    path: &Path,
+    drop_in_dir: &Path,
+) -> Result<NipartDaemonConf, NipartError> {
+    let mut merged = serde_yaml::Value::Null;
+    if path.exists() {
+        merge_yaml(&mut merged, read_yaml_file(path)?);
+    } else {
+        log::debug!("Daemon configuration file {} not found", path.display());
+    }
+
+    if drop_in_dir.is_dir() {
+        let mut drop_in_files: Vec<PathBuf> = std::fs::read_dir(drop_in_dir)
+            .map_err(|e| {
+                NipartError::new(
+                    ErrorKind::InvalidArgument,
+                    format!(
+                        "Failed to read folder {}: {e}",
+                        drop_in_dir.display()
+                    ),
+                )
+            })?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|p| {
+                p.is_file()
+                    && p.extension().and_then(|e| e.to_str())
+                        == Some(DROP_IN_FILE_EXTENSION)
+            })
+            .collect();
+        drop_in_files.sort_unstable();
+        for file in drop_in_files {
+            merge_yaml(&mut merged, read_yaml_file(&file)?);
+        }
+    }
+
+    if merged.is_null() {
+        Ok(NipartDaemonConf::default())
+    } else {
+        serde_yaml::from_value(merged).map_err(|e| {
+            NipartError::new(
+                ErrorKind::InvalidArgument,
+                format!("Invalid daemon configuration: {e}"),
+            )
+        })
+    }
+}
+
+fn read_yaml_file(path: &Path) -> Result<serde_yaml::Value, NipartError> {
+    let content = std::fs::read_to_string(path).map_err(|e| {
+        NipartError::new(
+            ErrorKind::InvalidArgument,
+            format!("Failed to read {}: {e}", path.display()),
+        )
+    })?;
+    serde_yaml::from_str(&content).map_err(|e| {
+        NipartError::new(
+            ErrorKind::InvalidArgument,
+            format!("Invalid YAML in {}: {e}", path.display()),
+        )
+    })
+}
+
+fn merge_yaml(base: &mut serde_yaml::Value, new: serde_yaml::Value) {
+    match (base, new) {
+        // Empty file
+        (_, serde_yaml::Value::Null) => (),
+        (
+            serde_yaml::Value::Mapping(base_map),
+            serde_yaml::Value::Mapping(new_map),
+        ) => {
+            for (key, value) in new_map {
+                match base_map.get_mut(&key) {
+                    Some(base_value) => merge_yaml(base_value, value),
+                    None => {
+                        base_map.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, new) => *base = new,
+    }
+}
+
+fn validate_conf(conf: &NipartDaemonConf) -> Result<(), NipartError> {
+    let mut abs_paths = vec![
+        ("api.socket-path", conf.api.socket_path.as_str()),
+        ("plugin.socket-dir", conf.plugin.socket_dir.as_str()),
+        ("state-dir", conf.state_dir.as_str()),
+    ];
+    if let Some(dirs) = conf.plugin.search_dirs.as_ref() {
+        if dirs.is_empty() {
+            return Err(NipartError::new(
+                ErrorKind::InvalidArgument,
+                "Invalid daemon configuration: plugin.search-dirs should not \
+                 be empty list"
+                    .to_string(),
+            ));
+        }
+        for dir in dirs {
+            abs_paths.push(("plugin.search-dirs", dir.as_str()));
+        }
+    }
+    for (name, path) in abs_paths {
+        if !Path::new(path).is_absolute() {
+            return Err(NipartError::new(
+                ErrorKind::InvalidArgument,
+                format!(
+                    "Invalid daemon configuration: {name} should be absolute \
+                     path, but got '{path}'"
+                ),
+            ));
+        }
+    }
+
+    for (name, value) in [
+        (
+            "bootup.nic-check-max-count",
+            conf.bootup.nic_check_max_count,
+        ),
+        (
+            "bootup.nic-check-interval-ms-quick",
+            conf.bootup.nic_check_interval_ms_quick,
+        ),
+        (
+            "bootup.nic-check-interval-sec-slow",
+            conf.bootup.nic_check_interval_sec_slow,
+        ),
+        (
+            "apply.verify-retry-count",
+            conf.apply.verify_retry_count as u64,
+        ),
+        (
+            "plugin.connect-retry-count",
+            conf.plugin.connect_retry_count.into(),
+        ),
+        ("plugin.call-timeout-ms", conf.plugin.call_timeout_ms.into()),
+        (
+            "plugin.restart-interval-ms-initial",
+            conf.plugin.restart_interval_ms_initial,
+        ),
+        (
+            "reconcile.min-interval-sec",
+            conf.reconcile.min_interval_sec,
+        ),
+    ] {
+        if value == 0 {
+            return Err(NipartError::new(
+                ErrorKind::InvalidArgument,
+                format!(
+                    "Invalid daemon configuration: {name} should be bigger \
+                     than 0"
+                ),
+            ));
+        }
+    }
+
+    if conf.bootup.nic_check_max_quick > conf.bootup.nic_check_max_count {
+        return Err(NipartError::new(
+            ErrorKind::InvalidArgument,
+            format!(
+                "Invalid daemon configuration: bootup.nic-check-max-quick \
+                 {} should not be bigger than bootup.nic-check-max-count {}",
+                conf.bootup.nic_check_max_quick,
+                conf.bootup.nic_check_max_count
+            ),
+        ));
+    }
+
+    if conf.plugin.restart_interval_ms_initial
+        > conf.plugin.restart_interval_sec_max.saturating_mul(1000)
+    {
+        return Err(NipartError::new(
+            ErrorKind::InvalidArgument,
+            format!(
+                "Invalid daemon configuration: \
+                 plugin.restart-interval-ms-initial {} should not be bigger \
+                 than plugin.restart-interval-sec-max {} seconds",
+                conf.plugin.restart_interval_ms_initial,
+                conf.plugin.restart_interval_sec_max
+            ),
+        ));
+    }
+
+    if conf.dbus.enabled
+        && conf.dbus.bus == NipartDaemonDbusBus::Session
+        && std::env::var_os(DBUS_SESSION_BUS_ADDRESS_ENV).is_none()
+    {
+        return Err(NipartError::new(
+            ErrorKind::InvalidArgument,
+            format!(
+                "Invalid daemon configuration: dbus.bus session requires \
+                 {DBUS_SESSION_BUS_ADDRESS_ENV} environment variable"
+            ),
+        ));
+    }
+
+    // The IPC message size is stored in u32
+    if conf.api.ipc_max_size < IPC_MIN_SIZE
+        || conf.api.ipc_max_size > u32::MAX as usize
+    {
+        return Err(NipartError::new(
+            ErrorKind::InvalidArgument,
+            format!(
+                "Invalid daemon configuration: api.ipc-max-size should be in \
+                 range of [{IPC_MIN_SIZE}, {}], but got {}",
+                u32::MAX,
+                conf.api.ipc_max_size
+            ),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_tmp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir()
+            .join(format!("nipartd_conf_test_{}_{name}", std::process::id()));
+        std::fs::remove_dir_all(&dir).ok();
+        std::fs::create_dir_all(dir.join("nipartd.conf.d")).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_daemon_conf_not_exist() {
+        let dir = new_tmp_dir("not_exist");
+        let conf = read_conf_files(
+            &dir.join("nipartd.conf"),
+            &dir.join("nipartd.conf.d"),
+        )
+        .unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(conf, NipartDaemonConf::default());
+    }
+
+    #[test]
+    fn test_daemon_conf_drop_in_merge() {
+        let dir = new_tmp_dir("merge");
+        std::fs::write(
+            dir.join("nipartd.conf"),
+            "api:\n  ipc-max-size: 4096\nbootup:\n  nic-check-max-count: 5\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("nipartd.conf.d/10-a.conf"),
+            "bootup:\n  nic-check-max-count: 20\n  nic-check-max-quick: 2\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("nipartd.conf.d/20-b.conf"),
+            "bootup:\n  nic-check-max-count: 8\n",
+        )
+        .unwrap();
+        // Ignored as not ending with `.conf`
+        std::fs::write(
+            dir.join("nipartd.conf.d/30-c.conf.bak"),
+            "bootup:\n  nic-check-max-count: 100\n",
+        )
+        .unwrap();
+        let conf = read_conf_files(
+            &dir.join("nipartd.conf"),
+            &dir.join("nipartd.conf.d"),
+        )
+        .unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(conf.api.ipc_max_size, 4096);
+        assert_eq!(
+            conf.api.socket_path,
+            NipartDaemonConf::default().api.socket_path
+        );
+        assert_eq!(conf.bootup.nic_check_max_count, 8);
+        assert_eq!(conf.bootup.nic_check_max_quick, 2);
+        validate_conf(&conf).unwrap();
+    }
+
+    #[test]
+    fn test_daemon_conf_unknown_property() {
+        let dir = new_tmp_dir("unknown");
+        std::fs::write(dir.join("nipartd.conf"), "api:\n  foo: 1\n").unwrap();
+        let result = read_conf_files(
+            &dir.join("nipartd.conf"),
+            &dir.join("nipartd.conf.d"),
+        );
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(result.unwrap_err().kind, ErrorKind::InvalidArgument);
+    }
+
+    #[test]
+    fn test_daemon_conf_validate() {
+        let mut conf = NipartDaemonConf::default();
+        validate_conf(&conf).unwrap();
+
+        conf.state_dir = "states".to_string();
+        assert!(validate_conf(&conf).is_err());
+
+        let mut conf = NipartDaemonConf::default();
+        conf.bootup.nic_check_max_quick = conf.bootup.nic_check_max_count + 1;
+        assert!(validate_conf(&conf).is_err());
+
+        let mut conf = NipartDaemonConf::default();
+        conf.apply.verify_retry_count = 0;
+        assert!(validate_conf(&conf).is_err());
+
+        let mut conf = NipartDaemonConf::default();
+        conf.api.ipc_max_size = 10;
+        assert!(validate_conf(&conf).is_err());
+
+        let mut conf = NipartDaemonConf::default();
+        conf.plugin.restart_interval_sec_max = 0;
+        assert!(validate_conf(&conf).is_err());
+
+        let mut conf = NipartDaemonConf::default();
+        conf.dbus.enabled = true;
+        validate_conf(&conf).unwrap();
+    }
+}
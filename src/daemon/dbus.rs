@@ -0,0 +1,415 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// D-Bus interface mirroring the unix socket API. All data are in JSON format.
+// Disabled by default, controlled by `dbus` section of daemon configuration.
+
+use std::{str::FromStr, sync::Mutex};
+
+use futures_util::StreamExt;
+use nipart::{
+    ErrorKind, NetworkState, NipartClientCmd, NipartDaemonDbusBus,
+    NipartDaemonDbusConf, NipartError, NipartUuid, NipartWaitOnlineOption,
+    NipartstateApplyOption, NipartstateQueryOption,
+};
+use serde::{Serialize, de::DeserializeOwned};
+use tokio::task::JoinHandle;
+use zbus::{
+    fdo, interface, message::Header, names::BusName,
+    object_server::SignalEmitter,
+};
+
+use crate::{
+    api::permission_check, commander::NipartCommander,
+    daemon_conf::NipartDaemonConfManager, lock::NipartLockManager,
+};
+
+const DBUS_NAME: &str = "org.nipart.Nipart1";
+const DBUS_PATH: &str = "/org/nipart/Nipart1";
+
+// Configuration of running D-Bus service and the task holding its connection
+static DBUS_SERVICE: Mutex<Option<(NipartDaemonDbusConf, JoinHandle<()>)>> =
+    Mutex::new(None);
+
+struct NipartDbusApi {
+    commander: NipartCommander,
+}
+
+#[interface(name = "org.nipart.Nipart1")]
+impl NipartDbusApi {
+    async fn ping(
+        &self,
+        #[zbus(header)] header: Header<'_>,
+        #[zbus(connection)] conn: &zbus::Connection,
+    ) -> fdo::Result<String> {
+        check_caller(conn, &header, &NipartClientCmd::Ping).await?;
+        Ok("pong".to_string())
+    }
+
+    /// Empty `option` means default query option.
+    async fn query_network_state(
+        &self,
+        option: &str,
+        #[zbus(header)] header: Header<'_>,
+        #[zbus(connection)] conn: &zbus::Connection,
+    ) -> fdo::Result<String> {
+        let opt: NipartstateQueryOption = from_json_or_default(option)?;
+        check_caller(
+            conn,
+            &header,
+            &NipartClientCmd::QueryNetworkState(Box::new(opt.clone())),
+        )
+        .await?;
+        let mut commander = self.commander.clone();
+        to_json(
+            &commander
+                .query_network_state(None, opt)
+                .await
+                .map_err(to_fdo_err)?,
+        )
+    }
+
+    /// Secrets are hidden.
+    async fn query_drift(
+        &self,
+        #[zbus(header)] header: Header<'_>,
+        #[zbus(connection)] conn: &zbus::Connection,
+    ) -> fdo::Result<String> {
+        check_caller(conn, &header, &NipartClientCmd::QueryDrift).await?;
+        let mut commander = self.commander.clone();
+        to_json(&commander.query_drift(None).await.map_err(to_fdo_err)?)
+    }
+
+    /// Empty `option` means default apply option, `dry-run` is not allowed
+    /// here, please use `PlanNetworkState` instead.
+    /// Return the changed state.
+    async fn apply_network_state(
+        &self,
+        state: &str,
+        option: &str,
+        #[zbus(header)] header: Header<'_>,
+        #[zbus(connection)] conn: &zbus::Connection,
+    ) -> fdo::Result<String> {
+        let desired_state: NetworkState = from_json(state)?;
+        let opt: NipartstateApplyOption = from_json_or_default(option)?;
+        let (uid, pid) = check_caller(
+            conn,
+            &header,
+            &NipartClientCmd::ApplyNetworkState(Box::new((
+                desired_state.clone(),
+                opt.clone(),
+            ))),
+        )
+        .await?;
+
+        let lock = NipartLockManager::lock(pid).await;
+        let mut commander = self.commander.clone();
+        let result = commander
+            .apply_network_state(None, desired_state, opt, Some((uid, pid)))
+            .await;
+        drop(lock);
+        to_json(&result.map_err(to_fdo_err)?)
+    }
+
+    /// Empty `option` means default apply option, `dry-run` is implied.
+    /// Return operations planned for applying desired state without
+    /// touching anything.
+    async fn plan_network_state(
+        &self,
+        state: &str,
+        option: &str,
+        #[zbus(header)] header: Header<'_>,
+        #[zbus(connection)] conn: &zbus::Connection,
+    ) -> fdo::Result<String> {
+        let desired_state: NetworkState = from_json(state)?;
+        let opt =
+            from_json_or_default::<NipartstateApplyOption>(option)?.dry_run();
+        check_caller(
+            conn,
+            &header,
+            &NipartClientCmd::ApplyNetworkState(Box::new((
+                desired_state.clone(),
+                opt.clone(),
+            ))),
+        )
+        .await?;
+        // Dry-run changes nothing, no lock required
+        let mut commander = self.commander.clone();
+        to_json(
+            &commander
+                .plan_network_state(None, desired_state, opt)
+                .await
+                .map_err(to_fdo_err)?,
+        )
+    }
+
+    /// Return the changed state.
+    async fn restore_generation(
+        &self,
+        generation: u64,
+        #[zbus(header)] header: Header<'_>,
+        #[zbus(connection)] conn: &zbus::Connection,
+    ) -> fdo::Result<String> {
+        let (uid, pid) = check_caller(
+            conn,
+            &header,
+            &NipartClientCmd::RestoreGeneration(generation),
+        )
+        .await?;
+        let lock = NipartLockManager::lock(pid).await;
+        let mut commander = self.commander.clone();
+        let result = commander
+            .restore_generation(None, generation, Some((uid, pid)))
+            .await;
+        drop(lock);
+        to_json(&result.map_err(to_fdo_err)?)
+    }
+
+    async fn commit(
+        &self,
+        #[zbus(header)] header: Header<'_>,
+        #[zbus(connection)] conn: &zbus::Connection,
+    ) -> fdo::Result<()> {
+        let (_, pid) =
+            check_caller(conn, &header, &NipartClientCmd::Commit).await?;
+        let lock = NipartLockManager::lock(pid).await;
+        let mut commander = self.commander.clone();
+        let result = commander.commit(None).await;
+        drop(lock);
+        result.map_err(to_fdo_err)
+    }
+
+    async fn rollback(
+        &self,
+        #[zbus(header)] header: Header<'_>,
+        #[zbus(connection)] conn: &zbus::Connection,
+    ) -> fdo::Result<()> {
+        let (_, pid) =
+            check_caller(conn, &header, &NipartClientCmd::Rollback).await?;
+        let lock = NipartLockManager::lock(pid).await;
+        let mut commander = self.commander.clone();
+        let result = commander.rollback_checkpoint(None).await;
+        drop(lock);
+        result.map_err(to_fdo_err)
+    }
+
+    /// Empty `iface_name` means scanning on all WiFi interfaces.
+    async fn wifi_scan(
+        &self,
+        iface_name: &str,
+        #[zbus(header)] header: Header<'_>,
+        #[zbus(connection)] conn: &zbus::Connection,
+    ) -> fdo::Result<String> {
+        let iface_name = if iface_name.is_empty() {
+            None
+        } else {
+            Some(iface_name)
+        };
+        check_caller(
+            conn,
+            &header,
+            &NipartClientCmd::WifiScan(iface_name.map(|i| i.to_string())),
+        )
+        .await?;
+        let mut commander = self.commander.clone();
+        to_json(
+            &commander
+                .wifi_scan(None, iface_name)
+                .await
+                .map_err(to_fdo_err)?,
+        )
+    }
+
+    /// Empty `option` means default wait online option.
+    async fn wait_online(
+        &self,
+        option: &str,
+        #[zbus(header)] header: Header<'_>,
+        #[zbus(connection)] conn: &zbus::Connection,
+    ) -> fdo::Result<()> {
+        let opt: NipartWaitOnlineOption = from_json_or_default(option)?;
+        check_caller(
+            conn,
+            &header,
+            &NipartClientCmd::WaitOnline(Box::new(opt.clone())),
+        )
+        .await?;
+        let mut commander = self.commander.clone();
+        commander.wait_online(None, opt).await.map_err(to_fdo_err)
+    }
+
+    async fn query_daemon_conf(
+        &self,
+        #[zbus(header)] header: Header<'_>,
+        #[zbus(connection)] conn: &zbus::Connection,
+    ) -> fdo::Result<String> {
+        check_caller(conn, &header, &NipartClientCmd::QueryDaemonConf).await?;
+        to_json(NipartDaemonConfManager::get().as_ref())
+    }
+
+    /// Empty `id` means all transactions in history.
+    async fn query_history(
+        &self,
+        id: &str,
+        #[zbus(header)] header: Header<'_>,
+        #[zbus(connection)] conn: &zbus::Connection,
+    ) -> fdo::Result<String> {
+        let id = if id.is_empty() {
+            None
+        } else {
+            Some(NipartUuid::from_str(id).map_err(to_fdo_err)?)
+        };
+        check_caller(conn, &header, &NipartClientCmd::QueryHistory(id)).await?;
+        let mut commander = self.commander.clone();
+        to_json(
+            &commander
+                .conf_manager
+                .query_history(id)
+                .await
+                .map_err(to_fdo_err)?,
+        )
+    }
+
+    /// Emitted for every [nipart::NipartEvent] in JSON format.
+    #[zbus(signal)]
+    async fn event(
+        emitter: &SignalEmitter<'_>,
+        event: &str,
+    ) -> zbus::Result<()>;
+}
+
+/// Start, restart or stop D-Bus service according to `dbus` section of
+/// daemon configuration in use. Invoked on daemon start and reload.
+pub(crate) async fn setup_dbus_service(
+    commander: NipartCommander,
+) -> Result<(), NipartError> {
+    let conf = NipartDaemonConfManager::get().dbus.clone();
+    let running = match DBUS_SERVICE.lock() {
+        Ok(mut s) => s.take(),
+        Err(e) => e.into_inner().take(),
+    };
+    if let Some((running_conf, task)) = running {
+        if running_conf == conf {
+            set_dbus_service(running_conf, task);
+            return Ok(());
+        }
+        // Wait the connection dropped to release the D-Bus name
+        task.abort();
+        task.await.ok();
+        log::info!("D-Bus service {DBUS_NAME} stopped");
+    }
+    if !conf.enabled {
+        log::debug!("D-Bus service disabled");
+        return Ok(());
+    }
+    let task = start_dbus_service(commander, conf.bus).await?;
+    set_dbus_service(conf, task);
+    Ok(())
+}
+
+fn set_dbus_service(conf: NipartDaemonDbusConf, task: JoinHandle<()>) {
+    match DBUS_SERVICE.lock() {
+        Ok(mut s) => *s = Some((conf, task)),
+        Err(e) => *e.into_inner() = Some((conf, task)),
+    }
+}
+
+async fn start_dbus_service(
+    mut commander: NipartCommander,
+    bus: NipartDaemonDbusBus,
+) -> Result<JoinHandle<()>, NipartError> {
+    let builder = match bus {
+        NipartDaemonDbusBus::Session => zbus::connection::Builder::session(),
+        _ => zbus::connection::Builder::system(),
+    }
+    .map_err(map_zbus_err)?;
+
+    let mut receiver = commander.event_emitter.subscribe();
+    commander.monitor_manager.set_emit_all(true).await?;
+
+    let connection = builder
+        .name(DBUS_NAME)
+        .map_err(map_zbus_err)?
+        .serve_at(DBUS_PATH, NipartDbusApi { commander })
+        .map_err(map_zbus_err)?
+        .build()
+        .await
+        .map_err(map_zbus_err)?;
+    log::info!("D-Bus service {DBUS_NAME} started on {bus} bus");
+
+    // This task holds the connection to keep D-Bus service alive
+    Ok(tokio::spawn(async move {
+        let emitter = match SignalEmitter::new(&connection, DBUS_PATH) {
+            Ok(e) => e,
+            Err(e) => {
+                log::error!("Failed to create D-Bus signal emitter: {e}");
+                return;
+            }
+        };
+        while let Some(event) = receiver.next().await {
+            match serde_json::to_string(&event) {
+                Ok(s) => {
+                    if let Err(e) = NipartDbusApi::event(&emitter, &s).await {
+                        log::warn!("Failed to emit D-Bus signal {s}: {e}");
+                    }
+                }
+                Err(e) => {
+                    log::error!("BUG: Failed to serialize {event:?}: {e}");
+                }
+            }
+        }
+    }))
+}
+
+/// Check permission of D-Bus caller, return (uid, pid) of caller.
+async fn check_caller(
+    conn: &zbus::Connection,
+    header: &Header<'_>,
+    cmd: &NipartClientCmd,
+) -> fdo::Result<(u32, i32)> {
+    let sender = header.sender().ok_or_else(|| {
+        fdo::Error::AccessDenied("Unknown D-Bus caller".to_string())
+    })?;
+    let proxy = fdo::DBusProxy::new(conn).await?;
+    let uid = proxy
+        .get_connection_unix_user(BusName::from(sender.clone()))
+        .await?;
+    let pid = proxy
+        .get_connection_unix_process_id(BusName::from(sender.clone()))
+        .await?;
+    permission_check(cmd, uid).map_err(to_fdo_err)?;
+    Ok((uid, pid as i32))
+}
+
+fn from_json<T: DeserializeOwned>(content: &str) -> fdo::Result<T> {
+    serde_json::from_str(content).map_err(|e| {
+        fdo::Error::InvalidArgs(format!("Invalid JSON {content}: {e}"))
+    })
+}
+
+fn from_json_or_default<T: DeserializeOwned + Default>(
+    content: &str,
+) -> fdo::Result<T> {
+    if content.is_empty() {
+        Ok(T::default())
+    } else {
+        from_json(content)
+    }
+}
+
+fn to_json<T: Serialize>(data: &T) -> fdo::Result<String> {
+    serde_json::to_string(data).map_err(|e| {
+        fdo::Error::Failed(format!("Failed to generate JSON string: {e}"))
+    })
+}
+
+fn to_fdo_err(e: NipartError) -> fdo::Error {
+    match e.kind {
+        ErrorKind::PermissionDeny => fdo::Error::AccessDenied(e.to_string()),
+        ErrorKind::InvalidArgument => fdo::Error::InvalidArgs(e.to_string()),
+        _ => fdo::Error::Failed(e.to_string()),
+    }
+}
+
+fn map_zbus_err(e: zbus::Error) -> NipartError {
+    NipartError::new(ErrorKind::Bug, format!("D-Bus error: {e}"))
+}
@@ -1,12 +1,12 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use nipart::{
-    BaseInterface, MergedNetworkState, NetworkState, NipartError, NipartIpcConnection,
-    NipartstateInterface,
+    BaseInterface, InterfaceIdentifier, MergedNetworkState, NetworkState,
+    NipartError, NipartIpcConnection, NipartNoDaemon, NipartstateInterface,
 };
 
 use super::{NipartDhcpCmd, NipartDhcpReply, NipartDhcpV4Worker};
-use crate::{TaskManager, log_debug};
+use crate::{TaskManager, emitter::NipartEventEmitter, log_debug};
 
 #[derive(Debug, Clone)]
 pub(crate) struct NipartDhcpV4Manager {
@@ -17,10 +17,16 @@ pub(crate) struct NipartDhcpV4Manager {
 // into Mutex protected `NipartDaemonShareData`. The
 // `MutexGuard` will cause function not `Send`.
 impl NipartDhcpV4Manager {
-    pub(crate) async fn new() -> Result<Self, NipartError> {
-        Ok(Self {
+    pub(crate) async fn new(
+        event_emitter: NipartEventEmitter,
+    ) -> Result<Self, NipartError> {
+        let mut ret = Self {
             mgr: TaskManager::new::<NipartDhcpV4Worker>("dhcp").await?,
-        })
+        };
+        ret.mgr
+            .exec(NipartDhcpCmd::SetEventEmitter(event_emitter))
+            .await?;
+        Ok(ret)
     }
 
     /// Fill the NetworkState with DHCP states
@@ -40,14 +46,27 @@ impl NipartDhcpV4Manager {
                         .ipv4
                         .get_or_insert(Default::default());
                     ipv4_conf.enabled = Some(true);
-                    ipv4_conf.dhcp = Some(true);
-                    ipv4_conf.dhcp_state = Some(dhcp_state);
+                    if let Some(state) = dhcp_state.state {
+                        ipv4_conf.dhcp = Some(true);
+                        ipv4_conf.dhcp_state = Some(state);
+                    }
+                    if dhcp_state.link_local.is_some() {
+                        ipv4_conf.link_local = dhcp_state.link_local;
+                        ipv4_conf.link_local_state =
+                            dhcp_state.link_local_state;
+                    }
                 }
             }
         }
         Ok(())
     }
 
+    /// Stop all DHCP threads without releasing acquired addresses
+    pub(crate) async fn shutdown(&mut self) -> Result<(), NipartError> {
+        self.mgr.exec(NipartDhcpCmd::Shutdown).await?;
+        Ok(())
+    }
+
     async fn start_iface_dhcp(
         &mut self,
         base_iface: &BaseInterface,
@@ -86,15 +105,39 @@ impl NipartDhcpV4Manager {
                     continue;
                 }
             };
-            if apply_iface.base_iface().mac_address.is_none() {
+            // MAC address used as identifier might differ from the one in
+            // use, e.g. bond port, query kernel for it instead.
+            if apply_iface.base_iface().identifier
+                == InterfaceIdentifier::MacAddress
+            {
+                apply_iface.base_iface_mut().mac_address = None;
+            } else if apply_iface.base_iface().mac_address.is_none() {
                 apply_iface.base_iface_mut().mac_address =
                     merged_iface.merged.base_iface().mac_address.clone();
             }
             apply_iface.base_iface_mut().iface_index =
                 merged_iface.merged.base_iface().iface_index;
+            // Interface created in this apply has no index or MAC address
+            // in merged state
+            if apply_iface.is_up()
+                && (apply_iface.base_iface().iface_index.is_none()
+                    || apply_iface.base_iface().mac_address.is_none())
+            {
+                let (iface_index, mac_address) =
+                    NipartNoDaemon::query_iface_index_mac(apply_iface.name())
+                        .await?;
+                let base_iface = apply_iface.base_iface_mut();
+                base_iface.iface_index.get_or_insert(iface_index);
+                if base_iface.mac_address.is_none() {
+                    base_iface.mac_address = mac_address;
+                }
+            }
             if apply_iface.is_up() {
-                if let Some(dhcp_enabled) =
-                    apply_iface.base_iface().ipv4.as_ref().map(|i| i.is_auto())
+                if let Some(dhcp_enabled) = apply_iface
+                    .base_iface()
+                    .ipv4
+                    .as_ref()
+                    .map(|i| i.is_auto() || i.is_link_local_enabled())
                 {
                     if dhcp_enabled {
                         log_debug(
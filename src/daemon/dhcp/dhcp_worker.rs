@@ -3,6 +3,7 @@
 use std::{
     collections::HashMap,
     sync::{Arc, Mutex},
+    time::Duration,
 };
 
 use futures_channel::{
@@ -13,24 +14,35 @@ use futures_util::StreamExt;
 use mozim::{DhcpV4Client, DhcpV4Config, DhcpV4Lease, DhcpV4State};
 use nipart::{
     BaseInterface, DhcpState, ErrorKind, Interface, InterfaceIpAddr,
-    InterfaceIpv4, NetworkState, NipartError, NipartNoDaemon, NipartstateApplyOption,
-    RouteEntry, Routes,
+    InterfaceIpv4, Ipv4LinkLocalMode, NetworkState, NipartDhcpLeaseEvent,
+    NipartError, NipartEvent, NipartIpv4LinkLocal, NipartNoDaemon,
+    NipartstateApplyOption, RouteEntry, Routes,
 };
 
-use crate::TaskWorker;
+use crate::{TaskWorker, emitter::NipartEventEmitter};
 
 const DEFAULT_ROUTE_TABLE_ID: u32 = 254;
+const DHCP_RETRY_INTERVAL: Duration = Duration::from_secs(60);
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone)]
 pub(crate) enum NipartDhcpCmd {
+    /// Set the emitter for sending events to subscribers. Must be invoked
+    /// right after NipartDhcpV4Worker started.
+    SetEventEmitter(NipartEventEmitter),
     StartIfaceDhcp(Box<BaseInterface>),
     StopIfaceDhcp(String),
     Query,
+    /// Stop all DHCP threads without releasing acquired addresses, used when
+    /// daemon is shutting down.
+    Shutdown,
 }
 
 impl std::fmt::Display for NipartDhcpCmd {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            Self::SetEventEmitter(_) => {
+                write!(f, "set-event-emitter")
+            }
             Self::StartIfaceDhcp(base_iface) => {
                 write!(f, "start-iface-dhcp:{}", base_iface.name)
             }
@@ -40,6 +52,9 @@ impl std::fmt::Display for NipartDhcpCmd {
             Self::Query => {
                 write!(f, "query-dhcp")
             }
+            Self::Shutdown => {
+                write!(f, "shutdown")
+            }
         }
     }
 }
@@ -47,7 +62,7 @@ impl std::fmt::Display for NipartDhcpCmd {
 #[derive(Debug, Clone, PartialEq)]
 pub(crate) enum NipartDhcpReply {
     None,
-    QueryReply(HashMap<String, DhcpState>),
+    QueryReply(HashMap<String, NipartDhcpShareData>),
 }
 
 type FromManager = (NipartDhcpCmd, Sender<Result<NipartDhcpReply, NipartError>>);
@@ -56,6 +71,7 @@ type FromManager = (NipartDhcpCmd, Sender<Result<NipartDhcpReply, NipartError>>)
 pub(crate) struct NipartDhcpV4Worker {
     threads: HashMap<String, NipartDhcpV4Thread>,
     receiver: UnboundedReceiver<FromManager>,
+    event_emitter: NipartEventEmitter,
 }
 
 impl TaskWorker for NipartDhcpV4Worker {
@@ -71,6 +87,7 @@ impl TaskWorker for NipartDhcpV4Worker {
         Ok(Self {
             threads: HashMap::new(),
             receiver,
+            event_emitter: NipartEventEmitter::default(),
         })
     }
 
@@ -83,9 +100,17 @@ impl TaskWorker for NipartDhcpV4Worker {
         cmd: NipartDhcpCmd,
     ) -> Result<NipartDhcpReply, NipartError> {
         match cmd {
+            NipartDhcpCmd::SetEventEmitter(emitter) => {
+                self.event_emitter = emitter;
+                Ok(NipartDhcpReply::None)
+            }
             NipartDhcpCmd::StartIfaceDhcp(base_iface) => {
                 let iface_name = base_iface.name.clone();
-                let thread = NipartDhcpV4Thread::new(*base_iface).await?;
+                let thread = NipartDhcpV4Thread::new(
+                    *base_iface,
+                    self.event_emitter.clone(),
+                )
+                .await?;
                 self.threads.insert(iface_name, thread);
                 Ok(NipartDhcpReply::None)
             }
@@ -101,98 +126,95 @@ impl TaskWorker for NipartDhcpV4Worker {
 
                 Ok(NipartDhcpReply::QueryReply(ret))
             }
+            NipartDhcpCmd::Shutdown => {
+                for (_, thread) in self.threads.drain() {
+                    thread.shutdown();
+                }
+                Ok(NipartDhcpReply::None)
+            }
         }
     }
 }
 
-#[derive(Debug, Default)]
-struct NipartDhcpShareData {
-    state: DhcpState,
+#[derive(Debug, Clone, PartialEq, Default)]
+pub(crate) struct NipartDhcpShareData {
+    pub(crate) state: Option<DhcpState>,
+    pub(crate) link_local: Option<Ipv4LinkLocalMode>,
+    pub(crate) link_local_state: Option<DhcpState>,
 }
 
 #[derive(Debug)]
 pub(crate) struct NipartDhcpV4Thread {
     pub(crate) base_iface: BaseInterface,
-    // No need to send any data. Dropping this Sender will cause
-    // Receiver.recv() got None which trigger DHCP thread quit.
-    _quit_notifer: UnboundedSender<()>,
+    // Dropping this Sender will cause Receiver.recv() got None which trigger
+    // DHCP thread quit. Sending `()` means daemon is shutting down, DHCP
+    // thread should quit without removing acquired address.
+    quit_notifer: UnboundedSender<()>,
+    // Quit notifier for IPv4 link-local thread running along with DHCP
+    link_local_quit_notifer: Option<UnboundedSender<()>>,
     share_data: Arc<Mutex<NipartDhcpShareData>>,
 }
 
 impl NipartDhcpV4Thread {
     pub(crate) async fn new(
         base_iface: BaseInterface,
+        event_emitter: NipartEventEmitter,
     ) -> Result<Self, NipartError> {
         let (sender, receiver) = unbounded();
-        let ret = Self {
+        let ipv4_conf = base_iface.ipv4.clone().unwrap_or_default();
+        let mut ret = Self {
             base_iface: base_iface.clone(),
-            _quit_notifer: sender,
-            share_data: Arc::new(Mutex::new(NipartDhcpShareData::default())),
-        };
-        let mac_addr = match base_iface.mac_address.as_deref() {
-            Some(m) => m,
-            None => {
-                return Err(NipartError::new(
-                    ErrorKind::Bug,
-                    format!(
-                        "Got no MAC address for DHCPv4 on interface {}({})",
-                        base_iface.name, base_iface.iface_type
-                    ),
-                ));
-            }
-        };
-        let iface_index = match base_iface.iface_index {
-            Some(m) => m,
-            None => {
-                return Err(NipartError::new(
-                    ErrorKind::Bug,
-                    format!(
-                        "Got no interface index for DHCPv4 on interface {}({})",
-                        base_iface.name, base_iface.iface_type
-                    ),
-                ));
-            }
+            quit_notifer: sender,
+            link_local_quit_notifer: None,
+            share_data: Arc::new(Mutex::new(NipartDhcpShareData {
+                state: None,
+                link_local: ipv4_conf.link_local,
+                link_local_state: None,
+            })),
         };
-        let mut dhcp_config = DhcpV4Config::new(base_iface.name.as_str());
-        dhcp_config
-            .set_iface_index(iface_index)
-            .set_iface_mac(mac_addr)
-            .map_err(|e| {
-                NipartError::new(
-                    ErrorKind::Bug,
-                    format!(
-                        "Failed to set iface {}/{} MAC {}: {e}",
-                        base_iface.name, base_iface.iface_type, mac_addr,
-                    ),
-                )
-            })?
-            .use_mac_as_client_id();
-        // TODO(Gris Ge): Support loading previous stored lease
-        let dhcp_client =
-            DhcpV4Client::init(dhcp_config, None).await.map_err(|e| {
-                NipartError::new(
-                    ErrorKind::Bug,
-                    format!(
-                        "Failed to start DHCPv4 client on iface {}/{}: {e}",
-                        base_iface.name, base_iface.iface_type,
-                    ),
-                )
-            })?;
+        if ipv4_conf.is_link_local_enabled() {
+            let (ll_sender, ll_receiver) = unbounded();
+            ret.link_local_quit_notifer = Some(ll_sender);
+            let share_data = ret.share_data.clone();
+            let base_iface = base_iface.clone();
+            tokio::spawn(async move {
+                link_local_thread(base_iface, ll_receiver, share_data).await
+            });
+        }
+        if !ipv4_conf.is_auto() {
+            return Ok(ret);
+        }
+        set_dhcp_state(&ret.share_data, &base_iface, DhcpState::default())?;
+        let link_local_fallback = ipv4_conf.is_link_local_fallback();
+        let dhcp_client = new_dhcp_client(&base_iface).await?;
 
         let share_data = ret.share_data.clone();
         tokio::spawn(async move {
-            if let Err(e) =
-                dhcp_thread(dhcp_client, base_iface, receiver, share_data).await
-            {
-                log::error!("{e}");
-            }
+            dhcp_with_link_local_fallback(
+                dhcp_client,
+                base_iface,
+                receiver,
+                share_data,
+                event_emitter,
+                link_local_fallback,
+            )
+            .await
         });
         Ok(ret)
     }
 
-    pub(crate) fn get_state(&self) -> Result<DhcpState, NipartError> {
+    /// Quit DHCP and IPv4 link-local threads but keep acquired addresses on
+    /// interface.
+    pub(crate) fn shutdown(self) {
+        self.quit_notifer.unbounded_send(()).ok();
+        if let Some(notifier) = self.link_local_quit_notifer.as_ref() {
+            notifier.unbounded_send(()).ok();
+        }
+    }
+
+    pub(crate) fn get_state(&self) -> Result<NipartDhcpShareData, NipartError> {
         match self.share_data.lock() {
-            Ok(data) => Ok(data.state.clone()),
+            Ok(data) => Ok(data.clone()),
             Err(e) => Err(NipartError::new(
                 ErrorKind::Bug,
                 format!(
@@ -205,12 +227,148 @@ impl NipartDhcpV4Thread {
     }
 }
 
-async fn dhcp_thread(
+async fn new_dhcp_client(
+    base_iface: &BaseInterface,
+) -> Result<DhcpV4Client, NipartError> {
+    let mac_addr = match base_iface.mac_address.as_deref() {
+        Some(m) => m,
+        None => {
+            return Err(NipartError::new(
+                ErrorKind::Bug,
+                format!(
+                    "Got no MAC address for DHCPv4 on interface {}({})",
+                    base_iface.name, base_iface.iface_type
+                ),
+            ));
+        }
+    };
+    let iface_index = match base_iface.iface_index {
+        Some(m) => m,
+        None => {
+            return Err(NipartError::new(
+                ErrorKind::Bug,
+                format!(
+                    "Got no interface index for DHCPv4 on interface {}({})",
+                    base_iface.name, base_iface.iface_type
+                ),
+            ));
+        }
+    };
+    let mut dhcp_config = DhcpV4Config::new(base_iface.name.as_str());
+    dhcp_config
+        .set_iface_index(iface_index)
+        .set_iface_mac(mac_addr)
+        .map_err(|e| {
+            NipartError::new(
+                ErrorKind::Bug,
+                format!(
+                    "Failed to set iface {}/{} MAC {}: {e}",
+                    base_iface.name, base_iface.iface_type, mac_addr,
+                ),
+            )
+        })?
+        .use_mac_as_client_id();
+    // TODO(Gris Ge): Support loading previous stored lease
+    DhcpV4Client::init(dhcp_config, None).await.map_err(|e| {
+        NipartError::new(
+            ErrorKind::Bug,
+            format!(
+                "Failed to start DHCPv4 client on iface {}/{}: {e}",
+                base_iface.name, base_iface.iface_type,
+            ),
+        )
+    })
+}
+
+async fn dhcp_with_link_local_fallback(
     mut dhcp_client: DhcpV4Client,
     base_iface: BaseInterface,
     mut quit_indicator: UnboundedReceiver<()>,
     share_data: Arc<Mutex<NipartDhcpShareData>>,
-) -> Result<(), NipartError> {
+    event_emitter: NipartEventEmitter,
+    link_local_fallback: bool,
+) {
+    let mut link_local_quit: Option<UnboundedSender<()>> = None;
+    loop {
+        match dhcp_thread(
+            dhcp_client,
+            &base_iface,
+            &mut quit_indicator,
+            share_data.clone(),
+            &event_emitter,
+            &mut link_local_quit,
+        )
+        .await
+        {
+            Ok(true) if link_local_fallback => {
+                if link_local_quit.is_none() {
+                    log::info!(
+                        "DHCPv4 on {}({}) failed, falling back to IPv4 \
+                         link-local",
+                        base_iface.name,
+                        base_iface.iface_type,
+                    );
+                    let (sender, receiver) = unbounded();
+                    link_local_quit = Some(sender);
+                    tokio::spawn(link_local_thread(
+                        base_iface.clone(),
+                        receiver,
+                        share_data.clone(),
+                    ));
+                }
+            }
+            Ok(_) => return,
+            Err(e) => {
+                log::error!("{e}");
+                return;
+            }
+        }
+        // Keep retrying DHCP in the background, the link-local address
+        // will be removed once got DHCP lease.
+        tokio::select! {
+            _ = tokio::time::sleep(DHCP_RETRY_INTERVAL) => (),
+            quit = quit_indicator.next() => {
+                stop_link_local(&mut link_local_quit, quit.is_some());
+                return;
+            }
+        }
+        log::info!(
+            "Retrying DHCPv4 on {}({})",
+            base_iface.name,
+            base_iface.iface_type,
+        );
+        dhcp_client = match new_dhcp_client(&base_iface).await {
+            Ok(c) => c,
+            Err(e) => {
+                log::error!("{e}");
+                return;
+            }
+        };
+    }
+}
+
+// Quit IPv4 link-local thread running as DHCP fallback, the link-local
+// address is kept if `keep_addr` is true, e.g. daemon is shutting down.
+fn stop_link_local(
+    link_local_quit: &mut Option<UnboundedSender<()>>,
+    keep_addr: bool,
+) {
+    if let Some(sender) = link_local_quit.take()
+        && keep_addr
+    {
+        sender.unbounded_send(()).ok();
+    }
+}
+
+/// Return `Ok(true)` if DHCP failed.
+async fn dhcp_thread(
+    mut dhcp_client: DhcpV4Client,
+    base_iface: &BaseInterface,
+    quit_indicator: &mut UnboundedReceiver<()>,
+    share_data: Arc<Mutex<NipartDhcpShareData>>,
+    event_emitter: &NipartEventEmitter,
+    link_local_quit: &mut Option<UnboundedSender<()>>,
+) -> Result<bool, NipartError> {
     log::debug!(
         "Waiting link carrier up for interface {}/{} before start DHCP",
         base_iface.name,
@@ -222,20 +380,8 @@ async fn dhcp_thread(
         base_iface.name,
         base_iface.iface_type
     );
-    match share_data.lock() {
-        Ok(mut share_data) => {
-            share_data.state = DhcpState::Running;
-        }
-        Err(e) => {
-            return Err(NipartError::new(
-                ErrorKind::Bug,
-                format!(
-                    "Failed to lock DHCPv4 {}({}) share data: {e}",
-                    base_iface.name, base_iface.iface_type,
-                ),
-            ));
-        }
-    }
+    set_dhcp_state(&share_data, base_iface, DhcpState::Running)?;
+    let mut has_lease = false;
     let result = loop {
         tokio::select! {
             result = dhcp_client.run() => {
@@ -247,24 +393,40 @@ async fn dhcp_thread(
                             base_iface.iface_type,
                             lease.yiaddr,
                         );
-                        match share_data.lock() {
-                            Ok(mut share_data) => {
-                                share_data.state = DhcpState::Done;
-                            }
-                            Err(e) => {
-                                break Err::<(), NipartError>(NipartError::new(
-                                    ErrorKind::Bug,
-                                    format!("Unhandled DHCPv4 error: {e}"),
-                                ));
-                            }
+                        if let Err(e) = set_dhcp_state(
+                            &share_data,
+                            base_iface,
+                            DhcpState::Done,
+                        ) {
+                            break Err::<(), NipartError>(e);
                         }
                         if let Err(e) = apply_lease(
-                            &base_iface,
+                            base_iface,
                             &lease,
                             share_data.clone()
                         ).await {
                             break Err(e);
                         }
+                        has_lease = true;
+                        if link_local_quit.is_some() {
+                            log::info!(
+                                "Removing IPv4 link-local address from \
+                                 {}({}) as DHCPv4 got lease",
+                                base_iface.name,
+                                base_iface.iface_type,
+                            );
+                            stop_link_local(link_local_quit, false);
+                        }
+                        event_emitter.emit(NipartEvent::DhcpLeaseAcquired(
+                            NipartDhcpLeaseEvent::new(
+                                base_iface.name.to_string(),
+                                format!(
+                                    "{}/{}",
+                                    lease.yiaddr,
+                                    lease.prefix_length()
+                                ),
+                            ),
+                        ));
                     }
                     Ok(dhcp_state) => {
                         log::info!(
@@ -282,34 +444,149 @@ async fn dhcp_thread(
                     }
                 }
             }
-            _ = quit_indicator.next() => {
+            quit = quit_indicator.next() => {
+                stop_link_local(link_local_quit, quit.is_some());
+                if quit.is_some() {
+                    log::info!(
+                        "DHCPv4 on {}({}) stopped for daemon shutdown, \
+                         keeping lease",
+                        base_iface.name,
+                        base_iface.iface_type,
+                    );
+                    return Ok(false);
+                }
                 log::info!(
                     "DHCPv4 on {}({}) stopped",
                     base_iface.name,
                     base_iface.iface_type,
                 );
-                return Ok(());
+                if has_lease {
+                    event_emitter.emit(NipartEvent::DhcpLeaseLost(
+                        base_iface.name.to_string(),
+                    ));
+                }
+                return Ok(false);
             }
         }
     };
 
     if let Err(e) = result {
-        match share_data.lock() {
-            Ok(mut share_data) => {
-                share_data.state = DhcpState::Error(e.to_string());
+        if has_lease {
+            event_emitter
+                .emit(NipartEvent::DhcpLeaseLost(base_iface.name.to_string()));
+        }
+        set_dhcp_state(
+            &share_data,
+            base_iface,
+            DhcpState::Error(e.to_string()),
+        )?;
+        Ok(true)
+    } else {
+        Ok(false)
+    }
+}
+
+fn set_dhcp_state(
+    share_data: &Arc<Mutex<NipartDhcpShareData>>,
+    base_iface: &BaseInterface,
+    state: DhcpState,
+) -> Result<(), NipartError> {
+    match share_data.lock() {
+        Ok(mut share_data) => {
+            share_data.state = Some(state);
+            Ok(())
+        }
+        Err(e) => Err(NipartError::new(
+            ErrorKind::Bug,
+            format!(
+                "Failed to lock DHCPv4 {}({}) share data: {e}",
+                base_iface.name, base_iface.iface_type,
+            ),
+        )),
+    }
+}
+
+fn set_link_local_state(
+    share_data: &Arc<Mutex<NipartDhcpShareData>>,
+    base_iface: &BaseInterface,
+    state: DhcpState,
+) -> Result<(), NipartError> {
+    match share_data.lock() {
+        Ok(mut share_data) => {
+            share_data.link_local_state = Some(state);
+            Ok(())
+        }
+        Err(e) => Err(NipartError::new(
+            ErrorKind::Bug,
+            format!(
+                "Failed to lock IPv4 link-local {}({}) share data: {e}",
+                base_iface.name, base_iface.iface_type,
+            ),
+        )),
+    }
+}
+
+async fn link_local_thread(
+    base_iface: BaseInterface,
+    mut quit_indicator: UnboundedReceiver<()>,
+    share_data: Arc<Mutex<NipartDhcpShareData>>,
+) {
+    if let Err(e) =
+        run_link_local(&base_iface, &mut quit_indicator, &share_data).await
+    {
+        log::error!("{e}");
+        set_link_local_state(
+            &share_data,
+            &base_iface,
+            DhcpState::Error(e.to_string()),
+        )
+        .ok();
+    }
+}
+
+async fn run_link_local(
+    base_iface: &BaseInterface,
+    quit_indicator: &mut UnboundedReceiver<()>,
+    share_data: &Arc<Mutex<NipartDhcpShareData>>,
+) -> Result<(), NipartError> {
+    set_link_local_state(share_data, base_iface, DhcpState::WaitLinkCarrier)?;
+    NipartNoDaemon::wait_link_carrier_up(base_iface.name.as_str()).await?;
+    let mut ipv4ll = NipartIpv4LinkLocal::new(base_iface.name.as_str()).await?;
+    loop {
+        set_link_local_state(share_data, base_iface, DhcpState::Running)?;
+        let ip = tokio::select! {
+            result = ipv4ll.acquire() => result?,
+            _ = quit_indicator.next() => {
+                log::info!(
+                    "IPv4 link-local on {}({}) stopped",
+                    base_iface.name,
+                    base_iface.iface_type,
+                );
+                return Ok(());
             }
-            Err(e) => {
-                return Err(NipartError::new(
-                    ErrorKind::Bug,
-                    format!(
-                        "Failed to lock DHCPv4 {}({}) share data: {e}",
-                        base_iface.name, base_iface.iface_type,
-                    ),
-                ));
+        };
+        NipartIpv4LinkLocal::assign(base_iface, ip).await?;
+        set_link_local_state(share_data, base_iface, DhcpState::Done)?;
+
+        tokio::select! {
+            result = ipv4ll.defend(ip) => {
+                result?;
+                NipartIpv4LinkLocal::release(base_iface, ip).await?;
+            }
+            quit = quit_indicator.next() => {
+                log::info!(
+                    "IPv4 link-local on {}({}) stopped",
+                    base_iface.name,
+                    base_iface.iface_type,
+                );
+                // `Some(())` means daemon is shutting down, keep the address
+                if quit.is_none() {
+                    NipartIpv4LinkLocal::release(base_iface, ip).await?;
+                }
+                return Ok(());
             }
         }
     }
-    Ok(())
 }
 
 async fn apply_lease(
@@ -0,0 +1,104 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use nipart::{
+    MergedNetworkState, NipartDriftEvent, NipartError, NipartEvent,
+    NipartstateInterface,
+};
+
+use super::{
+    commander::{NipartCommander, get_initialized_nics, remove_ready_state},
+    daemon_conf::NipartDaemonConfManager,
+};
+
+impl NipartCommander {
+    /// Start or stop the reconciler according to daemon configuration.
+    pub(crate) async fn start_reconcile(&mut self) {
+        let enabled = NipartDaemonConfManager::get().reconcile.enabled;
+        if let Err(e) = self.reconcile_manager.set_enabled(enabled).await {
+            log::error!("Failed to set reconciler enabled to {enabled}: {e}");
+        }
+    }
+
+    /// Reapply saved state if network state drifted from it, e.g. IP
+    /// address removed by `ip addr del`. NICs not initialized by udev are
+    /// ignored. Caller should hold the [crate::lock::NipartLockManager] lock.
+    pub(crate) async fn reconcile(&mut self) -> Result<(), NipartError> {
+        // Might be disabled by reload after reconcile requested
+        if !NipartDaemonConfManager::get().reconcile.enabled {
+            return Ok(());
+        }
+        if let Some(id) = self.checkpoint.pending_id()? {
+            log::debug!("Checkpoint {id} is pending, skip reconciling");
+            return Ok(());
+        }
+        let mut saved_state = self.conf_manager.query_state().await?;
+        if saved_state.is_empty() {
+            return Ok(());
+        }
+        let iface_names = get_initialized_nics(&saved_state).await?;
+        let mut desired_state =
+            remove_ready_state(&mut saved_state, &iface_names);
+        desired_state.ifaces.unify_veth_and_ethernet();
+
+        let mut cur_state =
+            self.query_network_state(None, Default::default()).await?;
+        cur_state.ifaces.unify_veth_and_ethernet();
+        desired_state.resolve_iface_identifiers(&cur_state)?;
+
+        let merged_state = MergedNetworkState::new(
+            desired_state,
+            cur_state.clone(),
+            Default::default(),
+        )?;
+
+        let routes_drifted = !merged_state.routes.changed_routes.is_empty();
+        let reason = if let Err(e) = merged_state.verify(&cur_state) {
+            e.msg
+        } else if routes_drifted {
+            "Routes drifted from saved state".to_string()
+        } else {
+            log::debug!("Network state is consistent with saved state");
+            return Ok(());
+        };
+
+        let drift_state =
+            match merged_state.gen_state_for_apply().gen_diff(&cur_state) {
+                Ok(s) => s,
+                Err(e) => {
+                    log::debug!("Failed to generate drifted state: {e}");
+                    merged_state.gen_state_for_apply()
+                }
+            };
+        let mut event = NipartDriftEvent::new(
+            drift_state
+                .ifaces
+                .iter()
+                .map(|i| i.name().to_string())
+                .collect(),
+            routes_drifted,
+            reason,
+        );
+        log::info!("Reapplying saved state to fix drift: {}", event.reason);
+        log::debug!("Drifted state {drift_state}");
+
+        self.monitor_manager.pause().await?;
+        let result = self
+            .apply_merged_state(None, &merged_state, &Default::default())
+            .await;
+        self.monitor_manager.resume().await?;
+
+        match result {
+            Ok(()) => {
+                log::info!("Drift from saved state fixed");
+                self.event_emitter.emit(NipartEvent::DriftCorrected(event));
+                Ok(())
+            }
+            Err(e) => {
+                event.error = Some(e.to_string());
+                self.event_emitter
+                    .emit(NipartEvent::DriftCorrectionFailed(event));
+                Err(e)
+            }
+        }
+    }
+}
@@ -0,0 +1,52 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::{Arc, Mutex};
+
+use futures_channel::mpsc::{UnboundedReceiver, UnboundedSender, unbounded};
+use nipart::NipartEvent;
+
+/// Broadcasting [NipartEvent] to all subscribers.
+/// This struct is safe to clone and move to threads.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct NipartEventEmitter {
+    subscribers: Arc<Mutex<Vec<UnboundedSender<NipartEvent>>>>,
+}
+
+impl NipartEventEmitter {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn subscribe(&self) -> UnboundedReceiver<NipartEvent> {
+        let (sender, receiver) = unbounded();
+        match self.subscribers.lock() {
+            Ok(mut subscribers) => subscribers.push(sender),
+            Err(e) => log::error!("BUG: Failed to lock event subscribers: {e}"),
+        }
+        receiver
+    }
+
+    /// Remove closed subscribers and return whether any subscriber remains.
+    pub(crate) fn has_subscriber(&self) -> bool {
+        match self.subscribers.lock() {
+            Ok(mut subscribers) => {
+                subscribers.retain(|s| !s.is_closed());
+                !subscribers.is_empty()
+            }
+            Err(e) => {
+                log::error!("BUG: Failed to lock event subscribers: {e}");
+                false
+            }
+        }
+    }
+
+    pub(crate) fn emit(&self, event: NipartEvent) {
+        log::debug!("Emitting event {event}");
+        match self.subscribers.lock() {
+            Ok(mut subscribers) => {
+                subscribers.retain(|s| s.unbounded_send(event.clone()).is_ok())
+            }
+            Err(e) => log::error!("BUG: Failed to lock event subscribers: {e}"),
+        }
+    }
+}
@@ -4,8 +4,9 @@ use std::time::SystemTime;
 
 use nipart::{
     ErrorKind, Interface, InterfaceState, InterfaceType, MergedNetworkState,
-    NetworkState, NipartError, NipartNoDaemon, NipartstateApplyOption, NipartstateInterface,
-    NipartstateQueryOption, WifiPhyInterface,
+    NetworkState, NipartError, NipartEvent, NipartNoDaemon, NipartWifiEvent,
+    NipartstateApplyOption, NipartstateInterface, NipartstateQueryOption,
+    WifiPhyInterface,
 };
 
 use super::commander::NipartCommander;
@@ -66,6 +67,7 @@ impl NipartCommander {
         {
             match cur_iface {
                 Interface::WifiPhy(wifi_phy_iface) => {
+                    self.emit_wifi_event(&event, wifi_phy_iface);
                     self.handle_wifi_phy_iface(
                         &event,
                         wifi_phy_iface,
@@ -85,6 +87,25 @@ impl NipartCommander {
         Ok(())
     }
 
+    fn emit_wifi_event(
+        &self,
+        event: &NipartLinkEvent,
+        cur_iface: &WifiPhyInterface,
+    ) {
+        let ssid = cur_iface.wifi.as_ref().map(|w| w.ssid.to_string());
+        if event.is_carrier_up() {
+            if ssid.is_some() {
+                self.event_emitter.emit(NipartEvent::WifiAssociated(
+                    NipartWifiEvent::new(event.iface_name.to_string(), ssid),
+                ));
+            }
+        } else if event.is_carrier_down() {
+            self.event_emitter.emit(NipartEvent::WifiDisassociated(
+                NipartWifiEvent::new(event.iface_name.to_string(), ssid),
+            ));
+        }
+    }
+
     async fn handle_wifi_phy_iface(
         &mut self,
         event: &NipartLinkEvent,
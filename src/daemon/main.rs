@@ -2,18 +2,27 @@
 
 mod api;
 mod apply;
+mod checkpoint;
 mod commander;
 mod conf;
 mod daemon;
+mod daemon_conf;
+mod dbus;
 mod dhcp;
+mod drift;
+mod emitter;
 mod event;
 mod lock;
 mod logger;
 mod monitor;
 mod plugin;
 mod query;
+mod reconcile;
+mod restore;
+mod systemd;
 mod task;
 mod udev;
+mod wait_online;
 
 pub(crate) use self::{
     logger::{log_debug, log_error, log_info, log_trace, log_warn},
@@ -1,10 +1,16 @@
 // SPDX-License-Identifier: Apache-2.0
 
+use std::collections::HashMap;
+
 use futures_channel::mpsc::UnboundedSender;
-use nipart::{InterfaceType, NipartError};
+use nipart::{ErrorKind, InterfaceType, NipartError};
 
 use super::{
-    super::daemon::NipartManagerCmd, NipartMonitorCmd, NipartMonitorReply, NipartMonitorWorker,
+    super::{
+        daemon::NipartManagerCmd, emitter::NipartEventEmitter,
+        event::NipartLinkEventType,
+    },
+    NipartMonitorCmd, NipartMonitorReply, NipartMonitorWorker,
 };
 use crate::TaskManager;
 
@@ -17,6 +23,7 @@ pub(crate) struct NipartMonitorManager {
 impl NipartMonitorManager {
     pub(crate) async fn new(
         msg_to_commander: UnboundedSender<NipartManagerCmd>,
+        event_emitter: NipartEventEmitter,
     ) -> Result<Self, NipartError> {
         let mut ret = Self {
             mgr: TaskManager::new::<NipartMonitorWorker>("monitor").await?,
@@ -27,6 +34,9 @@ impl NipartMonitorManager {
                 ret.msg_to_commander.clone(),
             ))
             .await?;
+        ret.mgr
+            .exec(NipartMonitorCmd::SetEventEmitter(event_emitter))
+            .await?;
         Ok(ret)
     }
 
@@ -40,6 +50,45 @@ impl NipartMonitorManager {
         Ok(())
     }
 
+    /// Whether to emit carrier changes of all interfaces to event
+    /// subscribers.
+    pub(crate) async fn set_emit_all(
+        &mut self,
+        value: bool,
+    ) -> Result<(), NipartError> {
+        self.mgr.exec(NipartMonitorCmd::EmitAll(value)).await?;
+        Ok(())
+    }
+
+    /// Whether to notify commander with `NipartManagerCmd::NicAdded` when
+    /// new NIC showing up.
+    pub(crate) async fn set_hotplug(
+        &mut self,
+        value: bool,
+    ) -> Result<(), NipartError> {
+        self.mgr.exec(NipartMonitorCmd::Hotplug(value)).await?;
+        Ok(())
+    }
+
+    /// Last known carrier state of all interfaces, only available after
+    /// `set_emit_all(true)`.
+    pub(crate) async fn query_carriers(
+        &mut self,
+    ) -> Result<HashMap<String, NipartLinkEventType>, NipartError> {
+        let reply = self.mgr.exec(NipartMonitorCmd::QueryCarriers).await?;
+        if let NipartMonitorReply::Carriers(c) = reply {
+            Ok(c)
+        } else {
+            Err(NipartError::new(
+                ErrorKind::Bug,
+                format!(
+                    "NipartMonitorCmd::QueryCarriers is not replying with \
+                     NipartMonitorReply::Carriers, but {reply:?}"
+                ),
+            ))
+        }
+    }
+
     /// Start monitoring on specified interface.
     pub(crate) async fn add_iface_to_monitor(
         &mut self,
@@ -10,7 +10,7 @@ use futures_channel::{
     oneshot::Sender,
 };
 use futures_util::{SinkExt, stream::StreamExt};
-use nipart::{ErrorKind, InterfaceType, NipartError};
+use nipart::{ErrorKind, InterfaceType, NipartError, NipartEvent};
 use rtnetlink::{
     MulticastGroup, new_multicast_connection,
     packet_core::{NetlinkMessage, NetlinkPayload},
@@ -26,19 +26,24 @@ use rtnetlink::{
 
 use super::super::{
     daemon::NipartManagerCmd,
+    daemon_conf::NipartDaemonConfManager,
+    emitter::NipartEventEmitter,
     event::{NipartLinkEvent, NipartLinkEventType},
     task::TaskWorker,
 };
 
-// When the same event happens, how long should consider previous event expired
-// and OK to emit the same event again.
-const EVENT_EXPIRE_TIME_SEC: u64 = 30;
-
 #[derive(Debug, Clone)]
 pub(crate) enum NipartMonitorCmd {
     /// Set the sender for monitor to contact commander. Must be invoked
     /// right after NipartMonitorWorker started.
     SetCommanderSender(UnboundedSender<NipartManagerCmd>),
+    /// Set the emitter for sending events to subscribers. Must be invoked
+    /// right after NipartMonitorWorker started.
+    SetEventEmitter(NipartEventEmitter),
+    /// Whether to emit carrier change of all interfaces to event subscribers
+    EmitAll(bool),
+    /// Whether to notify commander when new NIC added
+    Hotplug(bool),
     /// Start monitoring on specified interface type
     AddIfaceType(InterfaceType),
     /// Stop monitoring on specified interface type
@@ -52,6 +57,9 @@ pub(crate) enum NipartMonitorCmd {
     /// Resume the monitoring, emit current status of monitoring
     /// interface list.
     Resume,
+    /// Query last known carrier state of all interfaces, only available when
+    /// `EmitAll(true)`.
+    QueryCarriers,
 }
 
 impl std::fmt::Display for NipartMonitorCmd {
@@ -60,6 +68,15 @@ impl std::fmt::Display for NipartMonitorCmd {
             Self::SetCommanderSender(_) => {
                 write!(f, "set-commander-sender")
             }
+            Self::SetEventEmitter(_) => {
+                write!(f, "set-event-emitter")
+            }
+            Self::EmitAll(v) => {
+                write!(f, "emit-all:{v}")
+            }
+            Self::Hotplug(v) => {
+                write!(f, "hotplug:{v}")
+            }
             Self::AddIface(iface) => {
                 write!(f, "start-iface-monitor:{iface}")
             }
@@ -78,6 +95,9 @@ impl std::fmt::Display for NipartMonitorCmd {
             Self::Resume => {
                 write!(f, "resume-monitor")
             }
+            Self::QueryCarriers => {
+                write!(f, "query-carriers")
+            }
         }
     }
 }
@@ -85,6 +105,7 @@ impl std::fmt::Display for NipartMonitorCmd {
 #[derive(Debug, Clone, PartialEq)]
 pub(crate) enum NipartMonitorReply {
     None,
+    Carriers(HashMap<String, NipartLinkEventType>),
 }
 
 type FromManager = (NipartMonitorCmd, Sender<Result<NipartMonitorReply, NipartError>>);
@@ -101,6 +122,14 @@ pub(crate) struct NipartMonitorWorker {
     msg_to_commander: Option<UnboundedSender<NipartManagerCmd>>,
     manual_paused: bool,
     emited: HashMap<String, NipartLinkEvent>,
+    event_emitter: Option<NipartEventEmitter>,
+    emit_all: bool,
+    // Last known carrier state of all interfaces, only used when `emit_all`
+    // is true.
+    link_states: HashMap<String, NipartLinkEventType>,
+    hotplug: bool,
+    // Interface index of known NICs, only used when `hotplug` is true.
+    known_nics: Option<HashSet<u32>>,
 }
 
 impl TaskWorker for NipartMonitorWorker {
@@ -119,6 +148,11 @@ impl TaskWorker for NipartMonitorWorker {
             manual_paused: false,
             msg_to_commander: None,
             emited: HashMap::new(),
+            event_emitter: None,
+            emit_all: false,
+            link_states: HashMap::new(),
+            hotplug: false,
+            known_nics: None,
         })
     }
 
@@ -135,6 +169,39 @@ impl TaskWorker for NipartMonitorWorker {
             NipartMonitorCmd::SetCommanderSender(sender) => {
                 self.msg_to_commander = Some(sender);
             }
+            NipartMonitorCmd::SetEventEmitter(emitter) => {
+                self.event_emitter = Some(emitter);
+            }
+            NipartMonitorCmd::EmitAll(v) => {
+                self.emit_all = v;
+                if v {
+                    if self.netlink_msg_receiver.is_none()
+                        && !self.manual_paused
+                    {
+                        self.resume().await?;
+                    }
+                } else {
+                    self.link_states.clear();
+                    if !self.is_monitoring() {
+                        self.pause();
+                    }
+                }
+            }
+            NipartMonitorCmd::Hotplug(v) => {
+                self.hotplug = v;
+                if v {
+                    if self.netlink_msg_receiver.is_none()
+                        && !self.manual_paused
+                    {
+                        self.resume().await?;
+                    }
+                } else {
+                    self.known_nics = None;
+                    if !self.is_monitoring() {
+                        self.pause();
+                    }
+                }
+            }
             NipartMonitorCmd::AddIface(iface) => {
                 self.iface_monitor_list.insert(iface);
                 if self.netlink_msg_receiver.is_none() && !self.manual_paused {
@@ -143,7 +210,7 @@ impl TaskWorker for NipartMonitorWorker {
             }
             NipartMonitorCmd::DelIface(iface) => {
                 self.iface_monitor_list.remove(&iface);
-                if self.iface_monitor_list.is_empty() {
+                if !self.is_monitoring() {
                     self.pause();
                 }
             }
@@ -155,7 +222,7 @@ impl TaskWorker for NipartMonitorWorker {
             }
             NipartMonitorCmd::DelIfaceType(v) => {
                 self.iface_type_monitor_list.remove(&v);
-                if self.iface_type_monitor_list.is_empty() {
+                if !self.is_monitoring() {
                     self.pause();
                 }
             }
@@ -165,12 +232,15 @@ impl TaskWorker for NipartMonitorWorker {
             }
             NipartMonitorCmd::Resume => {
                 self.manual_paused = false;
-                if !self.iface_monitor_list.is_empty()
-                    || !self.iface_type_monitor_list.is_empty()
-                {
+                if self.is_monitoring() {
                     self.resume().await?;
                 }
             }
+            NipartMonitorCmd::QueryCarriers => {
+                return Ok(NipartMonitorReply::Carriers(
+                    self.link_states.clone(),
+                ));
+            }
         }
         Ok(NipartMonitorReply::None)
     }
@@ -221,6 +291,13 @@ impl TaskWorker for NipartMonitorWorker {
 }
 
 impl NipartMonitorWorker {
+    fn is_monitoring(&self) -> bool {
+        self.emit_all
+            || self.hotplug
+            || !self.iface_monitor_list.is_empty()
+            || !self.iface_type_monitor_list.is_empty()
+    }
+
     fn pause(&mut self) {
         self.netlink_handle = None;
         self.netlink_msg_receiver = None;
@@ -228,8 +305,19 @@ impl NipartMonitorWorker {
 
     async fn notify(&mut self, event: NipartLinkEvent) -> Result<(), NipartError> {
         log::trace!("NipartMonitorWorker sending out {event:?}");
+        self.send_to_commander(NipartManagerCmd::LinkEvent(Box::new(
+            event.clone(),
+        )))
+        .await?;
+        self.emited.insert(event.iface_name.to_string(), event);
+        Ok(())
+    }
+
+    async fn send_to_commander(
+        &mut self,
+        cmd: NipartManagerCmd,
+    ) -> Result<(), NipartError> {
         if let Some(sender) = self.msg_to_commander.as_mut() {
-            let cmd = NipartManagerCmd::LinkEvent(Box::new(event.clone()));
             sender.send(cmd).await.map_err(|e| {
                 NipartError::new(
                     ErrorKind::Bug,
@@ -237,9 +325,7 @@ impl NipartMonitorWorker {
                         "NipartMonitorWorker: Failed to send to commander: {e}"
                     ),
                 )
-            })?;
-            self.emited.insert(event.iface_name.to_string(), event);
-            Ok(())
+            })
         } else {
             Err(NipartError::new(
                 ErrorKind::Bug,
@@ -263,12 +349,13 @@ impl NipartMonitorWorker {
             })?;
         tokio::spawn(conn);
 
+        // NICs found in the first dump after hotplug enabled are existing ones
+        let notify_new_nic = self.known_nics.is_some();
         let mut link_handle = handle.link().get().execute();
         while let Some(Ok(link_msg)) = link_handle.next().await {
-            if let Some(event) = parse_link_msg(&link_msg)
-                && self.should_emit(&event)
-            {
-                self.notify(event).await?;
+            self.check_new_nic(&link_msg, notify_new_nic).await?;
+            if let Some(event) = parse_link_msg(&link_msg) {
+                self.process_link_event(event).await?;
             }
         }
 
@@ -281,21 +368,102 @@ impl NipartMonitorWorker {
         &mut self,
         nl_msg: NetlinkMessage<RouteNetlinkMessage>,
     ) -> Result<(), NipartError> {
-        if let Some(event) = parse_route_netlink_msg(nl_msg)
-            && self.should_emit(&event)
+        match nl_msg.payload {
+            NetlinkPayload::InnerMessage(RouteNetlinkMessage::NewLink(
+                link_msg,
+            )) => {
+                self.check_new_nic(&link_msg, true).await?;
+                if let Some(event) = parse_link_msg(&link_msg) {
+                    self.process_link_event(event).await?;
+                }
+            }
+            NetlinkPayload::InnerMessage(RouteNetlinkMessage::DelLink(
+                link_msg,
+            )) => {
+                if let Some(known_nics) = self.known_nics.as_mut() {
+                    known_nics.remove(&link_msg.header.index);
+                }
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    /// Notify commander when NIC not seen before showing up.
+    async fn check_new_nic(
+        &mut self,
+        link_msg: &LinkMessage,
+        notify: bool,
+    ) -> Result<(), NipartError> {
+        if !self.hotplug
+            || !matches!(
+                parse_iface_type_from_nl_msg(link_msg),
+                InterfaceType::Ethernet | InterfaceType::InfiniBand
+            )
         {
+            return Ok(());
+        }
+        let iface_index = link_msg.header.index;
+        if self
+            .known_nics
+            .get_or_insert_with(HashSet::new)
+            .insert(iface_index)
+            && notify
+        {
+            log::debug!("NIC with interface index {iface_index} added");
+            self.send_to_commander(NipartManagerCmd::NicAdded(iface_index))
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn process_link_event(
+        &mut self,
+        event: NipartLinkEvent,
+    ) -> Result<(), NipartError> {
+        if self.emit_all {
+            self.emit_carrier_change(&event);
+        }
+        if self.should_emit(&event) {
             self.notify(event).await?;
         }
         Ok(())
     }
 
+    /// Emit event to subscribers only when carrier changed from previous
+    /// known state.
+    fn emit_carrier_change(&mut self, event: &NipartLinkEvent) {
+        let pre_state = self
+            .link_states
+            .insert(event.iface_name.to_string(), event.event_type);
+        if pre_state.is_none() || pre_state == Some(event.event_type) {
+            return;
+        }
+        if let Some(emitter) = self.event_emitter.as_ref() {
+            emitter.emit(match event.event_type {
+                NipartLinkEventType::CarrierUp => {
+                    NipartEvent::CarrierUp(event.iface_name.to_string())
+                }
+                NipartLinkEventType::CarrierDown => {
+                    NipartEvent::CarrierDown(event.iface_name.to_string())
+                }
+            });
+        }
+    }
+
+    // When the same event happens, how long should consider previous event
+    // expired and OK to emit the same event again.
     fn is_previous_event_expired(&self, event: &NipartLinkEvent) -> bool {
         if let Some(previous_event) = self.emited.get(event.iface_name.as_str())
             && let Ok(elapsed) = previous_event.time_stamp.elapsed()
         {
             previous_event.event_type != event.event_type
                 || elapsed
-                    > std::time::Duration::from_secs(EVENT_EXPIRE_TIME_SEC)
+                    > std::time::Duration::from_secs(
+                        NipartDaemonConfManager::get()
+                            .monitor
+                            .event_expire_time_sec,
+                    )
         } else {
             true
         }
@@ -337,19 +505,6 @@ fn parse_link_msg(link_msg: &LinkMessage) -> Option<NipartLinkEvent> {
     Some(NipartLinkEvent::new(iface_name, iface_type, event_type))
 }
 
-fn parse_route_netlink_msg(
-    nl_msg: NetlinkMessage<RouteNetlinkMessage>,
-) -> Option<NipartLinkEvent> {
-    if let NetlinkPayload::InnerMessage(RouteNetlinkMessage::NewLink(
-        link_msg,
-    )) = nl_msg.payload
-    {
-        parse_link_msg(&link_msg)
-    } else {
-        None
-    }
-}
-
 fn parse_iface_type_from_nl_msg(link_msg: &LinkMessage) -> InterfaceType {
     if let Some(link_infos) = link_msg.attributes.iter().find_map(|attr| {
         if let LinkAttribute::LinkInfo(infos) = attr {
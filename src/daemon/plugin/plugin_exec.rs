@@ -1,9 +1,16 @@
 // SPDX-License-Identifier: Apache-2.0
 
+use std::time::Duration;
+
 use nipart::{
-    NetworkState, NipartError, NipartPluginClient, NipartPluginInfo,
-    NipartstateApplyOption, NipartstateInterface, NipartstateQueryOption,
+    ErrorKind, NetworkState, NipartError, NipartEvent, NipartLogEntry,
+    NipartPluginClient, NipartPluginInfo, NipartstateApplyOption,
+    NipartstateInterface, NipartstateQueryOption, NipartstateSection,
+    PluginStates,
 };
+use tokio::sync::mpsc::{UnboundedSender, unbounded_channel};
+
+use crate::daemon_conf::NipartDaemonConfManager;
 
 #[derive(Debug, Clone)]
 pub(crate) struct NipartDaemonPlugin {
@@ -13,24 +20,31 @@ pub(crate) struct NipartDaemonPlugin {
 }
 
 impl NipartDaemonPlugin {
-    // TODO(Gris Ge):
-    // * Timeout
-    // * Ignore failure of plugins
     pub(crate) async fn query_network_state(
         &self,
         opt: &NipartstateQueryOption,
+        log_sender: Option<&UnboundedSender<NipartLogEntry>>,
     ) -> Result<NetworkState, NipartError> {
-        let mut cli = NipartPluginClient::new(&self.socket_path).await?;
-        cli.query_network_state(opt.clone()).await
+        let mut state = self
+            .with_timeout(async {
+                let mut cli = self.connect(log_sender).await?;
+                cli.query_network_state(opt.clone()).await
+            })
+            .await?;
+        // Plugin may only report state owned by itself in `plugins` section
+        let own_state = state.plugins.remove(&self.name);
+        state.plugins = PluginStates::new();
+        if let Some(own_state) = own_state {
+            state.plugins.insert(self.name.clone(), own_state);
+        }
+        Ok(state)
     }
 
-    // TODO(Gris Ge):
-    // * Timeout
-    // * Ignore failure of plugins
     pub(crate) async fn apply_network_state(
         &self,
         apply_state: &NetworkState,
         opt: &NipartstateApplyOption,
+        log_sender: Option<&UnboundedSender<NipartLogEntry>>,
     ) -> Result<(), NipartError> {
         let mut new_state = NetworkState::new();
         // Include only interfaces supported by plugin
@@ -39,6 +53,27 @@ impl NipartDaemonPlugin {
                 new_state.ifaces.push(iface.clone());
             }
         }
+        // Include only plugin state owned by plugin
+        if let Some(value) = apply_state.plugins.get(&self.name) {
+            new_state.plugins.insert(self.name.clone(), value.clone());
+        }
+        // Include only top level sections owned by plugin
+        for section in self.plugin_info.capabilities.sections.iter() {
+            match section {
+                NipartstateSection::Routes => {
+                    new_state.routes = apply_state.routes.clone();
+                }
+                NipartstateSection::IpForwarding => {
+                    new_state.ip_forwarding = apply_state.ip_forwarding.clone();
+                }
+                _ => {
+                    log::debug!(
+                        "Plugin {} owning unknown section {section:?}",
+                        self.name
+                    );
+                }
+            }
+        }
         if new_state.is_empty() {
             log::trace!("No state require {} to apply", self.name);
             Ok(())
@@ -49,8 +84,109 @@ impl NipartDaemonPlugin {
                 new_state
             );
 
-            let mut cli = NipartPluginClient::new(&self.socket_path).await?;
-            cli.apply_network_state(new_state, opt.clone()).await
+            self.with_timeout(async {
+                let mut cli = self.connect(log_sender).await?;
+                cli.apply_network_state(new_state, opt.clone()).await
+            })
+            .await
+        }
+    }
+
+    pub(crate) async fn validate_network_state(
+        &self,
+        desired_state: &NetworkState,
+        log_sender: Option<&UnboundedSender<NipartLogEntry>>,
+    ) -> Result<(), NipartError> {
+        self.with_timeout(async {
+            let mut cli = self.connect(log_sender).await?;
+            cli.validate_network_state(desired_state.clone()).await
+        })
+        .await
+    }
+
+    pub(crate) async fn pre_apply(
+        &self,
+        merged_state: &NetworkState,
+        log_sender: Option<&UnboundedSender<NipartLogEntry>>,
+    ) -> Result<(), NipartError> {
+        self.with_timeout(async {
+            let mut cli = self.connect(log_sender).await?;
+            cli.pre_apply(merged_state.clone()).await
+        })
+        .await
+    }
+
+    pub(crate) async fn post_apply(
+        &self,
+        applied_state: &NetworkState,
+        result: &Result<(), NipartError>,
+        log_sender: Option<&UnboundedSender<NipartLogEntry>>,
+    ) -> Result<(), NipartError> {
+        self.with_timeout(async {
+            let mut cli = self.connect(log_sender).await?;
+            cli.post_apply(applied_state.clone(), result.clone()).await
+        })
+        .await
+    }
+
+    /// Connect to plugin with log entries sent by plugin forwarded to
+    /// `log_sender` using plugin name as source.
+    async fn connect(
+        &self,
+        log_sender: Option<&UnboundedSender<NipartLogEntry>>,
+    ) -> Result<NipartPluginClient, NipartError> {
+        let mut cli = NipartPluginClient::new(&self.socket_path).await?;
+        // Timeout is handled by `with_timeout()`
+        cli.set_timeout(u32::MAX);
+        if let Some(log_sender) = log_sender.cloned() {
+            let (sender, mut receiver) = unbounded_channel::<NipartLogEntry>();
+            let name = self.name.clone();
+            // Stop when `cli` dropped or `log_sender` closed
+            tokio::spawn(async move {
+                while let Some(mut entry) = receiver.recv().await {
+                    entry.source = name.clone();
+                    if log_sender.send(entry).is_err() {
+                        break;
+                    }
+                }
+            });
+            cli.set_log_forwarder(Some(sender));
         }
+        Ok(cli)
+    }
+
+    async fn with_timeout<T>(
+        &self,
+        future: impl Future<Output = Result<T, NipartError>>,
+    ) -> Result<T, NipartError> {
+        let timeout_ms = NipartDaemonConfManager::get().plugin.call_timeout_ms;
+        tokio::time::timeout(Duration::from_millis(timeout_ms.into()), future)
+            .await
+            .map_err(|_| {
+                NipartError::new(
+                    ErrorKind::Timeout,
+                    format!(
+                        "Plugin {} did not reply in {timeout_ms} milliseconds",
+                        self.name
+                    ),
+                )
+            })?
+    }
+
+    pub(crate) async fn notify_event(
+        &self,
+        event: &NipartEvent,
+    ) -> Result<(), NipartError> {
+        self.with_timeout(async {
+            let mut cli = self.connect(None).await?;
+            cli.notify_event(event.clone()).await
+        })
+        .await
+    }
+
+    pub(crate) async fn quit(&self) -> Result<(), NipartError> {
+        log::debug!("Requesting plugin {} to quit", self.name);
+        let mut cli = NipartPluginClient::new(&self.socket_path).await?;
+        cli.quit().await
     }
 }
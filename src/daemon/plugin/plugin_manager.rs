@@ -1,8 +1,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use nipart::{
-    ErrorKind, NetworkState, NipartError, NipartstateApplyOption, NipartstateQueryOption,
+    ErrorKind, NetworkState, NipartError, NipartEvent, NipartIpcConnection,
+    NipartLogEntry, NipartstateApplyOption, NipartstateQueryOption,
 };
+use tokio::sync::mpsc::{UnboundedSender, unbounded_channel};
 
 use super::{NipartPluginCmd, NipartPluginReply, NipartPluginWorker};
 use crate::TaskManager;
@@ -19,14 +21,17 @@ impl NipartPluginManager {
         })
     }
 
-    // TODO: Support redirect logs from plugin to user
+    /// Log entries sent by plugins will be forwarded to `conn` with plugin
+    /// name as source.
     pub(crate) async fn query_network_state(
         &mut self,
+        conn: Option<&mut NipartIpcConnection>,
         opt: NipartstateQueryOption,
     ) -> Result<Vec<NetworkState>, NipartError> {
         let reply = self
-            .mgr
-            .exec(NipartPluginCmd::QueryNetworkState(Box::new(opt)))
+            .exec_with_log(conn, |log_sender| {
+                NipartPluginCmd::QueryNetworkState(Box::new((opt, log_sender)))
+            })
             .await?;
         if let NipartPluginReply::States(s) = reply {
             Ok(s)
@@ -41,18 +46,139 @@ impl NipartPluginManager {
         }
     }
 
-    // TODO: Support redirect logs from plugin to user
+    /// Log entries sent by plugins will be forwarded to `conn` with plugin
+    /// name as source.
     pub(crate) async fn apply_network_state(
         &mut self,
+        conn: Option<&mut NipartIpcConnection>,
         state: &NetworkState,
         opt: &NipartstateApplyOption,
     ) -> Result<(), NipartError> {
-        self.mgr
-            .exec(NipartPluginCmd::ApplyNetworkState(Box::new((
+        self.exec_with_log(conn, |log_sender| {
+            NipartPluginCmd::ApplyNetworkState(Box::new((
                 state.clone(),
                 opt.clone(),
-            ))))
+                log_sender,
+            )))
+        })
+        .await?;
+        Ok(())
+    }
+
+    /// Fail if any plugin rejected the desired state.
+    pub(crate) async fn validate_network_state(
+        &mut self,
+        conn: Option<&mut NipartIpcConnection>,
+        desired_state: &NetworkState,
+    ) -> Result<(), NipartError> {
+        self.exec_with_log(conn, |log_sender| {
+            NipartPluginCmd::ValidateNetworkState(Box::new((
+                desired_state.clone(),
+                log_sender,
+            )))
+        })
+        .await?;
+        Ok(())
+    }
+
+    /// Fail if any plugin rejected the merged state.
+    pub(crate) async fn pre_apply(
+        &mut self,
+        conn: Option<&mut NipartIpcConnection>,
+        merged_state: &NetworkState,
+    ) -> Result<(), NipartError> {
+        self.exec_with_log(conn, |log_sender| {
+            NipartPluginCmd::PreApply(Box::new((
+                merged_state.clone(),
+                log_sender,
+            )))
+        })
+        .await?;
+        Ok(())
+    }
+
+    /// Failures of plugins are only logged.
+    pub(crate) async fn post_apply(
+        &mut self,
+        conn: Option<&mut NipartIpcConnection>,
+        applied_state: &NetworkState,
+        result: &Result<(), NipartError>,
+    ) -> Result<(), NipartError> {
+        self.exec_with_log(conn, |log_sender| {
+            NipartPluginCmd::PostApply(Box::new((
+                applied_state.clone(),
+                result.clone(),
+                log_sender,
+            )))
+        })
+        .await?;
+        Ok(())
+    }
+
+    /// Whether any plugin wants to be notified on daemon events
+    pub(crate) async fn has_event_listener(
+        &mut self,
+    ) -> Result<bool, NipartError> {
+        let reply = self.mgr.exec(NipartPluginCmd::HasEventListener).await?;
+        if let NipartPluginReply::Bool(v) = reply {
+            Ok(v)
+        } else {
+            Err(NipartError::new(
+                ErrorKind::Bug,
+                format!(
+                    "NipartPluginCmd::HasEventListener is not replying with \
+                     NipartPluginReply::Bool, but {reply:?}"
+                ),
+            ))
+        }
+    }
+
+    /// Notify plugins which want daemon events
+    pub(crate) async fn notify_event(
+        &mut self,
+        event: NipartEvent,
+    ) -> Result<(), NipartError> {
+        self.mgr
+            .exec(NipartPluginCmd::Event(Box::new(event)))
             .await?;
         Ok(())
     }
+
+    /// Request all plugins to quit
+    pub(crate) async fn quit(&mut self) -> Result<(), NipartError> {
+        self.mgr.exec(NipartPluginCmd::Quit).await?;
+        Ok(())
+    }
+
+    async fn exec_with_log<F>(
+        &mut self,
+        conn: Option<&mut NipartIpcConnection>,
+        gen_cmd: F,
+    ) -> Result<NipartPluginReply, NipartError>
+    where
+        F: FnOnce(Option<UnboundedSender<NipartLogEntry>>) -> NipartPluginCmd,
+    {
+        let Some(conn) = conn else {
+            return self.mgr.exec(gen_cmd(None)).await;
+        };
+        let (log_sender, mut log_receiver) =
+            unbounded_channel::<NipartLogEntry>();
+        let result = self.mgr.exec(gen_cmd(Some(log_sender)));
+        tokio::pin!(result);
+        loop {
+            tokio::select! {
+                result = &mut result => {
+                    // Flush log entries not forwarded yet, the receiver will
+                    // be closed once all plugin connections dropped.
+                    while let Some(entry) = log_receiver.recv().await {
+                        conn.log(entry).await.ok();
+                    }
+                    return result;
+                }
+                Some(entry) = log_receiver.recv() => {
+                    conn.log(entry).await.ok();
+                }
+            }
+        }
+    }
 }
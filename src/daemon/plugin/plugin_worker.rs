@@ -4,26 +4,62 @@ use std::{
     collections::HashMap,
     env::current_exe,
     os::unix::fs::{FileTypeExt, PermissionsExt},
+    path::Path,
+    process::Child,
+    time::{Duration, Instant},
 };
 
 use futures_channel::{mpsc::UnboundedReceiver, oneshot::Sender};
-use futures_util::{StreamExt, stream::FuturesUnordered};
+use futures_util::future::join_all;
 use nipart::{
-    NetworkState, NipartError, NipartPluginClient, NipartstateApplyOption,
-    NipartstateQueryOption,
+    ErrorKind, NetworkState, NipartError, NipartEvent, NipartLogEntry,
+    NipartPluginClient, NipartPluginCmdKind, NipartPluginInfo,
+    NipartPluginPolicy, NipartstateApplyOption, NipartstateQueryOption,
 };
+use tokio::{sync::mpsc::UnboundedSender, task::JoinHandle};
 
 use super::plugin_exec::NipartDaemonPlugin;
-use crate::TaskWorker;
+use crate::{TaskWorker, daemon_conf::NipartDaemonConfManager};
 
 const NM_PLUGIN_PREFIX: &str = "nipart-plugin-";
-const NM_PLUGIN_CONN_RETRY: i8 = 50;
-const NM_PLUGIN_CONN_RETRY_INTERVAL_MS: u64 = 200;
 
-#[derive(Debug, Clone, PartialEq)]
+/// Log entries sent by plugins during the command will be forwarded to the
+/// optional `UnboundedSender<NipartLogEntry>`.
+#[derive(Debug, Clone)]
 pub(crate) enum NipartPluginCmd {
-    QueryNetworkState(Box<NipartstateQueryOption>),
-    ApplyNetworkState(Box<(NetworkState, NipartstateApplyOption)>),
+    QueryNetworkState(
+        Box<(
+            NipartstateQueryOption,
+            Option<UnboundedSender<NipartLogEntry>>,
+        )>,
+    ),
+    ApplyNetworkState(
+        Box<(
+            NetworkState,
+            NipartstateApplyOption,
+            Option<UnboundedSender<NipartLogEntry>>,
+        )>,
+    ),
+    /// Request plugins to validate desired state
+    ValidateNetworkState(
+        Box<(NetworkState, Option<UnboundedSender<NipartLogEntry>>)>,
+    ),
+    /// Notify plugins on merged state about to apply
+    PreApply(Box<(NetworkState, Option<UnboundedSender<NipartLogEntry>>)>),
+    /// Notify plugins on merged state applied with its result
+    PostApply(
+        Box<(
+            NetworkState,
+            Result<(), NipartError>,
+            Option<UnboundedSender<NipartLogEntry>>,
+        )>,
+    ),
+    /// Request all plugins to quit
+    Quit,
+    /// Whether any plugin wants to be notified on daemon events
+    HasEventListener,
+    /// Notify plugins which want daemon events
+    Event(Box<NipartEvent>),
 }
 
 impl std::fmt::Display for NipartPluginCmd {
@@ -35,6 +71,24 @@ impl std::fmt::Display for NipartPluginCmd {
             Self::ApplyNetworkState(_) => {
                 write!(f, "apply-network-state")
             }
+            Self::ValidateNetworkState(_) => {
+                write!(f, "validate-network-state")
+            }
+            Self::PreApply(_) => {
+                write!(f, "pre-apply")
+            }
+            Self::PostApply(_) => {
+                write!(f, "post-apply")
+            }
+            Self::Quit => {
+                write!(f, "quit")
+            }
+            Self::HasEventListener => {
+                write!(f, "has-event-listener")
+            }
+            Self::Event(_) => {
+                write!(f, "event")
+            }
         }
     }
 }
@@ -43,6 +97,7 @@ impl std::fmt::Display for NipartPluginCmd {
 pub(crate) enum NipartPluginReply {
     None,
     States(Vec<NetworkState>),
+    Bool(bool),
 }
 
 type FromManager = (
@@ -54,6 +109,13 @@ type FromManager = (
 pub(crate) struct NipartPluginWorker {
     receiver: UnboundedReceiver<FromManager>,
     plugins: HashMap<String, NipartDaemonPlugin>,
+    // Count of plugin executables started
+    expected_plugin_count: usize,
+    // Plugins refused due to incompatibility, indexed by socket path with
+    // value of plugin name and reason.
+    refused: HashMap<String, (String, NipartError)>,
+    // Tasks restarting crashed plugin executables
+    supervisors: Vec<JoinHandle<()>>,
 }
 
 impl TaskWorker for NipartPluginWorker {
@@ -63,9 +125,16 @@ impl TaskWorker for NipartPluginWorker {
     async fn new(
         receiver: UnboundedReceiver<FromManager>,
     ) -> Result<Self, NipartError> {
-        let plugin_paths = get_plugin_files();
+        let conf = NipartDaemonConfManager::get();
+        let plugin_paths = match conf.plugin.search_dirs.as_ref() {
+            Some(dirs) => {
+                dirs.iter().flat_map(|d| get_plugin_files(d)).collect()
+            }
+            None => get_plugin_files_in_exe_dir(),
+        };
 
         let mut expected_plugin_count = 0;
+        let mut supervisors = Vec::new();
         for plugin_path in plugin_paths {
             if std::path::Path::new(&plugin_path)
                 .file_name()
@@ -76,25 +145,51 @@ impl TaskWorker for NipartPluginWorker {
                 continue;
             }
             log::debug!("Starting nipart plugin {}", plugin_path);
-            if let Err(e) = std::process::Command::new(&plugin_path).spawn() {
-                log::info!("Ignoring plugin {plugin_path} due to error: {e}");
+            match start_plugin(&plugin_path, &conf.plugin.socket_dir) {
+                Ok(child) => {
+                    supervisors.push(tokio::spawn(supervise_plugin(
+                        plugin_path,
+                        conf.plugin.socket_dir.clone(),
+                        child,
+                    )));
+                    expected_plugin_count += 1;
+                }
+                Err(e) => {
+                    log::info!(
+                        "Ignoring plugin {plugin_path} due to error: {e}"
+                    );
+                }
             }
-            expected_plugin_count += 1;
         }
 
         let mut plugins: HashMap<String, NipartDaemonPlugin> = HashMap::new();
-        let mut retry_left = NM_PLUGIN_CONN_RETRY;
+        let mut refused: HashMap<String, (String, NipartError)> =
+            HashMap::new();
+        let mut retry_count = 0;
 
-        while plugins.len() < expected_plugin_count && retry_left >= 0 {
-            retry_left -= 1;
-            connect_plugins(&mut plugins).await;
+        while plugins.len() + refused.len() < expected_plugin_count
+            && retry_count <= conf.plugin.connect_retry_count
+        {
+            retry_count += 1;
+            connect_plugins(
+                &mut plugins,
+                &mut refused,
+                &conf.plugin.socket_dir,
+            )
+            .await;
             tokio::time::sleep(std::time::Duration::from_millis(
-                NM_PLUGIN_CONN_RETRY_INTERVAL_MS,
+                conf.plugin.connect_retry_interval_ms,
             ))
             .await;
         }
 
-        Ok(Self { receiver, plugins })
+        Ok(Self {
+            receiver,
+            plugins,
+            expected_plugin_count,
+            refused,
+            supervisors,
+        })
     }
 
     fn receiver(&mut self) -> &mut UnboundedReceiver<FromManager> {
@@ -107,61 +202,394 @@ impl TaskWorker for NipartPluginWorker {
     ) -> Result<NipartPluginReply, NipartError> {
         log::debug!("Processing plugin command: {cmd}");
         match cmd {
-            NipartPluginCmd::QueryNetworkState(opt) => {
-                let mut ret = Vec::new();
-                // TODO(Gris Ge): Should querying all plugin at the same time
-                // instead of one by one.
-                for plugin in self.plugins.values() {
-                    match plugin.query_network_state(&opt).await {
-                        Ok(net_state) => ret.push(net_state),
-                        Err(e) => {
-                            log::info!("{e}");
+            NipartPluginCmd::QueryNetworkState(v) => {
+                let (opt, log_sender) = *v;
+                self.check_required_plugins().await?;
+                let plugins =
+                    self.sorted_plugins(NipartPluginCmdKind::QueryNetworkState);
+                let results = join_all(plugins.iter().map(|plugin| {
+                    plugin.query_network_state(&opt, log_sender.as_ref())
+                }))
+                .await;
+                let results = plugins
+                    .into_iter()
+                    .zip(results)
+                    .filter(|(plugin, result)| {
+                        if let Err(e) = result
+                            && e.kind == ErrorKind::NoSupport
+                        {
+                            log::debug!("Plugin {}: {e}", plugin.name);
+                            false
+                        } else {
+                            true
                         }
-                    }
-                }
+                    })
+                    .collect();
 
-                Ok(NipartPluginReply::States(ret))
+                Ok(NipartPluginReply::States(check_plugin_results(results)?))
             }
             NipartPluginCmd::ApplyNetworkState(v) => {
-                let (apply_state, opt) = *v;
-                // TODO(Gris Ge): Should request all plugin at the same time
-                // instead of one by one.
-                let mut result_futures = FuturesUnordered::new();
-                for plugin in self.plugins.values() {
-                    let result_future =
-                        plugin.apply_network_state(&apply_state, &opt);
-                    result_futures.push(result_future);
-                }
-
-                while let Some(result) = result_futures.next().await {
-                    // It is OK for plugin to fail, verification process will
-                    // noticed the difference
+                let (apply_state, opt, log_sender) = *v;
+                self.check_required_plugins().await?;
+                let plugins =
+                    self.sorted_plugins(NipartPluginCmdKind::ApplyNetworkState);
+                let results = join_all(plugins.iter().map(|plugin| {
+                    plugin.apply_network_state(
+                        &apply_state,
+                        &opt,
+                        log_sender.as_ref(),
+                    )
+                }))
+                .await;
+                // It is OK for optional plugin to fail, verification process
+                // will noticed the difference
+                check_plugin_results(
+                    plugins.into_iter().zip(results).collect(),
+                )?;
+                Ok(NipartPluginReply::None)
+            }
+            NipartPluginCmd::ValidateNetworkState(v) => {
+                let (desired_state, log_sender) = *v;
+                self.check_required_plugins().await?;
+                self.check_plugin_states(&desired_state)?;
+                let plugins = self
+                    .sorted_plugins(NipartPluginCmdKind::ValidateNetworkState);
+                let results = join_all(plugins.iter().map(|plugin| {
+                    plugin.validate_network_state(
+                        &desired_state,
+                        log_sender.as_ref(),
+                    )
+                }))
+                .await;
+                check_veto_results(plugins.into_iter().zip(results).collect())?;
+                Ok(NipartPluginReply::None)
+            }
+            NipartPluginCmd::PreApply(v) => {
+                let (merged_state, log_sender) = *v;
+                self.check_required_plugins().await?;
+                let plugins =
+                    self.sorted_plugins(NipartPluginCmdKind::PreApply);
+                let results = join_all(plugins.iter().map(|plugin| {
+                    plugin.pre_apply(&merged_state, log_sender.as_ref())
+                }))
+                .await;
+                check_veto_results(plugins.into_iter().zip(results).collect())?;
+                Ok(NipartPluginReply::None)
+            }
+            NipartPluginCmd::PostApply(v) => {
+                let (applied_state, result, log_sender) = *v;
+                let plugins =
+                    self.sorted_plugins(NipartPluginCmdKind::PostApply);
+                let results = join_all(plugins.iter().map(|plugin| {
+                    plugin.post_apply(
+                        &applied_state,
+                        &result,
+                        log_sender.as_ref(),
+                    )
+                }))
+                .await;
+                for (plugin, result) in plugins.into_iter().zip(results) {
                     if let Err(e) = result {
-                        log::warn!("{e}");
+                        log::warn!(
+                            "Plugin {} failed on post-apply: {e}",
+                            plugin.name
+                        );
+                    }
+                }
+                Ok(NipartPluginReply::None)
+            }
+            NipartPluginCmd::Quit => {
+                // Stop restarting plugins before requesting them to quit
+                for supervisor in self.supervisors.drain(..) {
+                    supervisor.abort();
+                }
+                for (name, plugin) in self.plugins.drain() {
+                    if let Err(e) = plugin.quit().await {
+                        log::info!("Failed to request plugin {name} quit: {e}");
                     }
                 }
                 Ok(NipartPluginReply::None)
             }
+            NipartPluginCmd::HasEventListener => Ok(NipartPluginReply::Bool(
+                self.plugins
+                    .values()
+                    .any(|p| p.plugin_info.capabilities.events),
+            )),
+            NipartPluginCmd::Event(event) => {
+                let plugins: Vec<NipartDaemonPlugin> = self
+                    .plugins
+                    .values()
+                    .filter(|p| p.plugin_info.capabilities.events)
+                    .cloned()
+                    .collect();
+                // Notify in detached task, so slow plugin cannot stall query
+                // and apply commands queued in this worker.
+                tokio::spawn(async move {
+                    let results = join_all(
+                        plugins
+                            .iter()
+                            .map(|plugin| plugin.notify_event(&event)),
+                    )
+                    .await;
+                    for (plugin, result) in plugins.iter().zip(results) {
+                        if let Err(e) = result {
+                            log::debug!(
+                                "Failed to notify plugin {} on event \
+                                 {event}: {e}",
+                                plugin.name
+                            );
+                        }
+                    }
+                });
+                Ok(NipartPluginReply::None)
+            }
         }
     }
 }
 
-fn get_plugin_files() -> Vec<String> {
-    let mut plugins: Vec<String> = Vec::new();
+impl NipartPluginWorker {
+    /// Plugins supporting specified command in ascending order of priority,
+    /// then name.
+    fn sorted_plugins(
+        &self,
+        cmd_kind: NipartPluginCmdKind,
+    ) -> Vec<&NipartDaemonPlugin> {
+        let mut plugins: Vec<&NipartDaemonPlugin> = self
+            .plugins
+            .values()
+            .filter(|p| p.plugin_info.capabilities.commands.contains(&cmd_kind))
+            .collect();
+        plugins.sort_unstable_by(|a, b| {
+            (a.plugin_info.priority, a.name.as_str())
+                .cmp(&(b.plugin_info.priority, b.name.as_str()))
+        });
+        plugins
+    }
+
+    /// Fail if `plugins` section of desired state containing state for plugin
+    /// not connected or not supporting apply.
+    fn check_plugin_states(
+        &self,
+        desired_state: &NetworkState,
+    ) -> Result<(), NipartError> {
+        for (name, _) in desired_state.plugins.iter() {
+            match self.plugins.get(name) {
+                Some(plugin)
+                    if plugin
+                        .plugin_info
+                        .capabilities
+                        .commands
+                        .contains(&NipartPluginCmdKind::ApplyNetworkState) => {}
+                Some(_) => {
+                    return Err(NipartError::new(
+                        ErrorKind::InvalidArgument,
+                        format!(
+                            "Plugin {name} does not support applying its \
+                             state in plugins section"
+                        ),
+                    ));
+                }
+                None => {
+                    return Err(NipartError::new(
+                        ErrorKind::InvalidArgument,
+                        format!(
+                            "Plugin {name} defined in plugins section is not \
+                             connected"
+                        ),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Reconnect plugins restarted or not connected yet, then fail if any
+    /// required plugin is not connected.
+    async fn check_required_plugins(&mut self) -> Result<(), NipartError> {
+        let conf = NipartDaemonConfManager::get();
+        if self.plugins.len() + self.refused.len() < self.expected_plugin_count
+        {
+            connect_plugins(
+                &mut self.plugins,
+                &mut self.refused,
+                &conf.plugin.socket_dir,
+            )
+            .await;
+        }
+        for (name, policy) in conf.plugin.policies.iter() {
+            if *policy == NipartPluginPolicy::Required
+                && !self.plugins.contains_key(name)
+            {
+                if let Some((_, e)) =
+                    self.refused.values().find(|(n, _)| n == name)
+                {
+                    return Err(e.clone());
+                }
+                return Err(NipartError::new(
+                    ErrorKind::PluginFailure,
+                    format!("Required plugin {name} is not connected"),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Plugin rejecting the state with [ErrorKind::InvalidArgument] fails
+/// regardless of its policy, other failures are handled by
+/// [check_plugin_results()].
+fn check_veto_results(
+    results: Vec<(&NipartDaemonPlugin, Result<(), NipartError>)>,
+) -> Result<(), NipartError> {
+    let mut remains = Vec::new();
+    for (plugin, result) in results {
+        match result {
+            Err(e) if e.kind == ErrorKind::InvalidArgument => {
+                return Err(NipartError::new(
+                    ErrorKind::InvalidArgument,
+                    format!(
+                        "Plugin {} rejected the state: {}",
+                        plugin.name, e.msg
+                    ),
+                ));
+            }
+            result => remains.push((plugin, result)),
+        }
+    }
+    check_plugin_results(remains)?;
+    Ok(())
+}
+
+/// Return succeeded results in the same order. Failures of optional plugins
+/// are logged as warning, failures of required plugins are merged into
+/// single error with plugin name included. Plugin policy is read from live
+/// daemon config to honor reloaded config.
+fn check_plugin_results<T>(
+    results: Vec<(&NipartDaemonPlugin, Result<T, NipartError>)>,
+) -> Result<Vec<T>, NipartError> {
+    let conf = NipartDaemonConfManager::get();
+    let mut ret = Vec::new();
+    let mut errors: Vec<(&str, NipartError)> = Vec::new();
+    for (plugin, result) in results {
+        match result {
+            Ok(v) => ret.push(v),
+            Err(e)
+                if conf.plugin.policy(&plugin.name)
+                    == NipartPluginPolicy::Required =>
+            {
+                errors.push((plugin.name.as_str(), e));
+            }
+            Err(e) => {
+                log::warn!(
+                    "Ignoring failure of optional plugin {}: {e}",
+                    plugin.name
+                );
+            }
+        }
+    }
+    match errors.as_slice() {
+        [] => Ok(ret),
+        [(name, e)] => Err(NipartError::new(
+            e.kind,
+            format!("Required plugin {name} failed: {}", e.msg),
+        )),
+        _ => Err(NipartError::new(
+            ErrorKind::PluginFailure,
+            format!(
+                "Required plugins failed: {}",
+                errors
+                    .iter()
+                    .map(|(name, e)| format!("{name}: {e}"))
+                    .collect::<Vec<String>>()
+                    .join("; ")
+            ),
+        )),
+    }
+}
+
+fn start_plugin(
+    plugin_path: &str,
+    socket_dir: &str,
+) -> Result<Child, std::io::Error> {
+    std::process::Command::new(plugin_path)
+        .env(NipartPluginClient::SOCKET_DIR_ENV, socket_dir)
+        .spawn()
+}
 
-    let search_dir = if let Some(p) = current_exe().ok().and_then(|p| {
+/// Wait plugin executable to exit and restart it with exponential backoff if
+/// it crashed. Plugin quitting with success exit code is not restarted.
+async fn supervise_plugin(
+    plugin_path: String,
+    socket_dir: String,
+    mut child: Child,
+) {
+    let conf = NipartDaemonConfManager::get();
+    let initial_interval =
+        Duration::from_millis(conf.plugin.restart_interval_ms_initial);
+    let max_interval =
+        Duration::from_secs(conf.plugin.restart_interval_sec_max);
+    let mut interval = initial_interval;
+    loop {
+        let started = Instant::now();
+        match tokio::task::spawn_blocking(move || child.wait()).await {
+            Ok(Ok(status)) if status.success() => {
+                log::info!("Plugin {plugin_path} quit");
+                return;
+            }
+            Ok(Ok(status)) => {
+                log::warn!("Plugin {plugin_path} crashed: {status}");
+            }
+            Ok(Err(e)) => {
+                log::warn!("Failed to wait plugin {plugin_path}: {e}");
+                return;
+            }
+            Err(e) => {
+                log::error!("Failed to wait plugin {plugin_path}: {e}");
+                return;
+            }
+        }
+        loop {
+            // Plugin running long enough is not considered as crash loop
+            if started.elapsed() > max_interval {
+                interval = initial_interval;
+            }
+            log::info!(
+                "Restarting plugin {plugin_path} in {} milliseconds",
+                interval.as_millis()
+            );
+            tokio::time::sleep(interval).await;
+            interval = (interval * 2).min(max_interval);
+            match start_plugin(&plugin_path, &socket_dir) {
+                Ok(c) => {
+                    child = c;
+                    break;
+                }
+                Err(e) => {
+                    log::warn!("Failed to restart plugin {plugin_path}: {e}");
+                }
+            }
+        }
+    }
+}
+
+fn get_plugin_files_in_exe_dir() -> Vec<String> {
+    if let Some(search_dir) = current_exe().ok().and_then(|p| {
         p.parent().and_then(|s| s.to_str()).map(|s| s.to_string())
     }) {
-        p
+        get_plugin_files(&search_dir)
     } else {
-        return plugins;
-    };
+        Vec::new()
+    }
+}
 
-    for file_path in get_file_paths_in_dir(&search_dir) {
-        let path = std::path::Path::new(&file_path);
+fn get_plugin_files(search_dir: &str) -> Vec<String> {
+    let mut plugins: Vec<String> = Vec::new();
+
+    for file_path in get_file_paths_in_dir(search_dir) {
+        let path = Path::new(&file_path);
         if is_executable(path)
             && path
-                .strip_prefix(&search_dir)
+                .strip_prefix(search_dir)
                 .ok()
                 .and_then(|p| p.to_str())
                 .map(|p| p.starts_with(NM_PLUGIN_PREFIX))
@@ -212,19 +640,45 @@ fn get_file_paths_in_dir(dir: &str) -> Vec<String> {
     ret
 }
 
-async fn connect_plugins(plugins: &mut HashMap<String, NipartDaemonPlugin>) {
-    for file_path in
-        get_file_paths_in_dir(NipartPluginClient::DEFAULT_SOCKET_DIR)
-    {
+async fn connect_plugins(
+    plugins: &mut HashMap<String, NipartDaemonPlugin>,
+    refused: &mut HashMap<String, (String, NipartError)>,
+    socket_dir: &str,
+) {
+    for file_path in get_file_paths_in_dir(socket_dir) {
         let path = std::path::Path::new(&file_path);
+        if plugins.values().any(|p| p.socket_path == file_path)
+            || refused.contains_key(&file_path)
+        {
+            continue;
+        }
         if is_socket(path) {
             if let Ok(mut client) = NipartPluginClient::new(&file_path).await {
                 match client.query_plugin_info().await {
+                    Ok(info) if !info.is_protocol_supported() => {
+                        let e = NipartError::new(
+                            ErrorKind::PluginFailure,
+                            format!(
+                                "Refusing plugin {} version {}: it implements \
+                                 plugin protocol version {}, but daemon only \
+                                 supports version {} to {}",
+                                info.name,
+                                info.version,
+                                info.protocol_version,
+                                NipartPluginInfo::MIN_PROTOCOL_VERSION,
+                                NipartPluginInfo::PROTOCOL_VERSION,
+                            ),
+                        );
+                        log::error!("{e}");
+                        refused.insert(file_path, (info.name, e));
+                    }
                     Ok(info) => {
                         log::info!(
-                            "Plugin {} version {} connected",
+                            "Plugin {} version {} connected with \
+                             capabilities {:?}",
                             info.name,
                             info.version,
+                            info.capabilities,
                         );
                         plugins.insert(
                             info.name.to_string(),
@@ -1,19 +1,21 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use nipart::{
-    ErrorKind, InterfaceType, NetworkState, NipartError, NipartIpcConnection,
-    NipartNoDaemon, NipartstateInterface, NipartstateQueryOption, NipartstateStateKind,
+    ErrorKind, InterfaceType, NetworkState, NipartDriftReport, NipartError,
+    NipartIpcConnection, NipartNoDaemon, NipartstateInterface,
+    NipartstateQueryOption, NipartstateStateKind, WifiConfig,
 };
 
 use super::commander::NipartCommander;
+use crate::log_debug;
 
 impl NipartCommander {
     pub(crate) async fn query_network_state(
         &mut self,
-        conn: Option<&mut NipartIpcConnection>,
+        mut conn: Option<&mut NipartIpcConnection>,
         opt: NipartstateQueryOption,
     ) -> Result<NetworkState, NipartError> {
-        if let Some(conn) = conn {
+        if let Some(conn) = conn.as_mut() {
             conn.log_debug(format!("querying network state with option {opt}"))
                 .await;
         } else {
@@ -21,34 +23,7 @@ impl NipartCommander {
         }
         match opt.kind {
             NipartstateStateKind::RunningNetworkState => {
-                let mut net_state =
-                    NipartNoDaemon::query_network_state(opt.clone()).await?;
-
-                let plugins_net_states = self
-                    .plugin_manager
-                    .query_network_state(opt.clone())
-                    .await?;
-
-                for plugins_net_state in plugins_net_states {
-                    net_state.merge(&plugins_net_state)?;
-                }
-
-                // Use WIFI config stored in conf_manager
-                let mut saved_state = self.conf_manager.query_state().await?;
-                for (_, iface) in saved_state.ifaces.user_ifaces.drain() {
-                    if iface.iface_type() == &InterfaceType::WifiCfg {
-                        net_state.ifaces.push(iface);
-                    }
-                }
-
-                self.dhcpv4_manager.fill_dhcp_states(&mut net_state).await?;
-
-                if !opt.include_secrets {
-                    net_state.hide_secrets();
-                }
-
-                // TODO: Mark interface/routes not int saved state as ignored.
-                Ok(net_state)
+                self.query_running_state(conn, &opt).await
             }
             NipartstateStateKind::SavedNetworkState => {
                 let mut state = self.conf_manager.query_state().await?;
@@ -63,4 +38,75 @@ impl NipartCommander {
             )),
         }
     }
+
+    /// Generate report on how running state drifted from saved state,
+    /// secrets are hidden.
+    pub(crate) async fn query_drift(
+        &mut self,
+        conn: Option<&mut NipartIpcConnection>,
+    ) -> Result<NipartDriftReport, NipartError> {
+        let mut saved_state = self.conf_manager.query_state().await?;
+        saved_state.ifaces.unify_veth_and_ethernet();
+        // Secrets are compared, hide them after drift generated
+        let mut cur_state = self
+            .query_running_state(
+                conn,
+                &NipartstateQueryOption::running().include_secrets(true),
+            )
+            .await?;
+        cur_state.ifaces.unify_veth_and_ethernet();
+        saved_state.resolve_iface_identifiers(&cur_state)?;
+        let mut report = saved_state.gen_drift(&cur_state)?;
+        report.changed.hide_secrets();
+        Ok(report)
+    }
+
+    async fn query_running_state(
+        &mut self,
+        conn: Option<&mut NipartIpcConnection>,
+        opt: &NipartstateQueryOption,
+    ) -> Result<NetworkState, NipartError> {
+        let mut net_state =
+            NipartNoDaemon::query_network_state(opt.clone()).await?;
+
+        let plugins_net_states = self
+            .plugin_manager
+            .query_network_state(conn, opt.clone())
+            .await?;
+
+        for plugins_net_state in plugins_net_states {
+            net_state.merge(&plugins_net_state)?;
+        }
+
+        // Use WIFI config stored in conf_manager
+        let mut saved_state = self.conf_manager.query_state().await?;
+        for (_, iface) in saved_state.ifaces.user_ifaces.drain() {
+            if iface.iface_type() == &InterfaceType::WifiCfg {
+                net_state.ifaces.push(iface);
+            }
+        }
+
+        self.dhcpv4_manager.fill_dhcp_states(&mut net_state).await?;
+
+        if !opt.include_secrets {
+            net_state.hide_secrets();
+        }
+        Ok(net_state)
+    }
+
+    pub(crate) async fn wifi_scan(
+        &mut self,
+        conn: Option<&mut NipartIpcConnection>,
+        iface_name: Option<&str>,
+    ) -> Result<Vec<WifiConfig>, NipartError> {
+        log_debug(
+            conn,
+            format!(
+                "Running WiFi scan on {}",
+                iface_name.unwrap_or("all WiFi interfaces")
+            ),
+        )
+        .await;
+        NipartNoDaemon::wifi_scan(iface_name).await
+    }
 }
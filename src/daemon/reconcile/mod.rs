@@ -0,0 +1,9 @@
+// SPDX-License-Identifier: Apache-2.0
+
+mod reconcile_manager;
+mod reconcile_worker;
+
+pub(crate) use self::{
+    reconcile_manager::NipartReconcileManager,
+    reconcile_worker::{NipartReconcileCmd, NipartReconcileWorker},
+};
@@ -0,0 +1,38 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use futures_channel::mpsc::UnboundedSender;
+use nipart::NipartError;
+
+use super::{
+    super::daemon::NipartManagerCmd, NipartReconcileCmd, NipartReconcileWorker,
+};
+use crate::TaskManager;
+
+#[derive(Debug, Clone)]
+pub(crate) struct NipartReconcileManager {
+    mgr: TaskManager<NipartReconcileCmd, ()>,
+}
+
+impl NipartReconcileManager {
+    /// The reconciler is disabled until [Self::set_enabled()] invoked.
+    pub(crate) async fn new(
+        msg_to_commander: UnboundedSender<NipartManagerCmd>,
+    ) -> Result<Self, NipartError> {
+        let mut ret = Self {
+            mgr: TaskManager::new::<NipartReconcileWorker>("reconcile").await?,
+        };
+        ret.mgr
+            .exec(NipartReconcileCmd::SetCommanderSender(msg_to_commander))
+            .await?;
+        Ok(ret)
+    }
+
+    /// Whether to request commander reconciling on link, address or route
+    /// changes.
+    pub(crate) async fn set_enabled(
+        &mut self,
+        enabled: bool,
+    ) -> Result<(), NipartError> {
+        self.mgr.exec(NipartReconcileCmd::Enable(enabled)).await
+    }
+}
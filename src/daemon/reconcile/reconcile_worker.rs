@@ -0,0 +1,241 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::Duration;
+
+use futures_channel::{
+    mpsc::{UnboundedReceiver, UnboundedSender},
+    oneshot::Sender,
+};
+use futures_util::{SinkExt, stream::StreamExt};
+use nipart::{ErrorKind, NipartError};
+use rtnetlink::{
+    MulticastGroup, new_multicast_connection,
+    packet_core::{NetlinkMessage, NetlinkPayload},
+    packet_route::RouteNetlinkMessage,
+    sys::SocketAddr,
+};
+use tokio::time::Instant;
+
+use super::super::{
+    daemon::NipartManagerCmd, daemon_conf::NipartDaemonConfManager,
+    task::TaskWorker,
+};
+
+#[derive(Debug, Clone)]
+pub(crate) enum NipartReconcileCmd {
+    /// Set the sender for requesting commander to reconcile. Must be invoked
+    /// right after NipartReconcileWorker started.
+    SetCommanderSender(UnboundedSender<NipartManagerCmd>),
+    /// Start or stop listening on link, address and route changes
+    Enable(bool),
+}
+
+impl std::fmt::Display for NipartReconcileCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::SetCommanderSender(_) => {
+                write!(f, "set-commander-sender")
+            }
+            Self::Enable(v) => {
+                write!(f, "enable:{v}")
+            }
+        }
+    }
+}
+
+type FromManager = (NipartReconcileCmd, Sender<Result<(), NipartError>>);
+
+type NetlinkMsgReceiver =
+    UnboundedReceiver<(NetlinkMessage<RouteNetlinkMessage>, SocketAddr)>;
+
+/// Request commander to reconcile after link, address or route changed.
+/// To avoid reconciling on every message of a burst of changes, the request
+/// is sent after `settle-time-ms` since first change, and no sooner than
+/// `min-interval-sec` since previous request.
+#[derive(Debug)]
+pub(crate) struct NipartReconcileWorker {
+    receiver: UnboundedReceiver<FromManager>,
+    msg_to_commander: Option<UnboundedSender<NipartManagerCmd>>,
+    netlink_handle: Option<rtnetlink::Handle>,
+    netlink_msg_receiver: Option<NetlinkMsgReceiver>,
+    // Time of first change not reconciled yet
+    first_pending_change: Option<Instant>,
+    last_request: Option<Instant>,
+}
+
+impl TaskWorker for NipartReconcileWorker {
+    type Cmd = NipartReconcileCmd;
+    type Reply = ();
+
+    async fn new(
+        receiver: UnboundedReceiver<FromManager>,
+    ) -> Result<Self, NipartError> {
+        Ok(Self {
+            receiver,
+            msg_to_commander: None,
+            netlink_handle: None,
+            netlink_msg_receiver: None,
+            first_pending_change: None,
+            last_request: None,
+        })
+    }
+
+    fn receiver(&mut self) -> &mut UnboundedReceiver<FromManager> {
+        &mut self.receiver
+    }
+
+    async fn process_cmd(
+        &mut self,
+        cmd: NipartReconcileCmd,
+    ) -> Result<(), NipartError> {
+        log::debug!("Processing reconcile command: {cmd}");
+        match cmd {
+            NipartReconcileCmd::SetCommanderSender(sender) => {
+                self.msg_to_commander = Some(sender);
+            }
+            NipartReconcileCmd::Enable(true) => {
+                if self.netlink_msg_receiver.is_none() {
+                    self.start()?;
+                }
+            }
+            NipartReconcileCmd::Enable(false) => {
+                self.netlink_handle = None;
+                self.netlink_msg_receiver = None;
+                self.first_pending_change = None;
+            }
+        }
+        Ok(())
+    }
+
+    async fn run(&mut self) {
+        loop {
+            let deadline = self.next_request_time();
+            tokio::select! {
+                cmd_result = self.receiver.next() => {
+                    if let Some((cmd, sender)) = cmd_result {
+                        let cmd_str = cmd.to_string();
+                        let result = self.process_cmd(cmd).await;
+                        if sender.send(result).is_err() {
+                            log::error!(
+                                "Failed to send reply for command {cmd_str}"
+                            );
+                        }
+                    } else {
+                        break;
+                    }
+                }
+                result = next_netlink_msg(&mut self.netlink_msg_receiver) => {
+                    match result {
+                        Some(nl_msg) => {
+                            if is_config_change(&nl_msg)
+                                && self.first_pending_change.is_none()
+                            {
+                                log::trace!("Got config change {nl_msg:?}");
+                                self.first_pending_change =
+                                    Some(Instant::now());
+                            }
+                        }
+                        None => {
+                            log::error!(
+                                "Netlink socket for reconcile closed, \
+                                 stopping reconcile"
+                            );
+                            self.netlink_handle = None;
+                            self.netlink_msg_receiver = None;
+                        }
+                    }
+                }
+                _ = sleep_until(deadline) => {
+                    self.request_reconcile().await;
+                }
+            }
+        }
+    }
+}
+
+impl NipartReconcileWorker {
+    fn start(&mut self) -> Result<(), NipartError> {
+        let (conn, handle, msg) = new_multicast_connection(&[
+            MulticastGroup::Link,
+            MulticastGroup::Ipv4Ifaddr,
+            MulticastGroup::Ipv6Ifaddr,
+            MulticastGroup::Ipv4Route,
+            MulticastGroup::Ipv6Route,
+        ])
+        .map_err(|e| {
+            NipartError::new(
+                ErrorKind::Bug,
+                format!(
+                    "Failed to create netlink multicast socket for \
+                     reconcile: {e}"
+                ),
+            )
+        })?;
+        tokio::spawn(conn);
+        self.netlink_handle = Some(handle);
+        self.netlink_msg_receiver = Some(msg);
+        Ok(())
+    }
+
+    fn next_request_time(&self) -> Option<Instant> {
+        let first_pending_change = self.first_pending_change?;
+        let conf = NipartDaemonConfManager::get();
+        let mut ret = first_pending_change
+            + Duration::from_millis(conf.reconcile.settle_time_ms);
+        if let Some(last_request) = self.last_request {
+            ret = ret.max(
+                last_request
+                    + Duration::from_secs(conf.reconcile.min_interval_sec),
+            );
+        }
+        Some(ret)
+    }
+
+    async fn request_reconcile(&mut self) {
+        self.first_pending_change = None;
+        self.last_request = Some(Instant::now());
+        if let Some(sender) = self.msg_to_commander.as_mut() {
+            if let Err(e) = sender.send(NipartManagerCmd::Reconcile).await {
+                log::error!(
+                    "NipartReconcileWorker: Failed to send to commander: {e}"
+                );
+            }
+        } else {
+            log::error!(
+                "BUG: NipartReconcileWorker has no sender to commander"
+            );
+        }
+    }
+}
+
+// Never complete when not listening
+async fn next_netlink_msg(
+    receiver: &mut Option<NetlinkMsgReceiver>,
+) -> Option<NetlinkMessage<RouteNetlinkMessage>> {
+    match receiver {
+        Some(receiver) => receiver.next().await.map(|(nl_msg, _)| nl_msg),
+        None => std::future::pending().await,
+    }
+}
+
+// Never complete when no pending change
+async fn sleep_until(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline).await,
+        None => std::future::pending().await,
+    }
+}
+
+fn is_config_change(nl_msg: &NetlinkMessage<RouteNetlinkMessage>) -> bool {
+    matches!(
+        &nl_msg.payload,
+        NetlinkPayload::InnerMessage(
+            RouteNetlinkMessage::NewLink(_)
+                | RouteNetlinkMessage::DelLink(_)
+                | RouteNetlinkMessage::NewAddress(_)
+                | RouteNetlinkMessage::DelAddress(_)
+                | RouteNetlinkMessage::NewRoute(_)
+                | RouteNetlinkMessage::DelRoute(_)
+        )
+    )
+}
@@ -0,0 +1,37 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use nipart::{NetworkState, NipartError, NipartIpcConnection};
+
+use crate::{commander::NipartCommander, log_info, log_trace};
+
+impl NipartCommander {
+    /// Apply specified generation of saved state through the normal apply
+    /// path, interfaces, routes and plugin states not found in that
+    /// generation are removed.
+    /// Caller should hold the [crate::lock::NipartLockManager] lock.
+    pub(crate) async fn restore_generation(
+        &mut self,
+        mut conn: Option<&mut NipartIpcConnection>,
+        generation: u64,
+        peer: Option<(u32, i32)>,
+    ) -> Result<NetworkState, NipartError> {
+        let generation_state =
+            self.conf_manager.query_generation(generation).await?;
+        let saved_state = self.conf_manager.query_state().await?;
+        let desired_state = generation_state.gen_restore_state(&saved_state);
+
+        log_info(
+            conn.as_deref_mut(),
+            format!("Restoring generation {generation} of saved state"),
+        )
+        .await;
+        log_trace(
+            conn.as_deref_mut(),
+            format!("Restoring with desired state {desired_state}"),
+        )
+        .await;
+
+        self.apply_network_state(conn, desired_state, Default::default(), peer)
+            .await
+    }
+}
@@ -0,0 +1,201 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Integration with systemd service manager, please refer to `man sd_notify`,
+// `man sd_listen_fds` and `man sd_watchdog_enabled` for detail.
+// All functions are no-op when daemon is not started by systemd.
+
+use std::{
+    os::{
+        fd::{FromRawFd, RawFd},
+        linux::net::SocketAddrExt,
+        unix::net::{SocketAddr, UnixDatagram, UnixListener},
+    },
+    time::Duration,
+};
+
+use nipart::{ErrorKind, NipartError};
+
+const NOTIFY_SOCKET_ENV: &str = "NOTIFY_SOCKET";
+const LISTEN_PID_ENV: &str = "LISTEN_PID";
+const LISTEN_FDS_ENV: &str = "LISTEN_FDS";
+const WATCHDOG_USEC_ENV: &str = "WATCHDOG_USEC";
+const WATCHDOG_PID_ENV: &str = "WATCHDOG_PID";
+const SD_LISTEN_FDS_START: RawFd = 3;
+
+/// Send state string like `READY=1` to systemd, failures are only logged.
+pub(crate) fn sd_notify(state: &str) {
+    if let Ok(path) = std::env::var(NOTIFY_SOCKET_ENV) {
+        match notify_socket(&path, state) {
+            Ok(()) => log::trace!("Notified systemd with {state:?}"),
+            Err(e) => log::warn!("{e}"),
+        }
+    }
+}
+
+/// Get the listening socket passed in by systemd socket activation.
+pub(crate) fn sd_listen_socket() -> Result<Option<UnixListener>, NipartError> {
+    let fd_count = get_listen_fd_count(
+        std::env::var(LISTEN_PID_ENV).ok().as_deref(),
+        std::env::var(LISTEN_FDS_ENV).ok().as_deref(),
+        std::process::id(),
+    );
+    if fd_count == 0 {
+        return Ok(None);
+    } else if fd_count > 1 {
+        log::warn!(
+            "Got {fd_count} sockets from systemd, only the first one will be \
+             used"
+        );
+    }
+    log::debug!("Got {fd_count} sockets from systemd");
+
+    // SAFETY: systemd guarantees `SD_LISTEN_FDS_START` is opened and owned
+    // by us when `LISTEN_PID` is our PID.
+    let listener = unsafe { UnixListener::from_raw_fd(SD_LISTEN_FDS_START) };
+    // The socket passed in by systemd has no FD_CLOEXEC, clone it to prevent
+    // plugins spawned by daemon inheriting it.
+    let ret = listener.try_clone().map_err(|e| {
+        NipartError::new(
+            ErrorKind::IpcFailure,
+            format!("Failed to clone socket passed in by systemd: {e}"),
+        )
+    })?;
+    ret.local_addr().map_err(|e| {
+        NipartError::new(
+            ErrorKind::IpcFailure,
+            format!(
+                "File descriptor {SD_LISTEN_FDS_START} passed in by systemd \
+                 is not unix socket: {e}"
+            ),
+        )
+    })?;
+    Ok(Some(ret))
+}
+
+/// Return the interval of sending `WATCHDOG=1`, None if watchdog is not
+/// enabled.
+pub(crate) fn sd_watchdog_interval() -> Option<Duration> {
+    get_watchdog_interval(
+        std::env::var(WATCHDOG_USEC_ENV).ok().as_deref(),
+        std::env::var(WATCHDOG_PID_ENV).ok().as_deref(),
+        std::process::id(),
+    )
+}
+
+fn notify_socket(path: &str, state: &str) -> Result<(), NipartError> {
+    let e_map = |e: std::io::Error| {
+        NipartError::new(
+            ErrorKind::IpcFailure,
+            format!("Failed to notify systemd via {path} with {state:?}: {e}"),
+        )
+    };
+    let socket = UnixDatagram::unbound().map_err(e_map)?;
+    if let Some(name) = path.strip_prefix('@') {
+        let addr = SocketAddr::from_abstract_name(name).map_err(e_map)?;
+        socket
+            .send_to_addr(state.as_bytes(), &addr)
+            .map_err(e_map)?;
+    } else {
+        socket.send_to(state.as_bytes(), path).map_err(e_map)?;
+    }
+    Ok(())
+}
+
+// Return 0 if sockets are not for us
+fn get_listen_fd_count(
+    listen_pid: Option<&str>,
+    listen_fds: Option<&str>,
+    pid: u32,
+) -> u32 {
+    if listen_pid.and_then(|p| p.parse::<u32>().ok()) != Some(pid) {
+        return 0;
+    }
+    listen_fds
+        .and_then(|c| c.parse::<u32>().ok())
+        .unwrap_or_default()
+}
+
+fn get_watchdog_interval(
+    watchdog_usec: Option<&str>,
+    watchdog_pid: Option<&str>,
+    pid: u32,
+) -> Option<Duration> {
+    if let Some(watchdog_pid) = watchdog_pid
+        && watchdog_pid.parse::<u32>().ok() != Some(pid)
+    {
+        return None;
+    }
+    let usec = watchdog_usec.and_then(|u| u.parse::<u64>().ok())?;
+    if usec == 0 {
+        return None;
+    }
+    // systemd recommends sending keep-alive ping at half of timeout
+    Some(Duration::from_micros(usec / 2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notify_socket_path() {
+        let path = std::env::temp_dir()
+            .join(format!("nipart_test_notify_{}", std::process::id()));
+        std::fs::remove_file(&path).ok();
+        let fake_systemd = UnixDatagram::bind(&path).unwrap();
+
+        notify_socket(path.to_str().unwrap(), "READY=1\nSTATUS=Running")
+            .unwrap();
+
+        let mut buf = [0u8; 64];
+        let size = fake_systemd.recv(&mut buf).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(&buf[..size], b"READY=1\nSTATUS=Running");
+    }
+
+    #[test]
+    fn test_notify_socket_abstract() {
+        let name = format!("nipart_test_notify_{}", std::process::id());
+        let addr = SocketAddr::from_abstract_name(&name).unwrap();
+        let fake_systemd = UnixDatagram::bind_addr(&addr).unwrap();
+
+        notify_socket(&format!("@{name}"), "WATCHDOG=1").unwrap();
+
+        let mut buf = [0u8; 64];
+        let size = fake_systemd.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..size], b"WATCHDOG=1");
+    }
+
+    #[test]
+    fn test_notify_socket_not_exist() {
+        assert!(
+            notify_socket("/nonexistent/nipart_notify", "READY=1").is_err()
+        );
+    }
+
+    #[test]
+    fn test_listen_fd_count() {
+        assert_eq!(get_listen_fd_count(Some("100"), Some("1"), 100), 1);
+        assert_eq!(get_listen_fd_count(Some("101"), Some("1"), 100), 0);
+        assert_eq!(get_listen_fd_count(None, Some("1"), 100), 0);
+        assert_eq!(get_listen_fd_count(Some("100"), None, 100), 0);
+    }
+
+    #[test]
+    fn test_watchdog_interval() {
+        assert_eq!(
+            get_watchdog_interval(Some("30000000"), None, 100),
+            Some(Duration::from_secs(15))
+        );
+        assert_eq!(
+            get_watchdog_interval(Some("30000000"), Some("100"), 100),
+            Some(Duration::from_secs(15))
+        );
+        assert_eq!(
+            get_watchdog_interval(Some("30000000"), Some("1"), 100),
+            None
+        );
+        assert_eq!(get_watchdog_interval(Some("0"), None, 100), None);
+        assert_eq!(get_watchdog_interval(None, None, 100), None);
+    }
+}
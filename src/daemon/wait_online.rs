@@ -0,0 +1,239 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{collections::HashMap, net::IpAddr, time::Duration};
+
+use futures_channel::mpsc::UnboundedReceiver;
+use futures_util::StreamExt;
+use nipart::{
+    DhcpState, ErrorKind, InterfaceType, NetworkState, NipartError,
+    NipartEvent, NipartIpcConnection, NipartWaitOnlineCondition,
+    NipartWaitOnlineIface, NipartWaitOnlineOption, NipartstateInterface,
+    NipartstateQueryOption,
+};
+
+use super::{commander::NipartCommander, event::NipartLinkEventType};
+use crate::{log_debug, log_info};
+
+// Changes like IPv6 autoconf address does not have event, so we still check
+// at this interval besides checking on every event.
+const WAIT_ONLINE_RECHECK_INTERVAL_SEC: u64 = 5;
+
+impl NipartCommander {
+    pub(crate) async fn wait_online(
+        &mut self,
+        mut conn: Option<&mut NipartIpcConnection>,
+        opt: NipartWaitOnlineOption,
+    ) -> Result<(), NipartError> {
+        log_debug(
+            conn.as_deref_mut(),
+            format!("Waiting network online with option {opt}"),
+        )
+        .await;
+        // Subscribe before checking to make sure no event lost
+        let mut receiver = self.event_emitter.subscribe();
+        self.monitor_manager.set_emit_all(true).await?;
+
+        let mut pending_reason = String::new();
+        let result = tokio::time::timeout(
+            Duration::from_secs(opt.timeout.into()),
+            self.wait_online_loop(
+                conn.as_deref_mut(),
+                &opt,
+                &mut receiver,
+                &mut pending_reason,
+            ),
+        )
+        .await;
+
+        drop(receiver);
+        if !self.event_emitter.has_subscriber() {
+            self.monitor_manager.set_emit_all(false).await?;
+        }
+
+        match result {
+            Ok(result) => result,
+            Err(_) => Err(NipartError::new(
+                ErrorKind::Timeout,
+                format!(
+                    "Network is not online after {} seconds: {pending_reason}",
+                    opt.timeout
+                ),
+            )),
+        }
+    }
+
+    async fn wait_online_loop(
+        &mut self,
+        mut conn: Option<&mut NipartIpcConnection>,
+        opt: &NipartWaitOnlineOption,
+        receiver: &mut UnboundedReceiver<NipartEvent>,
+        pending_reason: &mut String,
+    ) -> Result<(), NipartError> {
+        loop {
+            match self.check_online(&opt.condition).await? {
+                None => {
+                    log_info(conn, "Network is online".to_string()).await;
+                    return Ok(());
+                }
+                Some(reason) => {
+                    log_debug(
+                        conn.as_deref_mut(),
+                        format!("Network is not online yet: {reason}"),
+                    )
+                    .await;
+                    *pending_reason = reason;
+                }
+            }
+            tokio::select! {
+                event = receiver.next() => {
+                    if event.is_none() {
+                        return Err(NipartError::new(
+                            ErrorKind::Bug,
+                            "Event emitter closed".to_string(),
+                        ));
+                    }
+                }
+                _ = tokio::time::sleep(Duration::from_secs(
+                    WAIT_ONLINE_RECHECK_INTERVAL_SEC
+                )) => (),
+            }
+        }
+    }
+
+    /// Return the reason of not online, None means online.
+    async fn check_online(
+        &mut self,
+        condition: &NipartWaitOnlineCondition,
+    ) -> Result<Option<String>, NipartError> {
+        let carriers = self.monitor_manager.query_carriers().await?;
+        let cur_state = self
+            .query_network_state(None, NipartstateQueryOption::running())
+            .await?;
+
+        Ok(match condition {
+            NipartWaitOnlineCondition::AllSaved => {
+                let saved_state = self.conf_manager.query_state().await?;
+                saved_state
+                    .ifaces
+                    .kernel_ifaces
+                    .values()
+                    .filter(|i| i.is_up())
+                    .find_map(|iface| {
+                        let base_iface = iface.base_iface();
+                        let req = NipartWaitOnlineIface::new(
+                            iface.name().to_string(),
+                            base_iface
+                                .ipv4
+                                .as_ref()
+                                .map(|i| i.is_enabled())
+                                .unwrap_or_default(),
+                            base_iface
+                                .ipv6
+                                .as_ref()
+                                .map(|i| i.is_enabled())
+                                .unwrap_or_default(),
+                        );
+                        check_iface_online(&req, &cur_state, &carriers)
+                    })
+            }
+            NipartWaitOnlineCondition::DefaultRoute => {
+                if has_default_route(&cur_state) {
+                    None
+                } else {
+                    Some("No default route found".to_string())
+                }
+            }
+            NipartWaitOnlineCondition::Interfaces(ifaces) => ifaces
+                .iter()
+                .find_map(|i| check_iface_online(i, &cur_state, &carriers)),
+            _ => {
+                return Err(NipartError::new(
+                    ErrorKind::NoSupport,
+                    format!("Unsupported wait online condition {condition}"),
+                ));
+            }
+        })
+    }
+}
+
+fn check_iface_online(
+    req: &NipartWaitOnlineIface,
+    cur_state: &NetworkState,
+    carriers: &HashMap<String, NipartLinkEventType>,
+) -> Option<String> {
+    let Some(cur_iface) = cur_state.ifaces.kernel_ifaces.get(&req.name) else {
+        return Some(format!("Interface {} not found", req.name));
+    };
+    if !cur_iface.is_up() {
+        return Some(format!("Interface {} is not up", req.name));
+    }
+    // Kernel reports unknown operstate for these interfaces
+    if !matches!(
+        cur_iface.iface_type(),
+        InterfaceType::Loopback | InterfaceType::Dummy
+    ) && carriers.get(&req.name) != Some(&NipartLinkEventType::CarrierUp)
+    {
+        return Some(format!("Interface {} has no carrier", req.name));
+    }
+
+    let base_iface = cur_iface.base_iface();
+    if req.ipv4 {
+        let ipv4 = base_iface.ipv4.as_ref();
+        if let Some(ipv4) = ipv4
+            && ipv4.dhcp == Some(true)
+            && ipv4.dhcp_state != Some(DhcpState::Done)
+            && ipv4.link_local_state != Some(DhcpState::Done)
+        {
+            return Some(format!(
+                "DHCPv4 of interface {} is in {} state",
+                req.name,
+                String::from(ipv4.dhcp_state.clone().unwrap_or_default())
+            ));
+        }
+        if !ipv4
+            .and_then(|i| i.addresses.as_ref())
+            .map(|a| !a.is_empty())
+            .unwrap_or_default()
+        {
+            return Some(format!("Interface {} has no IPv4 address", req.name));
+        }
+    }
+    if req.ipv6
+        && !base_iface
+            .ipv6
+            .as_ref()
+            .and_then(|i| i.addresses.as_ref())
+            .map(|addrs| addrs.iter().any(|a| !is_ipv6_link_local(&a.ip)))
+            .unwrap_or_default()
+    {
+        return Some(format!(
+            "Interface {} has no IPv6 address other than link-local",
+            req.name
+        ));
+    }
+    None
+}
+
+fn is_ipv6_link_local(ip: &IpAddr) -> bool {
+    if let IpAddr::V6(ip) = ip {
+        (ip.segments()[0] & 0xffc0) == 0xfe80
+    } else {
+        false
+    }
+}
+
+fn has_default_route(cur_state: &NetworkState) -> bool {
+    cur_state
+        .routes
+        .running
+        .as_ref()
+        .map(|routes| {
+            routes.iter().any(|r| {
+                matches!(
+                    r.destination.as_deref(),
+                    Some("0.0.0.0/0") | Some("::/0")
+                )
+            })
+        })
+        .unwrap_or_default()
+}
@@ -1,10 +1,14 @@
 // SPDX-License-Identifier: Apache-2.0
 
+use futures_util::Stream;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    JsonDisplayHideSecrets, NetworkState, NipartCanIpc, NipartError, NipartIpcConnection,
-    NipartstateApplyOption, NipartstateQueryOption,
+    ErrorKind, JsonDisplayHideSecrets, NetworkState, NipartCanIpc,
+    NipartDaemonConf, NipartDriftReport, NipartError, NipartEvent,
+    NipartHistoryEntry, NipartIpcConnection, NipartLogLevel, NipartUuid,
+    NipartWaitOnlineOption, NipartstateApplyOption, NipartstateApplyPlan,
+    NipartstateQueryOption, WifiConfig,
 };
 
 impl NipartCanIpc for NetworkState {
@@ -13,6 +17,26 @@ impl NipartCanIpc for NetworkState {
     }
 }
 
+impl NipartCanIpc for NipartstateApplyPlan {
+    fn ipc_kind(&self) -> String {
+        "apply-plan".to_string()
+    }
+}
+
+impl NipartCanIpc for NipartDriftReport {
+    fn ipc_kind(&self) -> String {
+        "drift-report".to_string()
+    }
+}
+
+impl NipartCanIpc for Vec<WifiConfig> {
+    fn ipc_kind(&self) -> String {
+        "wifi-configs".to_string()
+    }
+}
+
+const WAIT_ONLINE_REPLY_MARGIN_SEC: u32 = 5;
+
 #[derive(Debug)]
 pub struct NipartClient {
     pub(crate) ipc: NipartIpcConnection,
@@ -27,6 +51,34 @@ pub enum NipartClientCmd {
     Ping,
     QueryNetworkState(Box<NipartstateQueryOption>),
     ApplyNetworkState(Box<(NetworkState, NipartstateApplyOption)>),
+    /// Commit the pending checkpoint created by applying with
+    /// [NipartstateApplyOption::no_commit].
+    Commit,
+    /// Rollback to the state before the pending checkpoint created.
+    Rollback,
+    /// Subscribe to daemon events, the connection will only be used for
+    /// receiving [NipartEvent] afterwards.
+    Subscribe,
+    /// WiFi active scan on specified interface or all WiFi interfaces if
+    /// None.
+    WifiScan(Option<String>),
+    /// Wait network to be online, reply with error
+    /// [crate::ErrorKind::Timeout] if not online before timeout.
+    WaitOnline(Box<NipartWaitOnlineOption>),
+    /// Query the daemon configuration currently in use.
+    QueryDaemonConf,
+    /// Set maximum level of log entries daemon and plugins send back to
+    /// this connection.
+    SetLogLevel(NipartLogLevel),
+    /// Query apply transaction history, oldest first. Only query the
+    /// specified transaction if ID defined.
+    QueryHistory(Option<NipartUuid>),
+    /// Apply the specified generation of saved state, interfaces, routes
+    /// and plugin states not found in that generation are removed.
+    RestoreGeneration(u64),
+    /// Query how running network state drifted from saved state, secrets
+    /// are hidden.
+    QueryDrift,
 }
 
 impl NipartCanIpc for NipartClientCmd {
@@ -35,6 +87,16 @@ impl NipartCanIpc for NipartClientCmd {
             Self::Ping => "ping".to_string(),
             Self::QueryNetworkState(_) => "query-network-state".to_string(),
             Self::ApplyNetworkState(_) => "apply-network-state".to_string(),
+            Self::Commit => "commit".to_string(),
+            Self::Rollback => "rollback".to_string(),
+            Self::Subscribe => "subscribe".to_string(),
+            Self::WifiScan(_) => "wifi-scan".to_string(),
+            Self::WaitOnline(_) => "wait-online".to_string(),
+            Self::QueryDaemonConf => "query-daemon-conf".to_string(),
+            Self::SetLogLevel(_) => "set-log-level".to_string(),
+            Self::QueryHistory(_) => "query-history".to_string(),
+            Self::RestoreGeneration(_) => "restore-generation".to_string(),
+            Self::QueryDrift => "query-drift".to_string(),
         }
     }
 }
@@ -87,6 +149,12 @@ impl NipartClient {
         desired_state: NetworkState,
         option: NipartstateApplyOption,
     ) -> Result<NetworkState, NipartError> {
+        if option.dry_run {
+            return Err(NipartError::new(
+                ErrorKind::InvalidArgument,
+                "Please use plan_network_state() for dry-run apply".to_string(),
+            ));
+        }
         self.ipc
             .send(Ok(NipartClientCmd::ApplyNetworkState(Box::new((
                 desired_state,
@@ -95,4 +163,158 @@ impl NipartClient {
             .await?;
         self.ipc.recv::<NetworkState>().await
     }
+
+    /// Generate operations planned for applying desired state without
+    /// touching anything.
+    pub async fn plan_network_state(
+        &mut self,
+        desired_state: NetworkState,
+        option: NipartstateApplyOption,
+    ) -> Result<NipartstateApplyPlan, NipartError> {
+        self.ipc
+            .send(Ok(NipartClientCmd::ApplyNetworkState(Box::new((
+                desired_state,
+                option.dry_run(),
+            )))))
+            .await?;
+        self.ipc.recv::<NipartstateApplyPlan>().await
+    }
+
+    /// Apply the specified generation of saved state, return the changed
+    /// state like [NipartClient::apply_network_state()].
+    pub async fn restore_generation(
+        &mut self,
+        generation: u64,
+    ) -> Result<NetworkState, NipartError> {
+        self.ipc
+            .send(Ok(NipartClientCmd::RestoreGeneration(generation)))
+            .await?;
+        self.ipc.recv::<NetworkState>().await
+    }
+
+    /// Commit the pending checkpoint.
+    pub async fn commit(&mut self) -> Result<(), NipartError> {
+        self.ipc.send(Ok(NipartClientCmd::Commit)).await?;
+        self.ipc.recv::<()>().await
+    }
+
+    /// Rollback the pending checkpoint.
+    pub async fn rollback(&mut self) -> Result<(), NipartError> {
+        self.ipc.send(Ok(NipartClientCmd::Rollback)).await?;
+        self.ipc.recv::<()>().await
+    }
+
+    /// Run WiFi active scan via daemon.
+    /// If `iface_name` is None, will scan on all found WiFi interfaces.
+    pub async fn wifi_scan(
+        &mut self,
+        iface_name: Option<&str>,
+    ) -> Result<Vec<WifiConfig>, NipartError> {
+        self.ipc
+            .send(Ok(NipartClientCmd::WifiScan(
+                iface_name.map(|i| i.to_string()),
+            )))
+            .await?;
+        self.ipc.recv::<Vec<WifiConfig>>().await
+    }
+
+    /// Wait network to be online
+    pub async fn wait_online(
+        &mut self,
+        option: NipartWaitOnlineOption,
+    ) -> Result<(), NipartError> {
+        // Daemon will reply before this timeout
+        let timeout_ms = option
+            .timeout
+            .saturating_add(WAIT_ONLINE_REPLY_MARGIN_SEC)
+            .saturating_mul(1000);
+        self.ipc
+            .send(Ok(NipartClientCmd::WaitOnline(Box::new(option))))
+            .await?;
+        let old_timeout_ms = self.ipc.timeout_ms;
+        self.ipc.set_timeout(timeout_ms);
+        let result = self.ipc.recv::<()>().await;
+        self.ipc.set_timeout(old_timeout_ms);
+        result
+    }
+
+    /// Query the daemon configuration currently in use.
+    pub async fn query_daemon_conf(
+        &mut self,
+    ) -> Result<NipartDaemonConf, NipartError> {
+        self.ipc.send(Ok(NipartClientCmd::QueryDaemonConf)).await?;
+        self.ipc.recv::<NipartDaemonConf>().await
+    }
+
+    /// Set maximum level of log entries daemon and plugins send back to
+    /// this client, default to [NipartLogLevel::Trace].
+    pub async fn set_log_level(
+        &mut self,
+        log_level: NipartLogLevel,
+    ) -> Result<(), NipartError> {
+        self.ipc
+            .send(Ok(NipartClientCmd::SetLogLevel(log_level)))
+            .await?;
+        self.ipc.recv::<()>().await
+    }
+
+    /// Query apply transaction history, oldest first. Requires root
+    /// permission as error message might contain secrets.
+    pub async fn query_history(
+        &mut self,
+    ) -> Result<Vec<NipartHistoryEntry>, NipartError> {
+        self.ipc
+            .send(Ok(NipartClientCmd::QueryHistory(None)))
+            .await?;
+        self.ipc.recv::<Vec<NipartHistoryEntry>>().await
+    }
+
+    /// Query specified apply transaction in history.
+    pub async fn query_history_entry(
+        &mut self,
+        id: NipartUuid,
+    ) -> Result<NipartHistoryEntry, NipartError> {
+        self.ipc
+            .send(Ok(NipartClientCmd::QueryHistory(Some(id))))
+            .await?;
+        self.ipc
+            .recv::<Vec<NipartHistoryEntry>>()
+            .await?
+            .pop()
+            .ok_or_else(|| {
+                NipartError::new(
+                    ErrorKind::Bug,
+                    format!("Daemon replied no history entry for {id}"),
+                )
+            })
+    }
+
+    /// Query how running network state drifted from saved state.
+    pub async fn query_drift(
+        &mut self,
+    ) -> Result<NipartDriftReport, NipartError> {
+        self.ipc.send(Ok(NipartClientCmd::QueryDrift)).await?;
+        self.ipc.recv::<NipartDriftReport>().await
+    }
+
+    /// Subscribe to daemon events. The returned stream ends when daemon
+    /// closed the connection.
+    pub async fn subscribe(
+        mut self,
+    ) -> Result<impl Stream<Item = Result<NipartEvent, NipartError>>, NipartError>
+    {
+        self.ipc.send(Ok(NipartClientCmd::Subscribe)).await?;
+        self.ipc.recv::<()>().await?;
+        // Events might not happen for a long time
+        self.ipc.set_timeout(u32::MAX);
+        // Stream ends after first error
+        Ok(futures_util::stream::unfold(Some(self), |cli| async move {
+            let mut cli = cli?;
+            match cli.ipc.recv::<NipartEvent>().await {
+                Ok(event) => Some((Ok(event), Some(cli))),
+                Err(e) if e.kind == ErrorKind::IpcClosed => None,
+                Err(e) => Some((Err(e), None)),
+            }
+        }))
+    }
 }
@@ -0,0 +1,278 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    JsonDisplay, NipartCanIpc, NipartClient, NipartIpcConnection,
+    NipartPluginClient,
+};
+
+/// Configuration of nipart daemon.
+/// Loaded from [NipartDaemonConf::PATH] in YAML format with drop-in files
+/// `*.conf` in [NipartDaemonConf::DROP_IN_DIR] merged in alphabetical order.
+/// Undefined properties are set to default values.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonDisplay)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields, default)]
+#[non_exhaustive]
+pub struct NipartDaemonConf {
+    pub api: NipartDaemonApiConf,
+    pub bootup: NipartDaemonBootupConf,
+    pub apply: NipartDaemonApplyConf,
+    pub monitor: NipartDaemonMonitorConf,
+    pub plugin: NipartDaemonPluginConf,
+    pub reconcile: NipartDaemonReconcileConf,
+    pub dbus: NipartDaemonDbusConf,
+    /// Folder holding the saved state, default to
+    /// [NipartDaemonConf::DEFAULT_STATE_DIR]
+    pub state_dir: String,
+}
+
+impl Default for NipartDaemonConf {
+    fn default() -> Self {
+        Self {
+            api: Default::default(),
+            bootup: Default::default(),
+            apply: Default::default(),
+            monitor: Default::default(),
+            plugin: Default::default(),
+            reconcile: Default::default(),
+            dbus: Default::default(),
+            state_dir: Self::DEFAULT_STATE_DIR.to_string(),
+        }
+    }
+}
+
+impl NipartDaemonConf {
+    pub const PATH: &'static str = "/etc/nipart/nipartd.conf";
+    pub const DROP_IN_DIR: &'static str = "/etc/nipart/nipartd.conf.d";
+    pub const DEFAULT_STATE_DIR: &'static str = "/etc/nipart/states/internal";
+}
+
+impl NipartCanIpc for NipartDaemonConf {
+    fn ipc_kind(&self) -> String {
+        "daemon-conf".to_string()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonDisplay)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields, default)]
+#[non_exhaustive]
+pub struct NipartDaemonApiConf {
+    /// UNIX socket path for client connection, changes require daemon
+    /// restart. Default to [NipartClient::DEFAULT_SOCKET_PATH].
+    pub socket_path: String,
+    /// Maximum size in bytes of single IPC message, default to
+    /// [NipartIpcConnection::DEFAULT_MAX_SIZE].
+    pub ipc_max_size: usize,
+}
+
+impl Default for NipartDaemonApiConf {
+    fn default() -> Self {
+        Self {
+            socket_path: NipartClient::DEFAULT_SOCKET_PATH.to_string(),
+            ipc_max_size: NipartIpcConnection::DEFAULT_MAX_SIZE,
+        }
+    }
+}
+
+/// Retry settings for applying saved state to NICs not initialized by udev
+/// yet during daemon start.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonDisplay)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields, default)]
+#[non_exhaustive]
+pub struct NipartDaemonBootupConf {
+    /// Maximum retry count, default to 30.
+    pub nic_check_max_count: u64,
+    /// Retry count using quick interval, default to 10.
+    pub nic_check_max_quick: u64,
+    /// Retry interval in milliseconds during quick retry, default to 500.
+    pub nic_check_interval_ms_quick: u64,
+    /// Retry interval in seconds after quick retry, default to 10.
+    pub nic_check_interval_sec_slow: u64,
+}
+
+impl Default for NipartDaemonBootupConf {
+    fn default() -> Self {
+        Self {
+            nic_check_max_count: 30,
+            nic_check_max_quick: 10,
+            nic_check_interval_ms_quick: 500,
+            nic_check_interval_sec_slow: 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonDisplay)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields, default)]
+#[non_exhaustive]
+pub struct NipartDaemonApplyConf {
+    /// Retry count on verification failure, default to 10.
+    pub verify_retry_count: usize,
+    /// Retry interval in milliseconds on verification failure, default to
+    /// 500.
+    pub verify_retry_interval_ms: u64,
+    /// Maximum count of apply transactions kept in history under
+    /// `state-dir`, oldest are removed first. Set to 0 to disable history.
+    /// Default to 100.
+    pub history_size: usize,
+    /// Maximum count of saved state generations kept under `state-dir` for
+    /// `npt restore`, oldest are removed first. Set to 0 to disable
+    /// generations. Default to 10.
+    pub generation_size: usize,
+}
+
+impl Default for NipartDaemonApplyConf {
+    fn default() -> Self {
+        Self {
+            verify_retry_count: 10,
+            verify_retry_interval_ms: 500,
+            history_size: 100,
+            generation_size: 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonDisplay)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields, default)]
+#[non_exhaustive]
+pub struct NipartDaemonMonitorConf {
+    /// Seconds to consider previous identical link event expired, default
+    /// to 30.
+    pub event_expire_time_sec: u64,
+}
+
+impl Default for NipartDaemonMonitorConf {
+    fn default() -> Self {
+        Self {
+            event_expire_time_sec: 30,
+        }
+    }
+}
+
+/// Plugin settings, changes require daemon restart.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonDisplay)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields, default)]
+#[non_exhaustive]
+pub struct NipartDaemonPluginConf {
+    /// Folders to search plugin executables. Default to the folder of
+    /// daemon executable.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search_dirs: Option<Vec<String>>,
+    /// Folder for plugin UNIX sockets, default to
+    /// [NipartPluginClient::DEFAULT_SOCKET_DIR].
+    pub socket_dir: String,
+    /// Retry count for connecting plugins, default to 50.
+    pub connect_retry_count: u32,
+    /// Retry interval in milliseconds for connecting plugins, default
+    /// to 200.
+    pub connect_retry_interval_ms: u64,
+    /// Timeout in milliseconds of each request to plugin, default to 30000.
+    pub call_timeout_ms: u32,
+    /// Milliseconds to wait before restarting crashed plugin, doubled on
+    /// each consecutive crash, default to 1000.
+    pub restart_interval_ms_initial: u64,
+    /// Maximum seconds to wait before restarting crashed plugin, default
+    /// to 60.
+    pub restart_interval_sec_max: u64,
+    /// Policy of plugins indexed by plugin name, plugins not mentioned are
+    /// [NipartPluginPolicy::Optional].
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub policies: HashMap<String, NipartPluginPolicy>,
+}
+
+impl Default for NipartDaemonPluginConf {
+    fn default() -> Self {
+        Self {
+            search_dirs: None,
+            socket_dir: NipartPluginClient::DEFAULT_SOCKET_DIR.to_string(),
+            connect_retry_count: 50,
+            connect_retry_interval_ms: 200,
+            call_timeout_ms: 30000,
+            restart_interval_ms_initial: 1000,
+            restart_interval_sec_max: 60,
+            policies: HashMap::new(),
+        }
+    }
+}
+
+impl NipartDaemonPluginConf {
+    pub fn policy(&self, plugin_name: &str) -> NipartPluginPolicy {
+        self.policies.get(plugin_name).copied().unwrap_or_default()
+    }
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonDisplay,
+)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+#[derive(Default)]
+pub enum NipartPluginPolicy {
+    /// Failure or absence of plugin fails the query or apply.
+    Required,
+    /// Failure of plugin is logged as warning and ignored.
+    #[default]
+    Optional,
+}
+
+/// Reconciler fixing drift of network state from saved state caused by
+/// changes done outside of nipart, e.g. `ip addr del`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonDisplay)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields, default)]
+#[non_exhaustive]
+pub struct NipartDaemonReconcileConf {
+    /// Whether to reapply saved state on drift, default to false.
+    pub enabled: bool,
+    /// Milliseconds to wait after first link, address or route change
+    /// before checking drift, default to 1000.
+    pub settle_time_ms: u64,
+    /// Minimum seconds between two drift checks, default to 10.
+    pub min_interval_sec: u64,
+}
+
+impl Default for NipartDaemonReconcileConf {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            settle_time_ms: 1000,
+            min_interval_sec: 10,
+        }
+    }
+}
+
+/// D-Bus service `org.nipart.Nipart1` mirroring the UNIX socket API.
+#[derive(
+    Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize, JsonDisplay,
+)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields, default)]
+#[non_exhaustive]
+pub struct NipartDaemonDbusConf {
+    /// Whether to provide D-Bus service, default to false.
+    pub enabled: bool,
+    /// Bus to provide D-Bus service on, default to
+    /// [NipartDaemonDbusBus::System].
+    pub bus: NipartDaemonDbusBus,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Default,
+    Serialize,
+    Deserialize,
+    JsonDisplay,
+)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum NipartDaemonDbusBus {
+    #[default]
+    System,
+    /// Session bus defined by `DBUS_SESSION_BUS_ADDRESS` environment
+    /// variable of daemon, mostly used for testing.
+    Session,
+}
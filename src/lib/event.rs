@@ -0,0 +1,112 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use serde::{Deserialize, Serialize};
+
+use crate::{JsonDisplay, NipartCanIpc, NipartUuid};
+
+/// Event sent by daemon to client subscribed via
+/// [crate::NipartClient::subscribe()].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonDisplay)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum NipartEvent {
+    /// Link carrier of specified interface changed to up
+    CarrierUp(String),
+    /// Link carrier of specified interface changed to down
+    CarrierDown(String),
+    /// DHCPv4 lease acquired on specified interface
+    DhcpLeaseAcquired(NipartDhcpLeaseEvent),
+    /// DHCPv4 lease lost on specified interface
+    DhcpLeaseLost(String),
+    /// Daemon started applying network state with specified transaction ID
+    ApplyStarted(NipartUuid),
+    /// Daemon finished applying network state with specified transaction ID
+    ApplyFinished(NipartUuid),
+    /// Daemon failed to apply network state with specified transaction ID,
+    /// the second value is the error message
+    ApplyFailed(NipartUuid, String),
+    /// Network state of specified transaction ID has been rolled back
+    ApplyRolledBack(NipartUuid),
+    /// WiFi interface associated to access point
+    WifiAssociated(NipartWifiEvent),
+    /// WiFi interface disassociated from access point
+    WifiDisassociated(NipartWifiEvent),
+    /// Reconciler reapplied saved state to fix drift of network state
+    DriftCorrected(NipartDriftEvent),
+    /// Reconciler failed to fix drift of network state
+    DriftCorrectionFailed(NipartDriftEvent),
+}
+
+impl NipartCanIpc for NipartEvent {
+    fn ipc_kind(&self) -> String {
+        "event".to_string()
+    }
+}
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize, JsonDisplay,
+)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub struct NipartDhcpLeaseEvent {
+    pub iface_name: String,
+    /// IPv4 address with prefix length, e.g. `192.0.2.10/24`
+    pub address: String,
+}
+
+impl NipartDhcpLeaseEvent {
+    pub fn new(iface_name: String, address: String) -> Self {
+        Self {
+            iface_name,
+            address,
+        }
+    }
+}
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize, JsonDisplay,
+)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub struct NipartWifiEvent {
+    pub iface_name: String,
+    /// SSID of access point, might be unknown on disassociation
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub ssid: Option<String>,
+}
+
+impl NipartWifiEvent {
+    pub fn new(iface_name: String, ssid: Option<String>) -> Self {
+        Self { iface_name, ssid }
+    }
+}
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize, JsonDisplay,
+)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub struct NipartDriftEvent {
+    /// Interfaces drifted from saved state
+    #[serde(default)]
+    pub ifaces: Vec<String>,
+    /// Whether routes drifted from saved state
+    #[serde(default)]
+    pub routes: bool,
+    /// Description of the drift
+    pub reason: String,
+    /// Failure of fixing the drift
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub error: Option<String>,
+}
+
+impl NipartDriftEvent {
+    pub fn new(ifaces: Vec<String>, routes: bool, reason: String) -> Self {
+        Self {
+            ifaces,
+            routes,
+            reason,
+            error: None,
+        }
+    }
+}
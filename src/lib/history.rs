@@ -0,0 +1,93 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use serde::{Deserialize, Serialize};
+
+use crate::{JsonDisplay, NetworkState, NipartCanIpc, NipartError, NipartUuid};
+
+/// Record of single apply transaction stored in daemon history.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonDisplay)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub struct NipartHistoryEntry {
+    /// Transaction ID, identical to the one in
+    /// [crate::NipartEvent::ApplyStarted].
+    pub id: NipartUuid,
+    /// Time when transaction started
+    pub time: chrono::DateTime<chrono::Utc>,
+    /// UID of client process, not set when applied by daemon itself, e.g.
+    /// applying saved state on boot.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peer_uid: Option<u32>,
+    /// PID of client process, not set when applied by daemon itself.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peer_pid: Option<i32>,
+    /// Desired state with secrets hidden
+    pub desired_state: NetworkState,
+    /// State changed by this transaction with secrets hidden, only set on
+    /// success.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff_state: Option<NetworkState>,
+    pub outcome: NipartHistoryOutcome,
+    /// Only set on failure. Might quote secrets in desired or current
+    /// state, hence only root could query history.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<NipartError>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum NipartHistoryOutcome {
+    Succeeded,
+    /// Failed to apply, daemon rolled back to the state before apply.
+    Failed,
+}
+
+impl std::fmt::Display for NipartHistoryOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Succeeded => "succeeded",
+                Self::Failed => "failed",
+            }
+        )
+    }
+}
+
+impl NipartCanIpc for Vec<NipartHistoryEntry> {
+    fn ipc_kind(&self) -> String {
+        "history-entries".to_string()
+    }
+}
+
+impl NipartHistoryEntry {
+    /// Create history entry with secrets of `desired_state` and result
+    /// hidden.
+    pub fn new(
+        id: NipartUuid,
+        time: chrono::DateTime<chrono::Utc>,
+        mut desired_state: NetworkState,
+        result: Result<NetworkState, NipartError>,
+    ) -> Self {
+        desired_state.hide_secrets();
+        let (outcome, diff_state, error) = match result {
+            Ok(mut diff_state) => {
+                diff_state.hide_secrets();
+                (NipartHistoryOutcome::Succeeded, Some(diff_state), None)
+            }
+            Err(e) => (NipartHistoryOutcome::Failed, None, Some(e)),
+        };
+        Self {
+            id,
+            time,
+            peer_uid: None,
+            peer_pid: None,
+            desired_state,
+            diff_state,
+            outcome,
+            error,
+        }
+    }
+}
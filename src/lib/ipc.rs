@@ -6,9 +6,10 @@ use serde::{Serialize, Serializer, de::DeserializeOwned, ser::SerializeMap};
 use tokio::{
     io::{AsyncReadExt, AsyncWriteExt},
     net::UnixStream,
+    sync::mpsc::UnboundedSender,
 };
 
-use crate::{ErrorKind, NipartError, NipartLogEntry};
+use crate::{ErrorKind, NipartError, NipartLogEntry, NipartLogLevel};
 
 #[derive(Debug)]
 /// IPC communication between:
@@ -20,9 +21,15 @@ use crate::{ErrorKind, NipartError, NipartLogEntry};
 pub struct NipartIpcConnection {
     /// Timeout in milliseconds.
     pub(crate) timeout_ms: u32,
+    /// Maximum size in bytes of single IPC message.
+    pub(crate) max_size: usize,
     pub(crate) socket: UnixStream,
     pub(crate) log_prefix: String,
     pub(crate) log_target: String,
+    /// Maximum level of log entries sent to remote end.
+    pub(crate) log_level: NipartLogLevel,
+    /// Where to forward log entries received from remote end.
+    pub(crate) log_forwarder: Option<UnboundedSender<NipartLogEntry>>,
 }
 
 impl std::os::fd::AsFd for NipartIpcConnection {
@@ -34,14 +41,38 @@ impl std::os::fd::AsFd for NipartIpcConnection {
 impl NipartIpcConnection {
     const DEFAULT_TIMEOUT_MS: u32 = 30000;
 
-    // Only accept size smaller than 10 MiB
-    // TODO(Gris Ge): Provide a mechanism to change this limitation
-    const IPC_MAX_SIZE: usize = 1024 * 1024 * 10;
+    /// Only accept size smaller than 10 MiB by default
+    pub const DEFAULT_MAX_SIZE: usize = 1024 * 1024 * 10;
 
     pub fn set_timeout(&mut self, timeout_ms: u32) {
         self.timeout_ms = timeout_ms;
     }
 
+    /// Set maximum size in bytes of single IPC message, default to
+    /// [NipartIpcConnection::DEFAULT_MAX_SIZE].
+    pub fn set_max_size(&mut self, max_size: usize) {
+        self.max_size = max_size;
+    }
+
+    /// Set maximum level of log entries sent to remote end, default to
+    /// [NipartLogLevel::Trace].
+    pub fn set_log_level(&mut self, log_level: NipartLogLevel) {
+        self.log_level = log_level;
+    }
+
+    pub fn log_level(&self) -> NipartLogLevel {
+        self.log_level
+    }
+
+    /// Besides emitting locally, forward log entries received from remote
+    /// end to specified sender.
+    pub fn set_log_forwarder(
+        &mut self,
+        sender: Option<UnboundedSender<NipartLogEntry>>,
+    ) {
+        self.log_forwarder = sender;
+    }
+
     pub async fn new_with_path(
         socket_path: &str,
         src_name: &str,
@@ -67,8 +98,11 @@ impl NipartIpcConnection {
         Self {
             socket: stream,
             timeout_ms: Self::DEFAULT_TIMEOUT_MS,
+            max_size: Self::DEFAULT_MAX_SIZE,
             log_prefix: format!("{src_name}<->{dst_name}: "),
             log_target: format!("nm.{src_name}"),
+            log_level: NipartLogLevel::Trace,
+            log_forwarder: None,
         }
     }
 
@@ -88,7 +122,7 @@ impl NipartIpcConnection {
             )
         })?;
         let data = json_str.as_bytes();
-        if data.len() > Self::IPC_MAX_SIZE {
+        if data.len() > self.max_size {
             return Err(NipartError::new(
                 ErrorKind::IpcMessageTooLarge,
                 format!(
@@ -96,7 +130,7 @@ impl NipartIpcConnection {
                      support({}): {}",
                     self.log_prefix,
                     data.len(),
-                    Self::IPC_MAX_SIZE,
+                    self.max_size,
                     json_str,
                 ),
             ));
@@ -131,11 +165,16 @@ impl NipartIpcConnection {
         Ok(())
     }
 
+    /// Send log entry to remote end, ignored if its level is higher than
+    /// [NipartIpcConnection::log_level()].
     pub async fn log(&mut self, log: NipartLogEntry) -> Result<(), NipartError> {
-        self.send(Ok(log)).await
+        if log.level <= self.log_level {
+            self.send(Ok(log)).await
+        } else {
+            Ok(())
+        }
     }
 
-    // TODO (Gris Ge): Support redirecting plugin log to user
     pub async fn recv<T>(&mut self) -> Result<T, NipartError>
     where
         T: NipartCanIpc,
@@ -152,7 +191,12 @@ impl NipartIpcConnection {
                         remain_time -= elapsed;
                     }
                     match msg {
-                        NipartMessage::Log(l) => l.emit(),
+                        NipartMessage::Log(l) => {
+                            l.emit();
+                            if let Some(sender) = self.log_forwarder.as_ref() {
+                                sender.send(l).ok();
+                            }
+                        }
                         NipartMessage::Error(e) => return Err(e),
                         NipartMessage::Data(d) => return Ok(d),
                     }
@@ -202,15 +246,13 @@ impl NipartIpcConnection {
                 format!("{}Connection is closed by remote", self.log_prefix),
             ));
         }
-        if message_size >= Self::IPC_MAX_SIZE {
+        if message_size >= self.max_size {
             return Err(NipartError::new(
                 ErrorKind::IpcMessageTooLarge,
                 format!(
                     "{}Received size({}) of IPC message exceeded the maximum \
                      support({})",
-                    self.log_prefix,
-                    message_size,
-                    Self::IPC_MAX_SIZE
+                    self.log_prefix, message_size, self.max_size
                 ),
             ));
         }
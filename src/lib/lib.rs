@@ -1,26 +1,45 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod client;
+mod daemon_conf;
 mod error;
+mod event;
+mod history;
 mod ipc;
 mod logging;
 mod nmstate;
 mod no_daemon;
 mod plugin;
 mod uuid;
+mod wait_online;
 
 pub use nipart_derive::{JsonDisplay, JsonDisplayHideSecrets};
 
 pub use self::{
     client::{NipartClient, NipartClientCmd},
+    daemon_conf::{
+        NipartDaemonApiConf, NipartDaemonApplyConf, NipartDaemonBootupConf,
+        NipartDaemonConf, NipartDaemonDbusBus, NipartDaemonDbusConf,
+        NipartDaemonMonitorConf, NipartDaemonPluginConf,
+        NipartDaemonReconcileConf, NipartPluginPolicy,
+    },
     error::{ErrorKind, NipartError},
+    event::{
+        NipartDhcpLeaseEvent, NipartDriftEvent, NipartEvent, NipartWifiEvent,
+    },
+    history::{NipartHistoryEntry, NipartHistoryOutcome},
     ipc::{NipartCanIpc, NipartIpcConnection},
     logging::{NipartLogEntry, NipartLogLevel},
     nmstate::*,
-    no_daemon::NipartNoDaemon,
+    no_daemon::{NipartIpv4LinkLocal, NipartNoDaemon},
     plugin::{
-        NipartIpcListener, NipartPlugin, NipartPluginClient, NipartPluginCmd,
-        NipartPluginInfo,
+        NipartIpcListener, NipartPlugin, NipartPluginCapabilities,
+        NipartPluginClient, NipartPluginCmd, NipartPluginCmdKind,
+        NipartPluginInfo, NipartstateSection,
     },
     uuid::NipartUuid,
+    wait_online::{
+        NipartWaitOnlineCondition, NipartWaitOnlineIface,
+        NipartWaitOnlineOption,
+    },
 };
@@ -200,11 +200,11 @@ impl NipartIpcConnection {
     /// remote end(ignore failure of transmission).
     pub async fn log_trace(&mut self, msg: String) {
         log::trace!(target: &self.log_target, "{msg}");
-        self.send(Ok(NipartLogEntry {
+        self.log(NipartLogEntry {
             source: self.log_target.to_string(),
             level: NipartLogLevel::Trace,
             message: msg,
-        }))
+        })
         .await
         .ok();
     }
@@ -213,11 +213,11 @@ impl NipartIpcConnection {
     /// remote end(ignore failure of transmission).
     pub async fn log_debug(&mut self, msg: String) {
         log::debug!(target: &self.log_target, "{msg}");
-        self.send(Ok(NipartLogEntry {
+        self.log(NipartLogEntry {
             source: self.log_target.to_string(),
             level: NipartLogLevel::Debug,
             message: msg,
-        }))
+        })
         .await
         .ok();
     }
@@ -226,11 +226,11 @@ impl NipartIpcConnection {
     /// end(ignore failure of transmission).
     pub async fn log_info(&mut self, msg: String) {
         log::info!(target: &self.log_target, "{msg}");
-        self.send(Ok(NipartLogEntry {
+        self.log(NipartLogEntry {
             source: self.log_target.to_string(),
             level: NipartLogLevel::Info,
             message: msg,
-        }))
+        })
         .await
         .ok();
     }
@@ -239,11 +239,11 @@ impl NipartIpcConnection {
     /// end(ignore failure of transmission).
     pub async fn log_warn(&mut self, msg: String) {
         log::warn!(target: &self.log_target, "{msg}");
-        self.send(Ok(NipartLogEntry {
+        self.log(NipartLogEntry {
             source: self.log_target.to_string(),
             level: NipartLogLevel::Warn,
             message: msg,
-        }))
+        })
         .await
         .ok();
     }
@@ -252,11 +252,11 @@ impl NipartIpcConnection {
     /// end(ignore failure of transmission).
     pub async fn log_error(&mut self, msg: String) {
         log::error!(target: &self.log_target, "{msg}");
-        self.send(Ok(NipartLogEntry {
+        self.log(NipartLogEntry {
             source: self.log_target.to_string(),
             level: NipartLogLevel::Error,
             message: msg,
-        }))
+        })
         .await
         .ok();
     }
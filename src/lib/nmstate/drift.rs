@@ -0,0 +1,126 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    InterfaceType, JsonDisplay, NetworkState, NipartError,
+    NipartstateInterface, RouteEntry,
+};
+
+/// Difference between saved and running network state, generated by
+/// [NetworkState::gen_drift()].
+#[derive(
+    Debug, Clone, PartialEq, Default, Serialize, Deserialize, JsonDisplay,
+)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub struct NipartDriftReport {
+    /// Saved interfaces not found in running state
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub missing: Vec<NipartDriftIface>,
+    /// Running interfaces not mentioned in saved state
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unmanaged: Vec<NipartDriftIface>,
+    /// Running routes not mentioned in saved state
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unmanaged_routes: Vec<RouteEntry>,
+    /// Properties of saved state not matching running state, holding the
+    /// saved values
+    #[serde(default, skip_serializing_if = "NetworkState::is_empty")]
+    pub changed: NetworkState,
+}
+
+impl NipartDriftReport {
+    pub fn is_empty(&self) -> bool {
+        self.missing.is_empty()
+            && self.unmanaged.is_empty()
+            && self.unmanaged_routes.is_empty()
+            && self.changed.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub struct NipartDriftIface {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub iface_type: InterfaceType,
+}
+
+impl NipartDriftIface {
+    fn new(iface: &impl NipartstateInterface) -> Self {
+        Self {
+            name: iface.name().to_string(),
+            iface_type: iface.iface_type().clone(),
+        }
+    }
+}
+
+impl NetworkState {
+    /// Generate report on how `current` drifted from `self`(the saved state).
+    /// Loopback interface and interfaces marked as
+    /// [crate::InterfaceState::Ignore] in saved state are excluded along with
+    /// their routes.
+    pub fn gen_drift(
+        &self,
+        current: &Self,
+    ) -> Result<NipartDriftReport, NipartError> {
+        let mut ret = NipartDriftReport::default();
+        let mut saved = self.clone();
+
+        let is_excluded = |iface_name: &str| {
+            self.ifaces
+                .get(iface_name, None)
+                .is_some_and(|i| i.is_ignore())
+                || current
+                    .ifaces
+                    .get(iface_name, None)
+                    .is_some_and(|i| i.iface_type() == &InterfaceType::Loopback)
+        };
+
+        for iface in self.ifaces.iter() {
+            if iface.is_ignore() {
+                saved.ifaces.remove(iface.name(), Some(iface.iface_type()));
+            } else if current
+                .ifaces
+                .get(iface.name(), Some(iface.iface_type()))
+                .is_none()
+            {
+                saved.ifaces.remove(iface.name(), Some(iface.iface_type()));
+                if !iface.is_absent() {
+                    ret.missing.push(NipartDriftIface::new(iface));
+                }
+            }
+        }
+        ret.missing.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+
+        ret.changed = saved.gen_diff(current)?;
+
+        for cur_iface in current.ifaces.kernel_ifaces.values() {
+            if self.ifaces.get(cur_iface.name(), None).is_none()
+                && !is_excluded(cur_iface.name())
+            {
+                ret.unmanaged.push(NipartDriftIface::new(cur_iface));
+            }
+        }
+        ret.unmanaged.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+
+        let saved_routes: Vec<_> = self
+            .routes
+            .config
+            .as_deref()
+            .unwrap_or_default()
+            .iter()
+            .filter(|r| !r.is_absent())
+            .collect();
+        for cur_route in current.routes.config.as_deref().unwrap_or_default() {
+            if !saved_routes.iter().any(|r| r.is_match(cur_route))
+                && !cur_route.next_hop_iface.as_deref().is_some_and(is_excluded)
+            {
+                ret.unmanaged_routes.push(cur_route.clone());
+            }
+        }
+        Ok(ret)
+    }
+}
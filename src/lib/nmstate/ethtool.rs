@@ -0,0 +1,400 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// This file is based on the work of nmstate project(https://nmstate.io/) which
+// is under license of Apache 2.0, authors of original file are:
+//  * Gris Ge <fge@redhat.com>
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{ErrorKind, JsonDisplay, NipartError};
+
+// Alias of `ethtool -K` command line to kernel feature name
+const ETHTOOL_FEATURE_CLI_ALIAS: [(&str, &str); 11] = [
+    ("rx", "rx-checksum"),
+    ("sg", "tx-scatter-gather"),
+    ("tso", "tx-tcp-segmentation"),
+    ("ufo", "tx-udp-fragmentation"),
+    ("gso", "tx-generic-segmentation"),
+    ("gro", "rx-gro"),
+    ("lro", "rx-lro"),
+    ("rxvlan", "rx-vlan-hw-parse"),
+    ("txvlan", "tx-vlan-hw-insert"),
+    ("ntuple", "rx-ntuple-filter"),
+    ("rxhash", "rx-hashing"),
+];
+
+/// Ethtool settings of network interface.
+/// Example YAML of disabling TCP segmentation offload and enlarging RX ring:
+/// ```yaml
+/// ---
+/// interfaces:
+///   - name: eth1
+///     type: ethernet
+///     state: up
+///     ethtool:
+///       feature:
+///         tso: false
+///       ring:
+///         rx: 4096
+/// ```
+#[derive(
+    Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize, JsonDisplay,
+)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+#[non_exhaustive]
+pub struct EthtoolConfig {
+    /// Pause frame(IEEE 802.3x flow control) settings.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pause: Option<EthtoolPauseConfig>,
+    /// Offload features using kernel feature name as key, e.g.
+    /// `tx-tcp-segmentation`. The short names of `ethtool -K` command like
+    /// `tso`, `gro` and `rx` are also accepted when applying. Query only
+    /// show changeable features.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feature: Option<BTreeMap<String, bool>>,
+    /// Interrupt coalescing settings.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coalesce: Option<EthtoolCoalesceConfig>,
+    /// Ring buffer sizes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ring: Option<EthtoolRingConfig>,
+    /// Number of queues(channels).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channels: Option<EthtoolChannelsConfig>,
+    /// Forward error correction settings.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fec: Option<EthtoolFecConfig>,
+}
+
+impl EthtoolConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// * Convert `ethtool -K` command line alias to kernel feature name.
+    /// * Remove query only properties.
+    pub(crate) fn sanitize(&mut self) -> Result<(), NipartError> {
+        if let Some(features) = self.feature.as_mut() {
+            for (alias, kernel_name) in ETHTOOL_FEATURE_CLI_ALIAS {
+                if let Some(value) = features.remove(alias) {
+                    if let Some(kernel_value) = features.get(kernel_name)
+                        && *kernel_value != value
+                    {
+                        return Err(NipartError::new(
+                            ErrorKind::InvalidArgument,
+                            format!(
+                                "Ethtool feature {alias} is alias of \
+                                 {kernel_name}, but they are set to \
+                                 different values"
+                            ),
+                        ));
+                    }
+                    features.insert(kernel_name.to_string(), value);
+                }
+            }
+        }
+        if let Some(fec) = self.fec.as_mut() {
+            fec.active = None;
+        }
+        Ok(())
+    }
+}
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize, JsonDisplay,
+)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+#[non_exhaustive]
+pub struct EthtoolPauseConfig {
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::deserializer::option_bool_or_string"
+    )]
+    pub rx: Option<bool>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::deserializer::option_bool_or_string"
+    )]
+    pub tx: Option<bool>,
+    /// Whether pause frame settings are auto negotiated.
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::deserializer::option_bool_or_string"
+    )]
+    pub autoneg: Option<bool>,
+}
+
+impl EthtoolPauseConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize, JsonDisplay,
+)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+#[non_exhaustive]
+pub struct EthtoolCoalesceConfig {
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::deserializer::option_bool_or_string"
+    )]
+    pub adaptive_rx: Option<bool>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::deserializer::option_bool_or_string"
+    )]
+    pub adaptive_tx: Option<bool>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::deserializer::option_u32_or_string"
+    )]
+    pub pkt_rate_high: Option<u32>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::deserializer::option_u32_or_string"
+    )]
+    pub pkt_rate_low: Option<u32>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::deserializer::option_u32_or_string"
+    )]
+    pub rate_sample_interval: Option<u32>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::deserializer::option_u32_or_string"
+    )]
+    pub rx_frames: Option<u32>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::deserializer::option_u32_or_string"
+    )]
+    pub rx_frames_high: Option<u32>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::deserializer::option_u32_or_string"
+    )]
+    pub rx_frames_irq: Option<u32>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::deserializer::option_u32_or_string"
+    )]
+    pub rx_frames_low: Option<u32>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::deserializer::option_u32_or_string"
+    )]
+    pub rx_usecs: Option<u32>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::deserializer::option_u32_or_string"
+    )]
+    pub rx_usecs_high: Option<u32>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::deserializer::option_u32_or_string"
+    )]
+    pub rx_usecs_irq: Option<u32>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::deserializer::option_u32_or_string"
+    )]
+    pub rx_usecs_low: Option<u32>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::deserializer::option_u32_or_string"
+    )]
+    pub stats_block_usecs: Option<u32>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::deserializer::option_u32_or_string"
+    )]
+    pub tx_frames: Option<u32>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::deserializer::option_u32_or_string"
+    )]
+    pub tx_frames_high: Option<u32>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::deserializer::option_u32_or_string"
+    )]
+    pub tx_frames_irq: Option<u32>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::deserializer::option_u32_or_string"
+    )]
+    pub tx_frames_low: Option<u32>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::deserializer::option_u32_or_string"
+    )]
+    pub tx_usecs: Option<u32>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::deserializer::option_u32_or_string"
+    )]
+    pub tx_usecs_high: Option<u32>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::deserializer::option_u32_or_string"
+    )]
+    pub tx_usecs_irq: Option<u32>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::deserializer::option_u32_or_string"
+    )]
+    pub tx_usecs_low: Option<u32>,
+}
+
+impl EthtoolCoalesceConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize, JsonDisplay,
+)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+#[non_exhaustive]
+pub struct EthtoolRingConfig {
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::deserializer::option_u32_or_string"
+    )]
+    pub rx: Option<u32>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::deserializer::option_u32_or_string"
+    )]
+    pub rx_jumbo: Option<u32>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::deserializer::option_u32_or_string"
+    )]
+    pub rx_mini: Option<u32>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::deserializer::option_u32_or_string"
+    )]
+    pub tx: Option<u32>,
+}
+
+impl EthtoolRingConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize, JsonDisplay,
+)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+#[non_exhaustive]
+pub struct EthtoolChannelsConfig {
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::deserializer::option_u32_or_string"
+    )]
+    pub rx: Option<u32>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::deserializer::option_u32_or_string"
+    )]
+    pub tx: Option<u32>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::deserializer::option_u32_or_string"
+    )]
+    pub other: Option<u32>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::deserializer::option_u32_or_string"
+    )]
+    pub combined: Option<u32>,
+}
+
+impl EthtoolChannelsConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize, JsonDisplay,
+)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+#[non_exhaustive]
+pub struct EthtoolFecConfig {
+    /// Configured FEC mode.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<EthtoolFecMode>,
+    /// FEC mode currently in use. Ignored during apply.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active: Option<EthtoolFecMode>,
+}
+
+impl EthtoolFecConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[derive(
+    Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, JsonDisplay,
+)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum EthtoolFecMode {
+    /// Select FEC mode automatically based on link mode and cable.
+    Auto,
+    /// No FEC.
+    Off,
+    /// Reed-Solomon FEC.
+    Rs,
+    /// BASE-R(Fire Code) FEC.
+    Baser,
+    /// Low Latency Reed-Solomon FEC.
+    Llrs,
+}
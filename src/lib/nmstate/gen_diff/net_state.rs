@@ -31,6 +31,15 @@ impl NetworkState {
             ret.version = None;
         }
 
+        if let Some(ip_forwarding) = self.ip_forwarding.as_ref() {
+            let diff = ip_forwarding.gen_diff(old.ip_forwarding.as_ref());
+            if !diff.is_empty() {
+                ret.ip_forwarding = Some(diff);
+            }
+        }
+
+        ret.plugins = self.plugins.gen_diff(&old.plugins);
+
         let merged_state =
             MergedNetworkState::new(self, old, Default::default())?;
 
@@ -0,0 +1,205 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashMap;
+
+use crate::{
+    ErrorKind, Interface, InterfaceIdentifier, Interfaces, NetworkState,
+    NipartError, NipartstateInterface,
+};
+
+impl NetworkState {
+    /// Rename interfaces not using [InterfaceIdentifier::Name] to the name
+    /// of kernel interface in `current` matching their identifier, next hop
+    /// interface of routes included. Interfaces without matching kernel
+    /// interface are untouched. Port list of controller is not changed.
+    ///
+    /// Return a HashMap with kernel interface name as key and logical name
+    /// as value, which could be used by
+    /// [NetworkState::restore_iface_identifiers()].
+    pub fn resolve_iface_identifiers(
+        &mut self,
+        current: &Self,
+    ) -> Result<HashMap<String, String>, NipartError> {
+        let mut ret: HashMap<String, String> = HashMap::new();
+        for iface in self
+            .ifaces
+            .kernel_ifaces
+            .values()
+            .filter(|i| !i.base_iface().identifier.is_name())
+        {
+            validate_identifier(iface)?;
+            let matched: Vec<&str> = current
+                .ifaces
+                .kernel_ifaces
+                .values()
+                .filter(|cur_iface| {
+                    !cur_iface.is_virtual()
+                        && iface
+                            .base_iface()
+                            .match_kernel_iface(cur_iface.base_iface())
+                })
+                .map(|cur_iface| cur_iface.name())
+                .collect();
+            match matched.as_slice() {
+                [] => {
+                    log::debug!(
+                        "No kernel interface matches {} with identifier {}",
+                        iface.name(),
+                        iface.base_iface().identifier
+                    );
+                }
+                [kernel_name] => {
+                    if let Some(other) = ret.insert(
+                        kernel_name.to_string(),
+                        iface.name().to_string(),
+                    ) {
+                        return Err(NipartError::new(
+                            ErrorKind::InvalidArgument,
+                            format!(
+                                "Interface {other} and {} are both matching \
+                                 kernel interface {kernel_name}",
+                                iface.name()
+                            ),
+                        ));
+                    }
+                }
+                _ => {
+                    return Err(NipartError::new(
+                        ErrorKind::InvalidArgument,
+                        format!(
+                            "Interface {} with identifier {} matches \
+                             multiple kernel interfaces: {}",
+                            iface.name(),
+                            iface.base_iface().identifier,
+                            matched.join(", ")
+                        ),
+                    ));
+                }
+            }
+        }
+        ret.retain(|kernel_name, logical_name| kernel_name != logical_name);
+
+        // Remove all before renaming in case two interfaces swapped names
+        let mut renamed_ifaces = Vec::new();
+        for (kernel_name, logical_name) in ret.iter() {
+            if let Some(iface) = self.ifaces.kernel_ifaces.remove(logical_name)
+            {
+                renamed_ifaces.push((kernel_name.as_str(), iface));
+            }
+        }
+        for (kernel_name, iface) in renamed_ifaces.iter() {
+            if let Some(other) = self.ifaces.kernel_ifaces.get(*kernel_name) {
+                return Err(NipartError::new(
+                    ErrorKind::InvalidArgument,
+                    format!(
+                        "Interface {} is matching kernel interface \
+                         {kernel_name} which is also defined as {}",
+                        iface.name(),
+                        other.iface_type()
+                    ),
+                ));
+            }
+        }
+        let logical_to_kernel: HashMap<String, String> = ret
+            .iter()
+            .map(|(k, v)| (v.to_string(), k.to_string()))
+            .collect();
+        rename_ifaces(&mut self.ifaces, renamed_ifaces, &logical_to_kernel);
+        rename_route_next_hops(self, &logical_to_kernel);
+        Ok(ret)
+    }
+
+    /// Revert the renaming done by
+    /// [NetworkState::resolve_iface_identifiers()].
+    pub fn restore_iface_identifiers(
+        &mut self,
+        iface_names: &HashMap<String, String>,
+    ) {
+        let mut renamed_ifaces = Vec::new();
+        for (kernel_name, logical_name) in iface_names.iter() {
+            if self
+                .ifaces
+                .kernel_ifaces
+                .get(kernel_name)
+                .map(|i| i.base_iface().identifier.is_name())
+                == Some(false)
+                && let Some(iface) =
+                    self.ifaces.kernel_ifaces.remove(kernel_name)
+            {
+                renamed_ifaces.push((logical_name.as_str(), iface));
+            }
+        }
+        rename_ifaces(&mut self.ifaces, renamed_ifaces, iface_names);
+        rename_route_next_hops(self, iface_names);
+    }
+}
+
+fn validate_identifier(iface: &Interface) -> Result<(), NipartError> {
+    let base_iface = iface.base_iface();
+    let missing_prop = match base_iface.identifier {
+        InterfaceIdentifier::MacAddress if base_iface.mac_address.is_none() => {
+            Some("mac-address")
+        }
+        InterfaceIdentifier::PciPath if base_iface.pci_path.is_none() => {
+            Some("pci-path")
+        }
+        _ => None,
+    };
+    if let Some(prop) = missing_prop {
+        return Err(NipartError::new(
+            ErrorKind::InvalidArgument,
+            format!(
+                "Interface {} is using identifier {} but has no {prop} \
+                 defined",
+                iface.name(),
+                base_iface.identifier
+            ),
+        ));
+    }
+    if iface.is_virtual() {
+        return Err(NipartError::new(
+            ErrorKind::InvalidArgument,
+            format!(
+                "Virtual interface {}/{} can only use identifier {}",
+                iface.name(),
+                iface.iface_type(),
+                InterfaceIdentifier::Name
+            ),
+        ));
+    }
+    Ok(())
+}
+
+// Rename in `insert_order` also to preserve the position.
+fn rename_ifaces(
+    ifaces: &mut Interfaces,
+    renamed_ifaces: Vec<(&str, Interface)>,
+    names: &HashMap<String, String>,
+) {
+    for (new_name, mut iface) in renamed_ifaces {
+        iface.base_iface_mut().name = new_name.to_string();
+        ifaces.kernel_ifaces.insert(new_name.to_string(), iface);
+    }
+    for (name, _) in ifaces.insert_order.iter_mut() {
+        if let Some(new_name) = names.get(name) {
+            *name = new_name.to_string();
+        }
+    }
+}
+
+fn rename_route_next_hops(
+    net_state: &mut NetworkState,
+    names: &HashMap<String, String>,
+) {
+    if let Some(routes) = net_state.routes.config.as_mut() {
+        for route in routes.iter_mut() {
+            if let Some(new_name) = route
+                .next_hop_iface
+                .as_ref()
+                .and_then(|old_name| names.get(old_name))
+            {
+                route.next_hop_iface = Some(new_name.to_string());
+            }
+        }
+    }
+}
@@ -9,8 +9,8 @@
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    ErrorKind, InterfaceIpv4, InterfaceIpv6, InterfaceState, InterfaceType,
-    JsonDisplay, NipartError,
+    ErrorKind, EthtoolConfig, InterfaceIdentifier, InterfaceIpv4,
+    InterfaceIpv6, InterfaceState, InterfaceType, JsonDisplay, NipartError,
 };
 
 #[derive(
@@ -21,8 +21,12 @@ use crate::{
 /// Information shared among all interface types
 pub struct BaseInterface {
     pub name: String,
-    // TODO(Gris Ge): Introduce `iface_name` property so we can differentiate
-    // iface_name along with logical name(profile name).
+    /// How to find the kernel interface this config applies to, default to
+    /// [InterfaceIdentifier::Name]. When not matching by name, the `name`
+    /// property is the logical name and will be replaced by the name of
+    /// matched kernel interface when applying.
+    #[serde(default, skip_serializing_if = "InterfaceIdentifier::is_name")]
+    pub identifier: InterfaceIdentifier,
     #[serde(default, rename = "type")]
     pub iface_type: InterfaceType,
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -50,6 +54,16 @@ pub struct BaseInterface {
     /// Ignored during apply.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub permanent_mac_address: Option<String>,
+    /// PCI address of the device, e.g. `0000:00:03.0`. Used to match
+    /// kernel interface when `identifier` is [InterfaceIdentifier::PciPath],
+    /// ignored during apply otherwise.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pci_path: Option<String>,
+    /// Kernel driver of the device, e.g. `virtio_net`. When `identifier` is
+    /// not [InterfaceIdentifier::Name], only kernel interface using this
+    /// driver is matched. Ignored during apply otherwise.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub driver: Option<String>,
     /// Maximum transmission unit.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub mtu: Option<u64>,
@@ -71,10 +85,18 @@ pub struct BaseInterface {
     /// bond is not allowed to hold IP information).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ipv6: Option<InterfaceIpv6>,
+    /// Ethtool settings, e.g. offload features, ring sizes and interrupt
+    /// coalescing.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ethtool: Option<EthtoolConfig>,
 }
 
 impl BaseInterface {
-    pub fn hide_secrets(&mut self) {}
+    pub fn hide_secrets(&mut self) {
+        if let Some(ipv6) = self.ipv6.as_mut() {
+            ipv6.hide_secrets();
+        }
+    }
 
     pub fn sanitize(&mut self, current: Option<&Self>) -> Result<(), NipartError> {
         if let Some(ipv4) = self.ipv4.as_mut() {
@@ -83,6 +105,9 @@ impl BaseInterface {
         if let Some(ipv6) = self.ipv6.as_mut() {
             ipv6.sanitize(current.and_then(|c| c.ipv6.as_ref()))?;
         }
+        if let Some(ethtool) = self.ethtool.as_mut() {
+            ethtool.sanitize()?;
+        }
         self.iface_index = None;
         self.validate_mtu(current)?;
         Ok(())
@@ -119,6 +144,12 @@ impl BaseInterface {
 
     pub(crate) fn sanitize_before_verify(&mut self, current: &mut Self) {
         self.up_priority = 0;
+        // MAC address used as identifier is for matching kernel interface
+        // only, the MAC address in use might differ, e.g. bond port.
+        if self.identifier == InterfaceIdentifier::MacAddress {
+            self.mac_address = None;
+        }
+        self.identifier = InterfaceIdentifier::Name;
         if let Some(des_ipv4) = self.ipv4.as_mut()
             && let Some(cur_ipv4) = current.ipv4.as_mut()
         {
@@ -144,6 +175,35 @@ impl BaseInterface {
         self.state == InterfaceState::Absent
     }
 
+    /// Whether specified kernel interface matches the [InterfaceIdentifier]
+    /// of this interface.
+    pub fn match_kernel_iface(&self, current: &Self) -> bool {
+        match self.identifier {
+            InterfaceIdentifier::Name => return self.name == current.name,
+            InterfaceIdentifier::MacAddress => {
+                // Permanent MAC address is preferred as MAC address might be
+                // changed by bond or user
+                let cur_mac = current
+                    .permanent_mac_address
+                    .as_deref()
+                    .or(current.mac_address.as_deref());
+                if self.mac_address.is_none()
+                    || self.mac_address.as_deref().map(|m| m.to_uppercase())
+                        != cur_mac.map(|m| m.to_uppercase())
+                {
+                    return false;
+                }
+            }
+            InterfaceIdentifier::PciPath => {
+                if self.pci_path.is_none() || self.pci_path != current.pci_path
+                {
+                    return false;
+                }
+            }
+        }
+        self.driver.is_none() || self.driver == current.driver
+    }
+
     pub(crate) fn is_up_priority_valid(&self) -> bool {
         if self.has_controller() {
             self.up_priority != 0
@@ -195,6 +255,37 @@ impl BaseInterface {
     }
 }
 
+/// Method to find the kernel interface for an interface config
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Default,
+    Serialize,
+    Deserialize,
+    JsonDisplay,
+)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum InterfaceIdentifier {
+    /// Match by interface name
+    #[default]
+    Name,
+    /// Match by `mac-address` property against permanent MAC address of
+    /// kernel interface, or MAC address if permanent one is unavailable.
+    MacAddress,
+    /// Match by `pci-path` property
+    PciPath,
+}
+
+impl InterfaceIdentifier {
+    pub fn is_name(&self) -> bool {
+        self == &Self::Name
+    }
+}
+
 fn is_zero(d: &u32) -> bool {
     *d == 0
 }
@@ -60,6 +60,7 @@ impl Default for LoopbackInterface {
                         prefix_length: 128,
                         ..Default::default()
                     }]),
+                    ..Default::default()
                 }),
                 ..Default::default()
             },
@@ -15,7 +15,7 @@ mod vlan;
 mod wifi;
 
 pub use self::{
-    base::BaseInterface,
+    base::{BaseInterface, InterfaceIdentifier},
     bond::{
         BondAdSelect, BondAllPortActive, BondArpAllTargets, BondArpValidate,
         BondConfig, BondFailOverMac, BondInterface, BondLacpRate, BondMode,
@@ -19,6 +19,7 @@ use crate::{ErrorKind, JsonDisplay, NipartError};
 
 const IPV4_ADDR_LEN: usize = 32;
 const IPV6_ADDR_LEN: usize = 128;
+const IPV6_TOKEN_LEN: usize = 64;
 const FOREVER: &str = "forever";
 
 #[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
@@ -108,6 +109,12 @@ pub struct InterfaceIpv4 {
     pub dhcp: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub dhcp_state: Option<DhcpState>,
+    /// IPv4 link-local address(169.254.0.0/16) defined in RFC 3927.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link_local: Option<Ipv4LinkLocalMode>,
+    /// State of IPv4 link-local address. Ignored during apply.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link_local_state: Option<DhcpState>,
     /// IPv4 addresses.
     /// When applying with `None`, current IP address will be preserved.
     /// When applying with `Some(Vec::new())`, all IP address will be removed.
@@ -119,6 +126,50 @@ pub struct InterfaceIpv4 {
     /// flag.
     #[serde(skip_serializing_if = "Option::is_none", rename = "address")]
     pub addresses: Option<Vec<InterfaceIpAddr>>,
+    /// Timeout in milliseconds of RFC 5227 address conflict detection
+    /// performed before adding new static IPv4 addresses. When other host is
+    /// using the address, the apply action will fail with
+    /// [crate::ErrorKind::VerificationError]. Undefined or 0 means no
+    /// conflict detection. Ignored in query.
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::deserializer::option_u32_or_string"
+    )]
+    pub dad_timeout: Option<u32>,
+    /// Whether IPv4 packets forwarding is enabled on this interface.
+    /// Stored in sysctl `net.ipv4.conf.<iface>.forwarding`.
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::deserializer::option_bool_or_string"
+    )]
+    pub forwarding: Option<bool>,
+    /// Reverse path filtering mode.
+    /// Stored in sysctl `net.ipv4.conf.<iface>.rp_filter`.
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::deserializer::option_enum_string_or_integer"
+    )]
+    pub rp_filter: Option<RpFilterMode>,
+    /// Reply mode for ARP requests, valid values are 0 to 3 and 8.
+    /// Stored in sysctl `net.ipv4.conf.<iface>.arp_ignore`.
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::deserializer::option_u8_or_string"
+    )]
+    pub arp_ignore: Option<u8>,
+    /// Restriction level for announcing local source IP address in ARP
+    /// requests, valid values are 0 to 2.
+    /// Stored in sysctl `net.ipv4.conf.<iface>.arp_announce`.
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::deserializer::option_u8_or_string"
+    )]
+    pub arp_announce: Option<u8>,
 }
 
 impl Default for InterfaceIpv4 {
@@ -128,7 +179,14 @@ impl Default for InterfaceIpv4 {
             enabled: Some(false),
             dhcp: None,
             dhcp_state: None,
+            link_local: None,
+            link_local_state: None,
             addresses: None,
+            dad_timeout: None,
+            forwarding: None,
+            rp_filter: None,
+            arp_ignore: None,
+            arp_announce: None,
         }
     }
 }
@@ -153,13 +211,30 @@ impl InterfaceIpv4 {
             && !self.addresses.as_deref().unwrap_or_default().is_empty()
     }
 
-    // * Remove DHCP state
+    pub fn is_link_local_enabled(&self) -> bool {
+        self.is_enabled() && self.link_local == Some(Ipv4LinkLocalMode::Enabled)
+    }
+
+    pub fn is_link_local_fallback(&self) -> bool {
+        self.is_auto() && self.link_local == Some(Ipv4LinkLocalMode::Fallback)
+    }
+
+    // * Remove DHCP and link-local state
     // * Disable DHCP and remove address if enabled: false
     pub(crate) fn sanitize(
         &mut self,
         _current: Option<&Self>,
     ) -> Result<(), NipartError> {
         self.dhcp_state = None;
+        self.link_local_state = None;
+        if self.link_local == Some(Ipv4LinkLocalMode::Fallback)
+            && self.dhcp == Some(false)
+        {
+            return Err(NipartError::new(
+                ErrorKind::InvalidArgument,
+                "IPv4 link-local fallback mode requires DHCP enabled".into(),
+            ));
+        }
         if self.is_auto() {
             if let Some(addrs) = self.addresses.as_ref() {
                 for addr in addrs.iter().filter(|a| !a.is_auto()) {
@@ -202,9 +277,34 @@ impl InterfaceIpv4 {
             }
         }
 
+        if let Some(arp_ignore) = self.arp_ignore {
+            if !matches!(arp_ignore, 0..=3 | 8) {
+                return Err(NipartError::new(
+                    ErrorKind::InvalidArgument,
+                    format!(
+                        "Invalid IPv4 arp-ignore value {arp_ignore}, should \
+                         be in the range of 0 to 3 or 8"
+                    ),
+                ));
+            }
+        }
+        if let Some(arp_announce) = self.arp_announce {
+            if arp_announce > 2 {
+                return Err(NipartError::new(
+                    ErrorKind::InvalidArgument,
+                    format!(
+                        "Invalid IPv4 arp-announce value {arp_announce}, \
+                         should be in the range of 0 to 2"
+                    ),
+                ));
+            }
+        }
+
         if !self.is_enabled() {
             self.dhcp = None;
+            self.link_local = None;
             self.addresses = None;
+            self.dad_timeout = None;
         }
         Ok(())
     }
@@ -213,7 +313,22 @@ impl InterfaceIpv4 {
     ///   be latency after applied and query back.
     /// * Set current DHCP none to false.
     /// * Set current address none to empty array.
+    /// * Remove link-local address from current if link-local enabled.
+    /// * Kernel has no link-local mode information, use desired mode if
+    ///   current is undefined.
+    /// * The `dad_timeout` is not stored in kernel, use desired value.
     pub(crate) fn sanitize_before_verify(&mut self, current: &mut Self) {
+        current.dad_timeout = self.dad_timeout;
+        if self.link_local.is_some() && current.link_local.is_none() {
+            current.link_local = self.link_local;
+        }
+        if matches!(
+            self.link_local,
+            Some(Ipv4LinkLocalMode::Enabled | Ipv4LinkLocalMode::Fallback)
+        ) && let Some(cur_addrs) = current.addresses.as_mut()
+        {
+            cur_addrs.retain(|a| !a.is_ipv4_link_local());
+        }
         if let Some(addrs) = self.addresses.as_mut() {
             for addr in addrs {
                 if let Some(cur_addr) =
@@ -295,6 +410,48 @@ pub struct InterfaceIpv6 {
     /// The IP addresses will apply to kernel with the same order specified.
     #[serde(skip_serializing_if = "Option::is_none", rename = "address")]
     pub addresses: Option<Vec<InterfaceIpAddr>>,
+    /// Whether IPv6 packets forwarding is enabled on this interface.
+    /// Stored in sysctl `net.ipv6.conf.<iface>.forwarding`.
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::deserializer::option_bool_or_string"
+    )]
+    pub forwarding: Option<bool>,
+    /// Whether to accept IPv6 router advertisements.
+    /// Stored in sysctl `net.ipv6.conf.<iface>.accept_ra`.
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::deserializer::option_enum_string_or_integer"
+    )]
+    pub accept_ra: Option<Ipv6AcceptRa>,
+    /// Privacy extensions(RFC 4941) for IPv6 autoconf addresses.
+    /// Stored in sysctl `net.ipv6.conf.<iface>.use_tempaddr`.
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::deserializer::option_enum_string_or_integer"
+    )]
+    pub use_tempaddr: Option<Ipv6UseTempaddr>,
+    /// Mode for generating IPv6 link-local and autoconf addresses.
+    /// Stored in sysctl `net.ipv6.conf.<iface>.addr_gen_mode`.
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::deserializer::option_enum_string_or_integer"
+    )]
+    pub addr_gen_mode: Option<Ipv6AddrGenMode>,
+    /// Secret used for generating stable privacy addresses defined in
+    /// RFC 7217, in the format of IPv6 address. Only used when
+    /// `addr_gen_mode` is set to [Ipv6AddrGenMode::StablePrivacy].
+    /// Stored in sysctl `net.ipv6.conf.<iface>.stable_secret`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stable_secret: Option<String>,
+    /// Interface identifier token used for IPv6 autoconf addresses, only the
+    /// lower 64 bits are allowed. Set to `::` to remove current token.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<Ipv6Addr>,
 }
 
 impl Default for InterfaceIpv6 {
@@ -305,6 +462,12 @@ impl Default for InterfaceIpv6 {
             dhcp: None,
             autoconf: None,
             addresses: None,
+            forwarding: None,
+            accept_ra: None,
+            use_tempaddr: None,
+            addr_gen_mode: None,
+            stable_secret: None,
+            token: None,
         }
     }
 }
@@ -369,7 +532,8 @@ impl InterfaceIpv6 {
             });
             addrs.iter_mut().for_each(|a| {
                 a.valid_life_time = None;
-                a.preferred_life_time = None
+                a.preferred_life_time = None;
+                a.temporary = None;
             });
         }
 
@@ -392,6 +556,30 @@ impl InterfaceIpv6 {
             })
         };
 
+        if let Some(secret) = self.stable_secret.as_deref()
+            && secret != crate::NetworkState::HIDE_PASSWORD_STR
+            && Ipv6Addr::from_str(secret).is_err()
+        {
+            return Err(NipartError::new(
+                ErrorKind::InvalidArgument,
+                "Invalid IPv6 stable-secret, should be in the format of IPv6 \
+                 address"
+                    .into(),
+            ));
+        }
+
+        if let Some(token) = self.token
+            && u128::from(token) >> IPV6_TOKEN_LEN != 0
+        {
+            return Err(NipartError::new(
+                ErrorKind::InvalidArgument,
+                format!(
+                    "Invalid IPv6 token {token}, only the lower \
+                     {IPV6_TOKEN_LEN} bits are allowed"
+                ),
+            ));
+        }
+
         if !self.is_enabled() {
             self.dhcp = None;
             self.autoconf = None;
@@ -401,11 +589,36 @@ impl InterfaceIpv6 {
         Ok(())
     }
 
+    pub(crate) fn hide_secrets(&mut self) {
+        if self.stable_secret.is_some() {
+            self.stable_secret =
+                Some(crate::NetworkState::HIDE_PASSWORD_STR.to_string());
+        }
+    }
+
     /// * Sync `valid_life_time` and `preferred_life_time` because there might
     ///   be latency after applied and query back.
     /// * Set current DHCP none to false.
     /// * Set current address none to empty array.
+    /// * Remove dynamic temporary addresses from current.
+    /// * Set current token none to `::`.
+    /// * Use current `stable_secret` if both sides are identical IPv6
+    ///   address, as kernel does not preserve the text format.
     pub(crate) fn sanitize_before_verify(&mut self, current: &mut Self) {
+        let parse_secret = |secret: Option<&str>| {
+            secret.and_then(|s| Ipv6Addr::from_str(s).ok())
+        };
+        if let Some(des_ip) = parse_secret(self.stable_secret.as_deref())
+            && parse_secret(current.stable_secret.as_deref()) == Some(des_ip)
+        {
+            self.stable_secret = current.stable_secret.clone();
+        }
+        if let Some(cur_addrs) = current.addresses.as_mut() {
+            cur_addrs.retain(|a| !(a.is_auto() && a.temporary == Some(true)));
+        }
+        if current.token.is_none() {
+            current.token = Some(Ipv6Addr::UNSPECIFIED);
+        }
         if let Some(addrs) = self.addresses.as_mut() {
             for addr in addrs {
                 if let Some(cur_addr) =
@@ -431,6 +644,232 @@ impl InterfaceIpv6 {
     }
 }
 
+#[derive(
+    Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, JsonDisplay,
+)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+/// IPv4 link-local address(169.254.0.0/16) mode
+pub enum Ipv4LinkLocalMode {
+    /// Always assign link-local address.
+    /// Serialize and deserialize to/from `enabled`.
+    Enabled,
+    /// Assign link-local address only when DHCPv4 failed.
+    /// Serialize and deserialize to/from `fallback`.
+    Fallback,
+    /// Do not assign link-local address.
+    /// Serialize and deserialize to/from `disabled`.
+    Disabled,
+}
+
+#[derive(
+    Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, JsonDisplay,
+)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+/// Reverse path filtering mode defined in RFC 3704
+pub enum RpFilterMode {
+    /// No source validation.
+    /// Serialize to `disabled`.
+    /// Deserialize from 0 or `disabled`.
+    #[serde(alias = "0")]
+    Disabled,
+    /// Drop packet if the reverse path is not the best path.
+    /// Serialize to `strict`.
+    /// Deserialize from 1 or `strict`.
+    #[serde(alias = "1")]
+    Strict,
+    /// Drop packet if the source address is not reachable via any interface.
+    /// Serialize to `loose`.
+    /// Deserialize from 2 or `loose`.
+    #[serde(alias = "2")]
+    Loose,
+}
+
+impl From<RpFilterMode> for u8 {
+    fn from(v: RpFilterMode) -> Self {
+        match v {
+            RpFilterMode::Disabled => 0,
+            RpFilterMode::Strict => 1,
+            RpFilterMode::Loose => 2,
+        }
+    }
+}
+
+impl TryFrom<i64> for RpFilterMode {
+    type Error = NipartError;
+
+    fn try_from(v: i64) -> Result<Self, NipartError> {
+        match v {
+            0 => Ok(Self::Disabled),
+            1 => Ok(Self::Strict),
+            2 => Ok(Self::Loose),
+            _ => Err(NipartError::new(
+                ErrorKind::InvalidArgument,
+                format!("Invalid rp_filter value {v}"),
+            )),
+        }
+    }
+}
+
+#[derive(
+    Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, JsonDisplay,
+)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+/// Whether to accept IPv6 router advertisements
+pub enum Ipv6AcceptRa {
+    /// Do not accept router advertisements.
+    /// Serialize to `disabled`.
+    /// Deserialize from 0 or `disabled`.
+    #[serde(alias = "0")]
+    Disabled,
+    /// Accept router advertisements if forwarding is disabled.
+    /// Serialize to `enabled`.
+    /// Deserialize from 1 or `enabled`.
+    #[serde(alias = "1")]
+    Enabled,
+    /// Accept router advertisements even if forwarding is enabled.
+    /// Serialize to `always`.
+    /// Deserialize from 2 or `always`.
+    #[serde(alias = "2")]
+    Always,
+}
+
+impl From<Ipv6AcceptRa> for u8 {
+    fn from(v: Ipv6AcceptRa) -> Self {
+        match v {
+            Ipv6AcceptRa::Disabled => 0,
+            Ipv6AcceptRa::Enabled => 1,
+            Ipv6AcceptRa::Always => 2,
+        }
+    }
+}
+
+impl TryFrom<i64> for Ipv6AcceptRa {
+    type Error = NipartError;
+
+    fn try_from(v: i64) -> Result<Self, NipartError> {
+        match v {
+            0 => Ok(Self::Disabled),
+            1 => Ok(Self::Enabled),
+            2 => Ok(Self::Always),
+            _ => Err(NipartError::new(
+                ErrorKind::InvalidArgument,
+                format!("Invalid accept_ra value {v}"),
+            )),
+        }
+    }
+}
+
+#[derive(
+    Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, JsonDisplay,
+)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+/// IPv6 privacy extensions defined in RFC 4941
+pub enum Ipv6UseTempaddr {
+    /// Do not generate temporary addresses.
+    /// Serialize to `disabled`.
+    /// Deserialize from 0 or `disabled`.
+    #[serde(alias = "0")]
+    Disabled,
+    /// Generate temporary addresses but prefer public addresses.
+    /// Serialize to `prefer-public`.
+    /// Deserialize from 1 or `prefer-public`.
+    #[serde(alias = "1")]
+    PreferPublic,
+    /// Generate temporary addresses and prefer them over public addresses.
+    /// Serialize to `prefer-temporary`.
+    /// Deserialize from 2 or `prefer-temporary`.
+    #[serde(alias = "2")]
+    PreferTemporary,
+}
+
+impl From<Ipv6UseTempaddr> for u8 {
+    fn from(v: Ipv6UseTempaddr) -> Self {
+        match v {
+            Ipv6UseTempaddr::Disabled => 0,
+            Ipv6UseTempaddr::PreferPublic => 1,
+            Ipv6UseTempaddr::PreferTemporary => 2,
+        }
+    }
+}
+
+impl TryFrom<i64> for Ipv6UseTempaddr {
+    type Error = NipartError;
+
+    fn try_from(v: i64) -> Result<Self, NipartError> {
+        match v {
+            // Kernel use -1 for point-to-point and loopback devices
+            -1 | 0 => Ok(Self::Disabled),
+            1 => Ok(Self::PreferPublic),
+            2 => Ok(Self::PreferTemporary),
+            _ => Err(NipartError::new(
+                ErrorKind::InvalidArgument,
+                format!("Invalid use_tempaddr value {v}"),
+            )),
+        }
+    }
+}
+
+#[derive(
+    Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, JsonDisplay,
+)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+/// Mode for generating IPv6 interface identifier
+pub enum Ipv6AddrGenMode {
+    /// Use EUI-64 derived from MAC address.
+    /// Serialize to `eui64`.
+    /// Deserialize from 0 or `eui64`.
+    #[serde(alias = "0")]
+    Eui64,
+    /// Do not generate link-local address.
+    /// Serialize to `none`.
+    /// Deserialize from 1 or `none`.
+    #[serde(alias = "1")]
+    None,
+    /// Use stable privacy addresses defined in RFC 7217.
+    /// Serialize to `stable-privacy`.
+    /// Deserialize from 2 or `stable-privacy`.
+    #[serde(alias = "2")]
+    StablePrivacy,
+    /// Use random interface identifier.
+    /// Serialize to `random`.
+    /// Deserialize from 3 or `random`.
+    #[serde(alias = "3")]
+    Random,
+}
+
+impl From<Ipv6AddrGenMode> for u8 {
+    fn from(v: Ipv6AddrGenMode) -> Self {
+        match v {
+            Ipv6AddrGenMode::Eui64 => 0,
+            Ipv6AddrGenMode::None => 1,
+            Ipv6AddrGenMode::StablePrivacy => 2,
+            Ipv6AddrGenMode::Random => 3,
+        }
+    }
+}
+
+impl TryFrom<i64> for Ipv6AddrGenMode {
+    type Error = NipartError;
+
+    fn try_from(v: i64) -> Result<Self, NipartError> {
+        match v {
+            0 => Ok(Self::Eui64),
+            1 => Ok(Self::None),
+            2 => Ok(Self::StablePrivacy),
+            3 => Ok(Self::Random),
+            _ => Err(NipartError::new(
+                ErrorKind::InvalidArgument,
+                format!("Invalid addr_gen_mode value {v}"),
+            )),
+        }
+    }
+}
+
 /// IP Address
 ///
 /// When `valid_life_time` or `preferred_life_time` not equal to `None` or
@@ -479,6 +918,11 @@ pub struct InterfaceIpAddr {
         alias = "preferred-lft"
     )]
     pub preferred_life_time: Option<String>,
+    /// Whether this is a IPv6 temporary address generated by privacy
+    /// extensions defined in RFC 4941.
+    /// Ignored during apply.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temporary: Option<bool>,
 }
 
 impl Default for InterfaceIpAddr {
@@ -488,6 +932,7 @@ impl Default for InterfaceIpAddr {
             prefix_length: 128,
             valid_life_time: None,
             preferred_life_time: None,
+            temporary: None,
         }
     }
 }
@@ -503,6 +948,14 @@ impl InterfaceIpAddr {
 }
 
 impl InterfaceIpAddr {
+    pub(crate) fn is_ipv4_link_local(&self) -> bool {
+        if let IpAddr::V4(ip) = self.ip {
+            ip.is_link_local()
+        } else {
+            false
+        }
+    }
+
     pub(crate) fn is_auto(&self) -> bool {
         self.valid_life_time.is_some()
             && self.valid_life_time.as_deref() != Some(FOREVER)
@@ -540,6 +993,7 @@ impl std::convert::TryFrom<&str> for InterfaceIpAddr {
             prefix_length,
             valid_life_time: None,
             preferred_life_time: None,
+            temporary: None,
         })
     }
 }
@@ -0,0 +1,89 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use serde::{Deserialize, Serialize};
+
+use crate::{ErrorKind, JsonDisplay, NipartError};
+
+/// Global IP forwarding switch.
+/// Example YAML of enabling IPv4 and IPv6 forwarding:
+/// ```yaml
+/// ---
+/// ip-forwarding:
+///   ipv4: true
+///   ipv6: true
+/// ```
+#[derive(
+    Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize, JsonDisplay,
+)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+#[non_exhaustive]
+pub struct IpForwarding {
+    /// Whether IPv4 forwarding is enabled globally.
+    /// Stored in sysctl `net.ipv4.ip_forward`.
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::deserializer::option_bool_or_string"
+    )]
+    pub ipv4: Option<bool>,
+    /// Whether IPv6 forwarding is enabled globally.
+    /// Stored in sysctl `net.ipv6.conf.all.forwarding`.
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::deserializer::option_bool_or_string"
+    )]
+    pub ipv6: Option<bool>,
+}
+
+impl IpForwarding {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ipv4.is_none() && self.ipv6.is_none()
+    }
+
+    pub(crate) fn merge(&self, new: &Self) -> Self {
+        Self {
+            ipv4: new.ipv4.or(self.ipv4),
+            ipv6: new.ipv6.or(self.ipv6),
+        }
+    }
+
+    /// Only include properties defined in `self` with value from `current`.
+    pub(crate) fn gen_current(&self, current: Option<&Self>) -> Self {
+        Self {
+            ipv4: self.ipv4.and(current.and_then(|c| c.ipv4)),
+            ipv6: self.ipv6.and(current.and_then(|c| c.ipv6)),
+        }
+    }
+
+    /// Only include properties changed comparing to `old`.
+    pub(crate) fn gen_diff(&self, old: Option<&Self>) -> Self {
+        let old = old.cloned().unwrap_or_default();
+        Self {
+            ipv4: self.ipv4.filter(|v| old.ipv4 != Some(*v)),
+            ipv6: self.ipv6.filter(|v| old.ipv6 != Some(*v)),
+        }
+    }
+
+    pub(crate) fn verify(
+        &self,
+        current: Option<&Self>,
+    ) -> Result<(), NipartError> {
+        let cur = self.gen_current(current);
+        if self != &cur {
+            Err(NipartError::new(
+                ErrorKind::VerificationError,
+                format!(
+                    "Verification failure: ip-forwarding desire {self}, but \
+                     current is {cur}"
+                ),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
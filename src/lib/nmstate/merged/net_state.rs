@@ -3,8 +3,8 @@
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    JsonDisplayHideSecrets, MergedInterfaces, MergedRoutes, NetworkState,
-    NipartError, NipartstateApplyOption,
+    IpForwarding, JsonDisplayHideSecrets, MergedInterfaces, MergedRoutes,
+    NetworkState, NipartError, NipartstateApplyOption, PluginStates,
 };
 
 #[derive(
@@ -20,6 +20,10 @@ use crate::{
 pub struct MergedNetworkState {
     pub version: Option<u32>,
     pub description: Option<String>,
+    /// Desired global IP forwarding switch
+    pub ip_forwarding: Option<IpForwarding>,
+    /// Desired states owned by plugins
+    pub plugins: PluginStates,
     pub ifaces: MergedInterfaces,
     pub routes: MergedRoutes,
     pub option: NipartstateApplyOption,
@@ -39,6 +43,8 @@ impl MergedNetworkState {
         Ok(Self {
             version: desired.version,
             description: desired.description.clone(),
+            ip_forwarding: desired.ip_forwarding.filter(|i| !i.is_empty()),
+            plugins: desired.plugins,
             ifaces: merged_ifaces,
             routes: merged_routes,
             option,
@@ -46,6 +52,10 @@ impl MergedNetworkState {
     }
 
     pub fn verify(&self, current: &NetworkState) -> Result<(), NipartError> {
+        if let Some(ip_forwarding) = self.ip_forwarding.as_ref() {
+            ip_forwarding.verify(current.ip_forwarding.as_ref())?;
+        }
+        self.plugins.verify(&current.plugins)?;
         self.ifaces.verify(&current.ifaces)
     }
 
@@ -55,11 +65,14 @@ impl MergedNetworkState {
             routes: self.routes.gen_state_for_apply(),
             version: self.version,
             description: self.description.clone(),
+            ip_forwarding: self.ip_forwarding.clone(),
+            plugins: self.plugins.clone(),
         }
     }
 
     pub fn hide_secrets(&mut self) {
-        self.ifaces.hide_secrets()
+        self.ifaces.hide_secrets();
+        self.plugins.hide_secrets();
     }
 }
 
@@ -71,6 +84,14 @@ impl NetworkState {
                 .description
                 .clone()
                 .or_else(|| self.description.clone()),
+            ip_forwarding: match (
+                self.ip_forwarding.as_ref(),
+                new_state.ip_forwarding.as_ref(),
+            ) {
+                (Some(old), Some(new)) => Some(old.merge(new)),
+                (old, new) => new.or(old).cloned(),
+            },
+            plugins: self.plugins.merge(&new_state.plugins),
             ifaces: self.ifaces.merge(&new_state.ifaces)?,
             routes: self.routes.merge(&new_state.routes)?,
         };
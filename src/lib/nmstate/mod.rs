@@ -1,14 +1,20 @@
 // SPDX-License-Identifier: Apache-2.0
 
+mod drift;
+mod ethtool;
 mod gen_diff;
 mod iface;
 mod iface_state;
+mod iface_identifier;
 mod iface_trait;
 mod iface_type;
 mod ifaces;
 mod ip;
+mod ip_forwarding;
 mod merged;
 mod net_state;
+mod plan;
+mod plugin_states;
 mod revert;
 mod route;
 mod state_options;
@@ -21,6 +27,12 @@ pub(crate) mod deserializer;
 pub(crate) mod serializer;
 
 pub use self::{
+    drift::{NipartDriftIface, NipartDriftReport},
+    ethtool::{
+        EthtoolChannelsConfig, EthtoolCoalesceConfig, EthtoolConfig,
+        EthtoolFecConfig, EthtoolFecMode, EthtoolPauseConfig,
+        EthtoolRingConfig,
+    },
     iface::Interface,
     iface_state::InterfaceState,
     iface_trait::NipartstateInterface,
@@ -31,8 +43,8 @@ pub use self::{
         BondLacpRate, BondMode, BondOptions, BondPortConfig,
         BondPrimaryReselect, BondXmitHashPolicy, BridgeVlanConfig,
         BridgeVlanMode, BridgeVlanRange, BridgeVlanTrunkTag, DummyInterface,
-        EthernetConfig, EthernetDuplex, EthernetInterface, Interfaces,
-        LinuxBridgeConfig, LinuxBridgeInterface,
+        EthernetConfig, EthernetDuplex, EthernetInterface, InterfaceIdentifier,
+        Interfaces, LinuxBridgeConfig, LinuxBridgeInterface,
         LinuxBridgeMulticastRouterType, LinuxBridgeOptions,
         LinuxBridgePortConfig, LinuxBridgeStpOptions, LoopbackInterface,
         OvsBridgeConfig, OvsBridgeInterface, OvsBridgePortConfig, OvsInterface,
@@ -40,11 +52,21 @@ pub use self::{
         VlanQosMapping, VlanRegistrationProtocol, WifiAuthType,
         WifiCfgInterface, WifiConfig, WifiPhyInterface, WifiState,
     },
-    ip::{DhcpState, InterfaceIpAddr, InterfaceIpv4, InterfaceIpv6},
+    ip::{
+        DhcpState, InterfaceIpAddr, InterfaceIpv4, InterfaceIpv6,
+        Ipv4LinkLocalMode, Ipv6AcceptRa, Ipv6AddrGenMode, Ipv6UseTempaddr,
+        RpFilterMode,
+    },
+    ip_forwarding::IpForwarding,
     merged::{
         MergedInterface, MergedInterfaces, MergedNetworkState, MergedRoutes,
     },
     net_state::NetworkState,
+    plan::{
+        NipartstateApplyPlan, NipartstatePlanAction, NipartstatePlanIface,
+        NipartstatePlanOvsDbOp, NipartstatePlanWpaNetwork,
+    },
+    plugin_states::PluginStates,
     route::{RouteEntry, RouteState, RouteType, Routes},
     state_options::{NipartstateApplyOption, NipartstateQueryOption, NipartstateStateKind},
     version::CUR_SCHEMA_VERSION,
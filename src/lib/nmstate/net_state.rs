@@ -3,8 +3,8 @@
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    CUR_SCHEMA_VERSION, ErrorKind, Interfaces, JsonDisplayHideSecrets, NipartError,
-    Routes,
+    CUR_SCHEMA_VERSION, ErrorKind, Interfaces, IpForwarding,
+    JsonDisplayHideSecrets, NipartError, PluginStates, Routes,
 };
 
 #[derive(
@@ -19,12 +19,22 @@ pub struct NetworkState {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     /// Description for the whole desire state.
     pub description: Option<String>,
+    /// Global IP forwarding switch
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "ip-forwarding"
+    )]
+    pub ip_forwarding: Option<IpForwarding>,
     /// Routes
     #[serde(default)]
     pub routes: Routes,
     /// Network interfaces
     #[serde(default, rename = "interfaces")]
     pub ifaces: Interfaces,
+    /// Opaque states owned by plugins, indexed by plugin name
+    #[serde(default, skip_serializing_if = "PluginStates::is_empty")]
+    pub plugins: PluginStates,
 }
 
 impl Default for NetworkState {
@@ -32,8 +42,10 @@ impl Default for NetworkState {
         Self {
             version: Some(CUR_SCHEMA_VERSION),
             description: None,
+            ip_forwarding: None,
             ifaces: Default::default(),
             routes: Default::default(),
+            plugins: Default::default(),
         }
     }
 }
@@ -48,6 +60,7 @@ impl NetworkState {
     pub fn hide_secrets(&mut self) -> Self {
         let old = self.clone();
         self.ifaces.hide_secrets();
+        self.plugins.hide_secrets();
         old.gen_diff_no_sanitize(self.clone()).unwrap_or_default()
     }
 
@@ -55,7 +68,10 @@ impl NetworkState {
         self == &Self {
             version: self.version,
             ..Default::default()
-        } || (self.ifaces.is_empty() && self.routes.is_empty())
+        } || (self.ifaces.is_empty()
+            && self.routes.is_empty()
+            && self.ip_forwarding.as_ref().is_none_or(|i| i.is_empty())
+            && self.plugins.is_empty())
     }
 
     pub fn new() -> Self {
@@ -0,0 +1,253 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Interface, InterfaceType, IpForwarding, JsonDisplay, MergedInterface,
+    MergedNetworkState, NipartError, NipartstateInterface, PluginStates,
+    RouteEntry,
+};
+
+/// Operations planned for applying network state without touching anything,
+/// generated by apply with [crate::NipartstateApplyOption::dry_run].
+#[derive(
+    Debug, Clone, PartialEq, Default, Serialize, Deserialize, JsonDisplay,
+)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub struct NipartstateApplyPlan {
+    /// Interfaces to create, delete or modify in the order of applying
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub interfaces: Vec<NipartstatePlanIface>,
+    /// Routes to add to kernel
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub routes_to_add: Vec<RouteEntry>,
+    /// Routes to remove from kernel
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub routes_to_remove: Vec<RouteEntry>,
+    /// Global IP forwarding switch to set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ip_forwarding: Option<IpForwarding>,
+    /// Networks to add or remove in wpa_supplicant
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub wpa_supplicant: Vec<NipartstatePlanWpaNetwork>,
+    /// Rows to insert, delete or update in OVS database
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ovsdb: Vec<NipartstatePlanOvsDbOp>,
+    /// Opaque states to send to plugins
+    #[serde(default, skip_serializing_if = "PluginStates::is_empty")]
+    pub plugins: PluginStates,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum NipartstatePlanAction {
+    Create,
+    Delete,
+    Modify,
+    /// Delete and create again because changed property cannot be modified
+    /// in place.
+    Recreate,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub struct NipartstatePlanIface {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub iface_type: InterfaceType,
+    pub action: NipartstatePlanAction,
+    pub up_priority: u32,
+    /// Properties changed with secrets hidden, not set for deletion.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub changes: Option<Interface>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub struct NipartstatePlanWpaNetwork {
+    /// [NipartstatePlanAction::Delete] without SSID means removing the
+    /// interface from wpa_supplicant.
+    pub action: NipartstatePlanAction,
+    /// WiFi interface name, not set when removing SSID from all interfaces.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interface: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ssid: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub struct NipartstatePlanOvsDbOp {
+    pub action: NipartstatePlanAction,
+    /// OVS database table, e.g. `Bridge` or `Interface`
+    pub table: String,
+    pub name: String,
+}
+
+impl NipartstateApplyPlan {
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+impl MergedNetworkState {
+    /// Generate operations planned for applying this merged state.
+    pub fn gen_plan(&self) -> Result<NipartstateApplyPlan, NipartError> {
+        let diff_ifaces = self.ifaces.gen_diff()?;
+
+        let mut changed_ifaces: Vec<(&MergedInterface, &Interface)> = self
+            .ifaces
+            .kernel_ifaces
+            .values()
+            .chain(self.ifaces.user_ifaces.values())
+            .filter_map(|m| m.for_apply.as_ref().map(|i| (m, i)))
+            .collect();
+        changed_ifaces.sort_unstable_by(|(_, a), (_, b)| {
+            (a.base_iface().up_priority, a.name())
+                .cmp(&(b.base_iface().up_priority, b.name()))
+        });
+
+        let mut ret = NipartstateApplyPlan::default();
+        for (merged_iface, apply_iface) in changed_ifaces {
+            let diff_iface = diff_ifaces
+                .get(apply_iface.name(), Some(apply_iface.iface_type()));
+            let action = match merged_iface.current.as_ref() {
+                None if apply_iface.is_absent() => continue,
+                None => NipartstatePlanAction::Create,
+                Some(_)
+                    if apply_iface.is_absent() && apply_iface.is_virtual() =>
+                {
+                    NipartstatePlanAction::Delete
+                }
+                Some(cur_iface)
+                    if apply_iface.is_up()
+                        && apply_iface.need_delete_before_change(cur_iface) =>
+                {
+                    NipartstatePlanAction::Recreate
+                }
+                Some(_) if diff_iface.is_some() => {
+                    NipartstatePlanAction::Modify
+                }
+                Some(_) => continue,
+            };
+            let changes = if action == NipartstatePlanAction::Delete {
+                None
+            } else {
+                diff_iface.cloned().map(|mut iface| {
+                    iface.hide_secrets();
+                    iface
+                })
+            };
+            if let Some(table) = ovsdb_table(apply_iface.iface_type()) {
+                ret.ovsdb.push(NipartstatePlanOvsDbOp {
+                    action: match action {
+                        NipartstatePlanAction::Create
+                        | NipartstatePlanAction::Delete => action,
+                        _ => NipartstatePlanAction::Modify,
+                    },
+                    table: table.to_string(),
+                    name: apply_iface.name().to_string(),
+                });
+            }
+            ret.wpa_supplicant.extend(gen_wpa_plan(apply_iface, self));
+            ret.interfaces.push(NipartstatePlanIface {
+                name: apply_iface.name().to_string(),
+                iface_type: apply_iface.iface_type().clone(),
+                action,
+                up_priority: apply_iface.base_iface().up_priority,
+                changes,
+            });
+        }
+
+        for route in self.routes.changed_routes.iter() {
+            if route.is_absent() {
+                ret.routes_to_remove.push(route.clone());
+            } else {
+                ret.routes_to_add.push(route.clone());
+            }
+        }
+        ret.ip_forwarding.clone_from(&self.ip_forwarding);
+        ret.plugins = self.plugins.clone();
+        ret.plugins.hide_secrets();
+
+        Ok(ret)
+    }
+}
+
+fn ovsdb_table(iface_type: &InterfaceType) -> Option<&'static str> {
+    match iface_type {
+        InterfaceType::OvsBridge => Some("Bridge"),
+        InterfaceType::OvsInterface => Some("Interface"),
+        _ => None,
+    }
+}
+
+// Should be consistent with `NipartWpaConn::apply()`
+fn gen_wpa_plan(
+    apply_iface: &Interface,
+    merged_state: &MergedNetworkState,
+) -> Vec<NipartstatePlanWpaNetwork> {
+    let wifi_cfg = match apply_iface {
+        Interface::WifiCfg(iface) => iface.wifi.as_ref(),
+        Interface::WifiPhy(iface) => iface.wifi.as_ref(),
+        _ => return Vec::new(),
+    };
+    if apply_iface.is_absent() || apply_iface.is_down() {
+        if apply_iface.iface_type() == &InterfaceType::WifiPhy {
+            vec![NipartstatePlanWpaNetwork {
+                action: NipartstatePlanAction::Delete,
+                interface: Some(apply_iface.name().to_string()),
+                ssid: None,
+            }]
+        } else {
+            vec![NipartstatePlanWpaNetwork {
+                action: NipartstatePlanAction::Delete,
+                interface: None,
+                ssid: Some(
+                    wifi_cfg
+                        .map(|w| w.ssid.as_str())
+                        .unwrap_or(apply_iface.name())
+                        .to_string(),
+                ),
+            }]
+        }
+    } else if let Some(wifi_cfg) = wifi_cfg
+        && apply_iface.is_up()
+    {
+        let iface_names: Vec<&str> = if apply_iface.iface_type()
+            == &InterfaceType::WifiPhy
+        {
+            vec![apply_iface.name()]
+        } else if let Some(iface_name) = wifi_cfg.base_iface.as_deref() {
+            vec![iface_name]
+        } else {
+            // Bind to any WIFI NICs
+            merged_state
+                .ifaces
+                .kernel_ifaces
+                .values()
+                .filter(|i| {
+                    i.for_apply.as_ref().map(|i| {
+                        i.iface_type() == &InterfaceType::WifiPhy && i.is_up()
+                    }) == Some(true)
+                })
+                .map(|i| i.merged.name())
+                .collect()
+        };
+        iface_names
+            .into_iter()
+            .map(|iface_name| NipartstatePlanWpaNetwork {
+                action: NipartstatePlanAction::Create,
+                interface: Some(iface_name.to_string()),
+                ssid: Some(wifi_cfg.ssid.clone()),
+            })
+            .collect()
+    } else {
+        Vec::new()
+    }
+}
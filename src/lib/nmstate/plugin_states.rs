@@ -0,0 +1,193 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::{
+    revert::{SECRET_KEYS, gen_revert_state},
+    value::{
+        copy_undefined_value, gen_diff_json_value, get_json_value_difference,
+    },
+};
+use crate::{ErrorKind, NipartError};
+
+/// Opaque states owned by plugins, indexed by plugin name. The daemon only
+/// routes each value to the plugin of the same name without parsing it.
+/// Setting value to `null` requests the plugin to remove its state.
+/// Example YAML of state owned by plugin `demo`:
+/// ```yaml
+/// ---
+/// plugins:
+///   demo:
+///     tunnel: wg0
+///     peers:
+///     - 192.0.2.1
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+#[non_exhaustive]
+pub struct PluginStates(BTreeMap<String, Value>);
+
+impl PluginStates {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn get(&self, plugin_name: &str) -> Option<&Value> {
+        self.0.get(plugin_name)
+    }
+
+    pub fn insert(&mut self, plugin_name: String, value: Value) {
+        self.0.insert(plugin_name, value);
+    }
+
+    pub fn remove(&mut self, plugin_name: &str) -> Option<Value> {
+        self.0.remove(plugin_name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Value)> {
+        self.0.iter()
+    }
+
+    /// Value of `new` overrides `self` while properties only defined in
+    /// `self` are preserved.
+    pub(crate) fn merge(&self, new: &Self) -> Self {
+        let mut ret = self.clone();
+        for (name, new_value) in new.iter() {
+            let mut value = new_value.clone();
+            if let Some(old_value) = self.0.get(name) {
+                copy_undefined_value(&mut value, old_value);
+            }
+            ret.0.insert(name.to_string(), value);
+        }
+        ret
+    }
+
+    /// Only include properties defined in `self` with value from `current`.
+    pub(crate) fn gen_current(&self, current: &Self) -> Self {
+        let mut ret = Self::new();
+        for (name, des_value) in self.iter() {
+            let value = match current.0.get(name) {
+                Some(cur_value) if des_value.is_object() => {
+                    let mut rev_value = Value::Object(Map::new());
+                    gen_revert_state(des_value, cur_value, &mut rev_value);
+                    rev_value
+                }
+                Some(cur_value) => cur_value.clone(),
+                None => Value::Null,
+            };
+            ret.0.insert(name.to_string(), value);
+        }
+        ret
+    }
+
+    /// Only include properties changed comparing to `old`.
+    pub(crate) fn gen_diff(&self, old: &Self) -> Self {
+        let mut ret = Self::new();
+        for (name, des_value) in self.iter() {
+            let diff = match old.0.get(name) {
+                Some(old_value) => gen_diff_plugin_value(des_value, old_value),
+                None => Some(des_value.clone()),
+            };
+            if let Some(diff) = diff {
+                ret.0.insert(name.to_string(), diff);
+            }
+        }
+        ret
+    }
+
+    pub(crate) fn verify(&self, current: &Self) -> Result<(), NipartError> {
+        for (name, des_value) in self.iter().filter(|(_, v)| !v.is_null()) {
+            let cur_value = current.0.get(name).unwrap_or(&Value::Null);
+            if let Some((reference, desire, current)) =
+                get_json_value_difference(
+                    format!("plugins.{name}"),
+                    des_value,
+                    cur_value,
+                )
+            {
+                return Err(NipartError::new(
+                    ErrorKind::VerificationError,
+                    format!(
+                        "Verification failure: {reference} desire \
+                         '{desire}', current '{current}'"
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Replace value of well-known secret keys with
+    /// [crate::NetworkState::HIDE_PASSWORD_STR].
+    pub(crate) fn hide_secrets(&mut self) {
+        for value in self.0.values_mut() {
+            hide_secrets_in_value(value);
+        }
+    }
+}
+
+// Plugin cannot apply partial array, hence any changed element means the full
+// desired array.
+fn gen_diff_plugin_value(desired: &Value, old: &Value) -> Option<Value> {
+    match (desired, old) {
+        (Value::Object(des_obj), Value::Object(old_obj)) => {
+            let mut diff_map = Map::new();
+            for (des_key, des_value) in des_obj.iter() {
+                let diff = match old_obj.get(des_key) {
+                    Some(old_value) => {
+                        gen_diff_plugin_value(des_value, old_value)
+                    }
+                    None => Some(des_value.clone()),
+                };
+                if let Some(diff) = diff {
+                    diff_map.insert(des_key.clone(), diff);
+                }
+            }
+            if diff_map.is_empty() {
+                None
+            } else {
+                Some(Value::Object(diff_map))
+            }
+        }
+        (Value::Array(des), Value::Array(old))
+            if des.len() == old.len()
+                && des
+                    .iter()
+                    .zip(old.iter())
+                    .all(|(d, o)| gen_diff_json_value(d, o).is_none()) =>
+        {
+            None
+        }
+        (Value::Array(_), _) => Some(desired.clone()),
+        _ => gen_diff_json_value(desired, old),
+    }
+}
+
+fn hide_secrets_in_value(value: &mut Value) {
+    match value {
+        Value::Object(obj) => {
+            for (key, sub_value) in obj.iter_mut() {
+                if SECRET_KEYS.contains(&key.as_str()) && sub_value.is_string()
+                {
+                    *sub_value =
+                        Value::from(crate::NetworkState::HIDE_PASSWORD_STR);
+                } else {
+                    hide_secrets_in_value(sub_value);
+                }
+            }
+        }
+        Value::Array(array) => {
+            for sub_value in array.iter_mut() {
+                hide_secrets_in_value(sub_value);
+            }
+        }
+        _ => (),
+    }
+}
@@ -5,3 +5,5 @@ mod iface;
 mod inter_ifaces;
 mod net_state;
 mod value;
+
+pub(crate) use self::value::{SECRET_KEYS, gen_revert_state};
@@ -1,6 +1,11 @@
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{MergedNetworkState, NetworkState, NipartError};
+use serde_json::Value;
+
+use crate::{
+    InterfaceState, MergedNetworkState, NetworkState, NipartError,
+    NipartstateInterface, RouteState,
+};
 
 impl NetworkState {
     /// Generate revert state of desired(&self) state
@@ -16,8 +21,51 @@ impl NetworkState {
             Default::default(),
         )?;
         Ok(Self {
+            ip_forwarding: merged_state.ip_forwarding.as_ref().map(
+                |ip_forwarding| {
+                    ip_forwarding
+                        .gen_current(pre_apply_state.ip_forwarding.as_ref())
+                },
+            ),
+            plugins: merged_state.plugins.gen_current(&pre_apply_state.plugins),
             ifaces: merged_state.ifaces.generate_revert()?,
             ..Default::default()
         })
     }
+
+    /// Generate desired state restoring saved state `saved` back to
+    /// self(previously saved state). Interfaces, routes and plugin states
+    /// only found in `saved` are marked as absent. Properties only defined
+    /// in `saved` are still preserved by merging.
+    pub fn gen_restore_state(&self, saved: &Self) -> Self {
+        let mut ret = self.clone();
+        for saved_iface in saved.ifaces.iter() {
+            if self
+                .ifaces
+                .get(saved_iface.name(), Some(saved_iface.iface_type()))
+                .is_none()
+            {
+                let mut absent_iface = saved_iface.clone_name_type_only();
+                absent_iface.base_iface_mut().state = InterfaceState::Absent;
+                ret.ifaces.push(absent_iface);
+            }
+        }
+        if let Some(saved_routes) = saved.routes.config.as_ref() {
+            let old_routes = self.routes.config.as_deref().unwrap_or_default();
+            let routes = ret.routes.config.get_or_insert_with(Vec::new);
+            for saved_route in saved_routes {
+                if !old_routes.contains(saved_route) {
+                    let mut absent_route = saved_route.clone();
+                    absent_route.state = Some(RouteState::Absent);
+                    routes.push(absent_route);
+                }
+            }
+        }
+        for (plugin_name, _) in saved.plugins.iter() {
+            if self.plugins.get(plugin_name).is_none() {
+                ret.plugins.insert(plugin_name.to_string(), Value::Null);
+            }
+        }
+        ret
+    }
 }
@@ -2,7 +2,7 @@
 
 use serde_json::{Map, Value};
 
-const SECRET_KEYS: [&str; 1] = ["password"];
+pub(crate) const SECRET_KEYS: [&str; 2] = ["password", "stable-secret"];
 
 pub(crate) fn gen_revert_state(
     desired: &Value,
@@ -0,0 +1,135 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{InterfaceType, NetworkState, NipartstateInterface};
+
+#[test]
+fn test_gen_drift() {
+    let saved: NetworkState = serde_yaml::from_str(
+        r#"
+        interfaces:
+        - name: eth1
+          type: ethernet
+          state: up
+          ipv4:
+            enabled: true
+            address:
+            - ip: 192.0.2.1
+              prefix-length: 24
+        - name: dummy1
+          type: dummy
+          state: up
+        routes:
+          config:
+          - destination: 198.51.100.0/24
+            next-hop-interface: eth1
+            next-hop-address: 192.0.2.254
+        "#,
+    )
+    .unwrap();
+    let current: NetworkState = serde_yaml::from_str(
+        r#"
+        interfaces:
+        - name: eth1
+          type: ethernet
+          state: up
+          ipv4:
+            enabled: true
+            address:
+            - ip: 192.0.2.1
+              prefix-length: 24
+            - ip: 192.0.2.2
+              prefix-length: 24
+        - name: eth2
+          type: ethernet
+          state: up
+        routes:
+          config:
+          - destination: 198.51.100.0/24
+            next-hop-interface: eth1
+            next-hop-address: 192.0.2.254
+          - destination: 203.0.113.0/24
+            next-hop-interface: eth2
+        "#,
+    )
+    .unwrap();
+
+    let drift = saved.gen_drift(&current).unwrap();
+
+    assert_eq!(drift.missing.len(), 1);
+    assert_eq!(drift.missing[0].name, "dummy1");
+    assert_eq!(drift.missing[0].iface_type, InterfaceType::Dummy);
+
+    assert_eq!(drift.unmanaged.len(), 1);
+    assert_eq!(drift.unmanaged[0].name, "eth2");
+    assert_eq!(drift.unmanaged[0].iface_type, InterfaceType::Ethernet);
+
+    assert_eq!(drift.unmanaged_routes.len(), 1);
+    assert_eq!(
+        drift.unmanaged_routes[0].destination.as_deref(),
+        Some("203.0.113.0/24")
+    );
+
+    let eth1 = drift.changed.ifaces.kernel_ifaces.get("eth1").unwrap();
+    assert!(eth1.base_iface().ipv4.is_some());
+    assert!(drift.changed.ifaces.kernel_ifaces.get("dummy1").is_none());
+    assert!(drift.changed.ifaces.kernel_ifaces.get("eth2").is_none());
+}
+
+#[test]
+fn test_gen_drift_exclude_loopback_and_ignored_iface() {
+    let saved: NetworkState = serde_yaml::from_str(
+        r#"
+        interfaces:
+        - name: eth1
+          type: ethernet
+          state: up
+        - name: eth2
+          type: ethernet
+          state: ignore
+        - name: dummy1
+          type: dummy
+          state: ignore
+        "#,
+    )
+    .unwrap();
+    let current: NetworkState = serde_yaml::from_str(
+        r#"
+        interfaces:
+        - name: lo
+          type: loopback
+          state: up
+        - name: eth1
+          type: ethernet
+          state: up
+        - name: eth2
+          type: ethernet
+          state: down
+        routes:
+          config:
+          - destination: 203.0.113.0/24
+            next-hop-interface: eth2
+        "#,
+    )
+    .unwrap();
+
+    let drift = saved.gen_drift(&current).unwrap();
+
+    assert!(drift.is_empty());
+}
+
+#[test]
+fn test_gen_drift_no_change() {
+    let state: NetworkState = serde_yaml::from_str(
+        r#"
+        interfaces:
+        - name: eth1
+          type: ethernet
+          state: up
+        "#,
+    )
+    .unwrap();
+
+    let drift = state.gen_drift(&state).unwrap();
+
+    assert!(drift.is_empty());
+}
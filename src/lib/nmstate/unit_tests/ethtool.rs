@@ -0,0 +1,45 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{ErrorKind, EthtoolConfig, EthtoolFecMode};
+
+#[test]
+fn test_ethtool_feature_alias() {
+    let mut ethtool: EthtoolConfig = serde_yaml::from_str(
+        r#"
+        feature:
+          tso: false
+          gro: true
+          rx-checksum: true
+        fec:
+          mode: rs
+          active: baser
+        "#,
+    )
+    .unwrap();
+    ethtool.sanitize().unwrap();
+    let features = ethtool.feature.as_ref().unwrap();
+    assert_eq!(features.get("tx-tcp-segmentation"), Some(&false));
+    assert_eq!(features.get("rx-gro"), Some(&true));
+    assert_eq!(features.get("rx-checksum"), Some(&true));
+    assert!(!features.contains_key("tso"));
+    let fec = ethtool.fec.as_ref().unwrap();
+    assert_eq!(fec.mode, Some(EthtoolFecMode::Rs));
+    assert_eq!(fec.active, None);
+}
+
+#[test]
+fn test_ethtool_feature_alias_conflict() {
+    let mut ethtool: EthtoolConfig = serde_yaml::from_str(
+        r#"
+        feature:
+          rx: false
+          rx-checksum: true
+        "#,
+    )
+    .unwrap();
+    let result = ethtool.sanitize();
+    assert!(result.is_err());
+    if let Err(e) = result {
+        assert_eq!(e.kind(), ErrorKind::InvalidArgument);
+    }
+}
@@ -0,0 +1,150 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{BaseInterface, ErrorKind, NetworkState};
+
+fn current_state() -> NetworkState {
+    serde_yaml::from_str(
+        r#"
+        interfaces:
+        - name: ens3
+          type: ethernet
+          mac-address: 00:23:45:67:89:1A
+          permanent-mac-address: 00:23:45:67:89:1A
+          pci-path: 0000:00:03.0
+          driver: virtio_net
+        - name: ens4
+          type: ethernet
+          mac-address: 00:23:45:67:89:1B
+          pci-path: 0000:00:04.0
+          driver: e1000e
+        "#,
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_resolve_iface_identifier_mac() {
+    let mut desired: NetworkState = serde_yaml::from_str(
+        r#"
+        interfaces:
+        - name: lan0
+          type: ethernet
+          identifier: mac-address
+          mac-address: 00:23:45:67:89:1a
+        routes:
+          config:
+          - destination: 198.51.100.0/24
+            next-hop-interface: lan0
+            next-hop-address: 192.0.2.254
+        "#,
+    )
+    .unwrap();
+    let origin = desired.clone();
+
+    let names = desired.resolve_iface_identifiers(&current_state()).unwrap();
+
+    assert_eq!(names.get("ens3").map(|n| n.as_str()), Some("lan0"));
+    assert!(desired.ifaces.kernel_ifaces.contains_key("ens3"));
+    assert!(!desired.ifaces.kernel_ifaces.contains_key("lan0"));
+    assert_eq!(
+        desired.routes.config.as_ref().unwrap()[0]
+            .next_hop_iface
+            .as_deref(),
+        Some("ens3")
+    );
+
+    desired.restore_iface_identifiers(&names);
+    assert_eq!(desired, origin);
+}
+
+#[test]
+fn test_resolve_iface_identifier_pci_path_and_driver() {
+    let mut desired: NetworkState = serde_yaml::from_str(
+        r#"
+        interfaces:
+        - name: lan0
+          type: ethernet
+          identifier: pci-path
+          pci-path: 0000:00:04.0
+          driver: e1000e
+        - name: lan1
+          type: ethernet
+          identifier: pci-path
+          pci-path: 0000:00:03.0
+          driver: e1000e
+        "#,
+    )
+    .unwrap();
+
+    let names = desired.resolve_iface_identifiers(&current_state()).unwrap();
+
+    assert_eq!(names.len(), 1);
+    assert!(desired.ifaces.kernel_ifaces.contains_key("ens4"));
+    // Driver mismatch
+    assert!(desired.ifaces.kernel_ifaces.contains_key("lan1"));
+}
+
+#[test]
+fn test_resolve_iface_identifier_conflict() {
+    let mut desired: NetworkState = serde_yaml::from_str(
+        r#"
+        interfaces:
+        - name: lan0
+          type: ethernet
+          identifier: mac-address
+          mac-address: 00:23:45:67:89:1A
+        - name: ens3
+          type: ethernet
+        "#,
+    )
+    .unwrap();
+
+    let result = desired.resolve_iface_identifiers(&current_state());
+
+    assert_eq!(result.unwrap_err().kind(), ErrorKind::InvalidArgument);
+}
+
+#[test]
+fn test_resolve_iface_identifier_missing_property() {
+    let mut desired: NetworkState = serde_yaml::from_str(
+        r#"
+        interfaces:
+        - name: lan0
+          type: ethernet
+          identifier: pci-path
+        "#,
+    )
+    .unwrap();
+
+    let result = desired.resolve_iface_identifiers(&current_state());
+
+    assert_eq!(result.unwrap_err().kind(), ErrorKind::InvalidArgument);
+}
+
+#[test]
+fn test_iface_identifier_mac_not_verified() {
+    let mut desired: BaseInterface = serde_yaml::from_str(
+        r#"
+        name: ens3
+        type: ethernet
+        identifier: mac-address
+        mac-address: 00:23:45:67:89:1A
+        "#,
+    )
+    .unwrap();
+    // MAC address changed by bond
+    let mut current: BaseInterface = serde_yaml::from_str(
+        r#"
+        name: ens3
+        type: ethernet
+        mac-address: 00:23:45:67:89:1B
+        permanent-mac-address: 00:23:45:67:89:1A
+        "#,
+    )
+    .unwrap();
+
+    desired.sanitize_before_verify(&mut current);
+
+    assert_eq!(desired.mac_address, None);
+    assert!(desired.identifier.is_name());
+}
@@ -1,7 +1,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::super::ip::sanitize_ip_network;
-use crate::ErrorKind;
+use crate::{
+    ErrorKind, InterfaceIpv4, InterfaceIpv6, Ipv4LinkLocalMode, Ipv6AcceptRa,
+    Ipv6AddrGenMode, RpFilterMode,
+};
 
 #[test]
 fn test_sanitize_ip_network_empty_str() {
@@ -87,3 +90,221 @@ fn test_sanitize_ip_network_ipv6_net() {
         "2001:db8:1::/64"
     );
 }
+
+#[test]
+fn test_ipv4_sysctl_from_integer() {
+    let ipv4: InterfaceIpv4 = serde_yaml::from_str(
+        r#"
+        enabled: true
+        forwarding: 1
+        rp-filter: 2
+        arp-ignore: 1
+        arp-announce: 2
+        "#,
+    )
+    .unwrap();
+    assert_eq!(ipv4.forwarding, Some(true));
+    assert_eq!(ipv4.rp_filter, Some(RpFilterMode::Loose));
+    assert_eq!(ipv4.arp_ignore, Some(1));
+    assert_eq!(ipv4.arp_announce, Some(2));
+}
+
+#[test]
+fn test_ipv6_sysctl_from_string() {
+    let ipv6: InterfaceIpv6 = serde_yaml::from_str(
+        r#"
+        enabled: true
+        accept-ra: always
+        addr-gen-mode: stable-privacy
+        "#,
+    )
+    .unwrap();
+    assert_eq!(ipv6.accept_ra, Some(Ipv6AcceptRa::Always));
+    assert_eq!(ipv6.addr_gen_mode, Some(Ipv6AddrGenMode::StablePrivacy));
+}
+
+#[test]
+fn test_ipv4_invalid_arp_ignore() {
+    let mut ipv4: InterfaceIpv4 = serde_yaml::from_str(
+        r#"
+        enabled: true
+        arp-ignore: 5
+        "#,
+    )
+    .unwrap();
+    let result = ipv4.sanitize(None);
+    assert!(result.is_err());
+    if let Err(e) = result {
+        assert_eq!(e.kind(), ErrorKind::InvalidArgument);
+    }
+}
+
+#[test]
+fn test_ipv4_sysctl_preserved_when_disabled() {
+    let mut ipv4: InterfaceIpv4 = serde_yaml::from_str(
+        r#"
+        enabled: false
+        forwarding: true
+        "#,
+    )
+    .unwrap();
+    ipv4.sanitize(None).unwrap();
+    assert_eq!(ipv4.forwarding, Some(true));
+}
+
+#[test]
+fn test_ipv6_invalid_token() {
+    let mut ipv6: InterfaceIpv6 = serde_yaml::from_str(
+        r#"
+        enabled: true
+        autoconf: true
+        token: 2001:db8::1
+        "#,
+    )
+    .unwrap();
+    let result = ipv6.sanitize(None);
+    assert!(result.is_err());
+    if let Err(e) = result {
+        assert_eq!(e.kind(), ErrorKind::InvalidArgument);
+    }
+}
+
+#[test]
+fn test_ipv6_verify_ignore_temporary_address() {
+    let mut desired: InterfaceIpv6 = serde_yaml::from_str(
+        r#"
+        enabled: true
+        autoconf: true
+        use-tempaddr: prefer-public
+        address: []
+        "#,
+    )
+    .unwrap();
+    let mut current: InterfaceIpv6 = serde_yaml::from_str(
+        r#"
+        enabled: true
+        autoconf: true
+        use-tempaddr: prefer-public
+        address:
+        - ip: 2001:db8:1::6c2b:53ff:fe2d:1d3a
+          prefix-length: 64
+          valid-life-time: 2591998sec
+          preferred-life-time: 604798sec
+          temporary: true
+        "#,
+    )
+    .unwrap();
+    desired.sanitize_before_verify(&mut current);
+    assert_eq!(current.addresses, Some(Vec::new()));
+}
+
+#[test]
+fn test_ipv6_verify_stable_secret_in_different_format() {
+    let mut desired: InterfaceIpv6 = serde_yaml::from_str(
+        r#"
+        enabled: true
+        stable-secret: 2001:0db8:0:0::1
+        "#,
+    )
+    .unwrap();
+    let mut current: InterfaceIpv6 = serde_yaml::from_str(
+        r#"
+        enabled: true
+        stable-secret: 2001:db8::1
+        "#,
+    )
+    .unwrap();
+    desired.sanitize_before_verify(&mut current);
+    assert_eq!(desired.stable_secret, current.stable_secret);
+}
+
+#[test]
+fn test_ipv6_verify_stable_secret_mismatch() {
+    let mut desired: InterfaceIpv6 = serde_yaml::from_str(
+        r#"
+        enabled: true
+        stable-secret: 2001:db8::1
+        "#,
+    )
+    .unwrap();
+    let mut current: InterfaceIpv6 = serde_yaml::from_str(
+        r#"
+        enabled: true
+        stable-secret: 2001:db8::2
+        "#,
+    )
+    .unwrap();
+    desired.sanitize_before_verify(&mut current);
+    assert_eq!(desired.stable_secret.as_deref(), Some("2001:db8::1"));
+}
+
+#[test]
+fn test_ipv4_link_local_fallback_without_dhcp() {
+    let mut ipv4: InterfaceIpv4 = serde_yaml::from_str(
+        r#"
+        enabled: true
+        dhcp: false
+        link-local: fallback
+        "#,
+    )
+    .unwrap();
+    let result = ipv4.sanitize(None);
+    assert!(result.is_err());
+    if let Err(e) = result {
+        assert_eq!(e.kind(), ErrorKind::InvalidArgument);
+    }
+}
+
+#[test]
+fn test_ipv4_verify_ignore_link_local_address() {
+    let mut desired: InterfaceIpv4 = serde_yaml::from_str(
+        r#"
+        enabled: true
+        link-local: enabled
+        address:
+        - ip: 192.0.2.1
+          prefix-length: 24
+        "#,
+    )
+    .unwrap();
+    let mut current: InterfaceIpv4 = serde_yaml::from_str(
+        r#"
+        enabled: true
+        address:
+        - ip: 192.0.2.1
+          prefix-length: 24
+        - ip: 169.254.37.12
+          prefix-length: 16
+        "#,
+    )
+    .unwrap();
+    desired.sanitize_before_verify(&mut current);
+    assert_eq!(current.link_local, Some(Ipv4LinkLocalMode::Enabled));
+    assert_eq!(current.addresses.as_ref().map(|a| a.len()), Some(1));
+}
+
+#[test]
+fn test_ipv4_verify_ignore_dad_timeout() {
+    let mut desired: InterfaceIpv4 = serde_yaml::from_str(
+        r#"
+        enabled: true
+        dad-timeout: "3000"
+        address:
+        - ip: 192.0.2.1
+          prefix-length: 24
+        "#,
+    )
+    .unwrap();
+    let mut current: InterfaceIpv4 = serde_yaml::from_str(
+        r#"
+        enabled: true
+        address:
+        - ip: 192.0.2.1
+          prefix-length: 24
+        "#,
+    )
+    .unwrap();
+    assert_eq!(desired.dad_timeout, Some(3000));
+    desired.sanitize_before_verify(&mut current);
+    assert_eq!(current.dad_timeout, Some(3000));
+}
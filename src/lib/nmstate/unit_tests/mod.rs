@@ -1,5 +1,11 @@
 // SPDX-License-Identifier: Apache-2.0
 
+mod drift;
+mod ethtool;
+mod iface_identifier;
 mod ip;
 mod loopback;
+mod plan;
+mod plugin_states;
+mod restore;
 mod wifi;
@@ -0,0 +1,65 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    MergedNetworkState, NetworkState, NipartstateInterface,
+    NipartstatePlanAction,
+};
+
+#[test]
+fn test_gen_plan() {
+    let desired: NetworkState = serde_yaml::from_str(
+        r#"
+        interfaces:
+        - name: dummy1
+          type: dummy
+          state: up
+        - name: dummy2
+          type: dummy
+          state: absent
+        - name: eth1
+          type: ethernet
+          state: up
+          mtu: 9000
+        "#,
+    )
+    .unwrap();
+    let current: NetworkState = serde_yaml::from_str(
+        r#"
+        interfaces:
+        - name: dummy2
+          type: dummy
+          state: up
+        - name: eth1
+          type: ethernet
+          state: up
+          mtu: 1500
+        "#,
+    )
+    .unwrap();
+
+    let merged_state =
+        MergedNetworkState::new(desired, current, Default::default()).unwrap();
+    let plan = merged_state.gen_plan().unwrap();
+
+    let actions: Vec<(&str, NipartstatePlanAction)> = plan
+        .interfaces
+        .iter()
+        .map(|i| (i.name.as_str(), i.action))
+        .collect();
+    assert_eq!(
+        actions,
+        vec![
+            ("dummy1", NipartstatePlanAction::Create),
+            ("dummy2", NipartstatePlanAction::Delete),
+            ("eth1", NipartstatePlanAction::Modify),
+        ]
+    );
+    assert_eq!(
+        plan.interfaces[2]
+            .changes
+            .as_ref()
+            .and_then(|i| i.base_iface().mtu),
+        Some(9000)
+    );
+    assert!(plan.interfaces[1].changes.is_none());
+}
@@ -0,0 +1,196 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::NetworkState;
+
+#[test]
+fn test_plugin_states_merge() {
+    let mut saved: NetworkState = serde_yaml::from_str(
+        r#"
+        plugins:
+          demo:
+            tunnel: wg0
+            mtu: 1420
+          foo:
+            enabled: true
+        "#,
+    )
+    .unwrap();
+    let desired: NetworkState = serde_yaml::from_str(
+        r#"
+        plugins:
+          demo:
+            mtu: 1380
+          foo: null
+        "#,
+    )
+    .unwrap();
+    let expected: NetworkState = serde_yaml::from_str(
+        r#"
+        plugins:
+          demo:
+            tunnel: wg0
+            mtu: 1380
+          foo: null
+        "#,
+    )
+    .unwrap();
+
+    saved.merge(&desired).unwrap();
+
+    assert_eq!(saved.plugins, expected.plugins);
+}
+
+#[test]
+fn test_plugin_states_gen_diff_and_revert() {
+    let desired: NetworkState = serde_yaml::from_str(
+        r#"
+        plugins:
+          demo:
+            tunnel: wg0
+            mtu: 1380
+        "#,
+    )
+    .unwrap();
+    let current: NetworkState = serde_yaml::from_str(
+        r#"
+        plugins:
+          demo:
+            tunnel: wg0
+            mtu: 1420
+            peers:
+            - 192.0.2.1
+        "#,
+    )
+    .unwrap();
+    let expected_diff: NetworkState = serde_yaml::from_str(
+        r#"
+        plugins:
+          demo:
+            mtu: 1380
+        "#,
+    )
+    .unwrap();
+    let expected_revert: NetworkState = serde_yaml::from_str(
+        r#"
+        plugins:
+          demo:
+            tunnel: wg0
+            mtu: 1420
+        "#,
+    )
+    .unwrap();
+
+    let diff = desired.gen_diff(&current).unwrap();
+    let revert = desired.generate_revert(&current).unwrap();
+
+    assert_eq!(diff.plugins, expected_diff.plugins);
+    assert_eq!(revert.plugins, expected_revert.plugins);
+}
+
+#[test]
+fn test_plugin_states_gen_diff_changed_array() {
+    let desired: NetworkState = serde_yaml::from_str(
+        r#"
+        plugins:
+          demo:
+            tunnel: wg0
+            peers:
+            - 192.0.2.1
+            - 192.0.2.3
+        "#,
+    )
+    .unwrap();
+    let current: NetworkState = serde_yaml::from_str(
+        r#"
+        plugins:
+          demo:
+            tunnel: wg0
+            peers:
+            - 192.0.2.1
+            - 192.0.2.2
+        "#,
+    )
+    .unwrap();
+    let expected_diff: NetworkState = serde_yaml::from_str(
+        r#"
+        plugins:
+          demo:
+            peers:
+            - 192.0.2.1
+            - 192.0.2.3
+        "#,
+    )
+    .unwrap();
+
+    let diff = desired.gen_diff(&current).unwrap();
+
+    assert_eq!(diff.plugins, expected_diff.plugins);
+}
+
+#[test]
+fn test_plugin_states_gen_diff_unchanged_array() {
+    let desired: NetworkState = serde_yaml::from_str(
+        r#"
+        plugins:
+          demo:
+            tunnel: wg1
+            peers:
+            - 192.0.2.1
+        "#,
+    )
+    .unwrap();
+    let current: NetworkState = serde_yaml::from_str(
+        r#"
+        plugins:
+          demo:
+            tunnel: wg0
+            peers:
+            - 192.0.2.1
+        "#,
+    )
+    .unwrap();
+    let expected_diff: NetworkState = serde_yaml::from_str(
+        r#"
+        plugins:
+          demo:
+            tunnel: wg1
+        "#,
+    )
+    .unwrap();
+
+    let diff = desired.gen_diff(&current).unwrap();
+
+    assert_eq!(diff.plugins, expected_diff.plugins);
+}
+
+#[test]
+fn test_plugin_states_hide_secrets() {
+    let mut state: NetworkState = serde_yaml::from_str(
+        r#"
+        plugins:
+          demo:
+            peers:
+            - address: 192.0.2.1
+              password: abc
+        "#,
+    )
+    .unwrap();
+    let expected_secrets: NetworkState = serde_yaml::from_str(
+        r#"
+        plugins:
+          demo:
+            peers:
+            - address: 192.0.2.1
+              password: abc
+        "#,
+    )
+    .unwrap();
+
+    let secrets = state.hide_secrets();
+
+    assert_eq!(
+        state.plugins.get("demo").unwrap()["peers"][0]["password"],
+        NetworkState::HIDE_PASSWORD_STR
+    );
+    assert_eq!(secrets.plugins, expected_secrets.plugins);
+}
@@ -0,0 +1,84 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use serde_json::Value;
+
+use crate::{InterfaceType, NetworkState, NipartstateInterface, RouteState};
+
+#[test]
+fn test_gen_restore_state() {
+    let generation: NetworkState = serde_yaml::from_str(
+        r#"
+        interfaces:
+        - name: dummy1
+          type: dummy
+          state: up
+          mtu: 1500
+        routes:
+          config:
+          - destination: 198.51.100.0/24
+            next-hop-interface: dummy1
+        "#,
+    )
+    .unwrap();
+    let saved: NetworkState = serde_yaml::from_str(
+        r#"
+        interfaces:
+        - name: dummy1
+          type: dummy
+          state: up
+          mtu: 9000
+        - name: dummy2
+          type: dummy
+          state: up
+        routes:
+          config:
+          - destination: 198.51.100.0/24
+            next-hop-interface: dummy1
+          - destination: 203.0.113.0/24
+            next-hop-interface: dummy2
+        plugins:
+          demo:
+            tunnel: wg0
+        "#,
+    )
+    .unwrap();
+
+    let restore_state = generation.gen_restore_state(&saved);
+
+    let dummy1 = restore_state
+        .ifaces
+        .get("dummy1", Some(&InterfaceType::Dummy))
+        .unwrap();
+    assert!(dummy1.is_up());
+    assert_eq!(dummy1.base_iface().mtu, Some(1500));
+    assert!(
+        restore_state
+            .ifaces
+            .get("dummy2", Some(&InterfaceType::Dummy))
+            .unwrap()
+            .is_absent()
+    );
+
+    let routes = restore_state.routes.config.as_ref().unwrap();
+    assert_eq!(routes.len(), 2);
+    assert_eq!(routes[0].state, None);
+    assert_eq!(routes[1].state, Some(RouteState::Absent));
+    assert_eq!(routes[1].destination.as_deref(), Some("203.0.113.0/24"));
+
+    assert_eq!(restore_state.plugins.get("demo"), Some(&Value::Null));
+}
+
+#[test]
+fn test_gen_restore_state_identical() {
+    let saved: NetworkState = serde_yaml::from_str(
+        r#"
+        interfaces:
+        - name: dummy1
+          type: dummy
+          state: up
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(saved.gen_restore_state(&saved), saved);
+}
@@ -1,9 +1,13 @@
 // SPDX-License-Identifier: Apache-2.0
 
-use super::{inter_ifaces::apply_ifaces, route::apply_routes};
+use super::{
+    inter_ifaces::apply_ifaces, route::apply_routes,
+    sysctl::apply_ip_forwarding,
+};
 use crate::{
-    InterfaceType, MergedNetworkState, NetworkState, NipartError, NipartNoDaemon,
-    NipartstateApplyOption, NipartstateInterface,
+    ErrorKind, InterfaceType, MergedNetworkState, NetworkState, NipartError,
+    NipartNoDaemon, NipartstateApplyOption, NipartstateApplyPlan,
+    NipartstateInterface,
 };
 
 const RETRY_COUNT_COMMON: usize = 10;
@@ -12,11 +16,18 @@ const RETRY_INTERVAL_MS: u64 = 500;
 
 impl NipartNoDaemon {
     pub async fn apply_network_state(
-        desired_state: NetworkState,
+        mut desired_state: NetworkState,
         option: NipartstateApplyOption,
     ) -> Result<NetworkState, NipartError> {
+        if option.dry_run {
+            return Err(NipartError::new(
+                ErrorKind::InvalidArgument,
+                "Please use plan_network_state() for dry-run apply".to_string(),
+            ));
+        }
         let current_state =
             Self::query_network_state(Default::default()).await?;
+        desired_state.resolve_iface_identifiers(&current_state)?;
 
         log::trace!("Applying {desired_state} with option {option}");
         let merged_state = MergedNetworkState::new(
@@ -43,6 +54,7 @@ impl NipartNoDaemon {
         Self::apply_merged_state(&merged_state).await?;
         if option.dhcp_in_no_daemon {
             Self::run_dhcp_once(&merged_state.ifaces).await?;
+            Self::run_ipv4_link_local_once(&merged_state.ifaces).await?;
         }
 
         let max_retry_count = get_max_retry_count(&merged_state);
@@ -60,6 +72,8 @@ impl NipartNoDaemon {
                     Self::apply_merged_state(&merged_state).await?;
                     if option.dhcp_in_no_daemon {
                         Self::run_dhcp_once(&merged_state.ifaces).await?;
+                        Self::run_ipv4_link_local_once(&merged_state.ifaces)
+                            .await?;
                     }
                 }
                 result = merged_state.verify(&post_apply_current_state);
@@ -86,9 +100,27 @@ impl NipartNoDaemon {
         Ok(diff_state)
     }
 
+    /// Generate operations planned for applying desired state without
+    /// touching anything.
+    pub async fn plan_network_state(
+        mut desired_state: NetworkState,
+        option: NipartstateApplyOption,
+    ) -> Result<NipartstateApplyPlan, NipartError> {
+        let current_state =
+            Self::query_network_state(Default::default()).await?;
+        desired_state.resolve_iface_identifiers(&current_state)?;
+
+        log::trace!("Planning {desired_state} with option {option}");
+        MergedNetworkState::new(desired_state, current_state, option)?
+            .gen_plan()
+    }
+
     pub async fn apply_merged_state(
         merged_state: &MergedNetworkState,
     ) -> Result<(), NipartError> {
+        if let Some(ip_forwarding) = merged_state.ip_forwarding.as_ref() {
+            apply_ip_forwarding(ip_forwarding)?;
+        }
         apply_ifaces(&merged_state.ifaces).await?;
         apply_routes(&merged_state.routes).await?;
         Ok(())
@@ -0,0 +1,319 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    net::Ipv4Addr,
+    os::fd::{AsRawFd, FromRawFd, OwnedFd},
+};
+
+use nix::libc;
+use tokio::io::unix::AsyncFd;
+
+use super::netlink::ETH_ALEN;
+use crate::{ErrorKind, NipartError};
+
+const ARP_PKT_LEN: usize = 28;
+const ARP_HTYPE_ETHER: u16 = 1;
+const ARP_PTYPE_IPV4: u16 = 0x0800;
+const ARP_OP_REQUEST: u16 = 1;
+const BROADCAST_MAC: [u8; ETH_ALEN] = [0xff; ETH_ALEN];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ArpPacket {
+    pub(crate) sender_mac: [u8; ETH_ALEN],
+    pub(crate) sender_ip: Ipv4Addr,
+    pub(crate) target_ip: Ipv4Addr,
+}
+
+impl ArpPacket {
+    fn parse(buf: &[u8]) -> Option<Self> {
+        if buf.len() < ARP_PKT_LEN
+            || u16::from_be_bytes([buf[0], buf[1]]) != ARP_HTYPE_ETHER
+            || u16::from_be_bytes([buf[2], buf[3]]) != ARP_PTYPE_IPV4
+            || buf[4] as usize != ETH_ALEN
+            || buf[5] != 4
+        {
+            return None;
+        }
+        let mut sender_mac = [0u8; ETH_ALEN];
+        sender_mac.copy_from_slice(&buf[8..14]);
+        Some(Self {
+            sender_mac,
+            sender_ip: Ipv4Addr::new(buf[14], buf[15], buf[16], buf[17]),
+            target_ip: Ipv4Addr::new(buf[24], buf[25], buf[26], buf[27]),
+        })
+    }
+
+    /// Whether this packet indicate other host is using or probing specified
+    /// address, defined in RFC 5227 section 2.1.1.
+    pub(crate) fn is_conflict(
+        &self,
+        ip: Ipv4Addr,
+        own_mac: &[u8; ETH_ALEN],
+    ) -> bool {
+        &self.sender_mac != own_mac
+            && (self.sender_ip == ip
+                || (self.sender_ip.is_unspecified() && self.target_ip == ip))
+    }
+
+    /// Whether other host is using specified address, probes are ignored as
+    /// they are not conflict once address is claimed, defined in RFC 3927
+    /// section 2.5.
+    pub(crate) fn is_claimed_by_other(
+        &self,
+        ip: Ipv4Addr,
+        own_mac: &[u8; ETH_ALEN],
+    ) -> bool {
+        &self.sender_mac != own_mac && self.sender_ip == ip
+    }
+}
+
+/// ARP socket bind to specified interface, the link layer header is handled
+/// by kernel.
+#[derive(Debug)]
+pub(crate) struct ArpSocket {
+    fd: AsyncFd<OwnedFd>,
+    iface_index: u32,
+    mac: [u8; ETH_ALEN],
+}
+
+impl ArpSocket {
+    pub(crate) fn new(
+        iface_index: u32,
+        mac: [u8; ETH_ALEN],
+    ) -> Result<Self, NipartError> {
+        let protocol = (libc::ETH_P_ARP as u16).to_be();
+        // SAFETY: Plain integer arguments without any pointer, the returned
+        // file descriptor is checked before use.
+        let raw_fd = unsafe {
+            libc::socket(
+                libc::AF_PACKET,
+                libc::SOCK_DGRAM | libc::SOCK_NONBLOCK | libc::SOCK_CLOEXEC,
+                protocol as libc::c_int,
+            )
+        };
+        if raw_fd < 0 {
+            return Err(io_error_to_nipart(
+                std::io::Error::last_os_error(),
+                format!(
+                    "Failed to create ARP socket for interface index \
+                     {iface_index}"
+                ),
+            ));
+        }
+        // SAFETY: The `raw_fd` is valid and owned by nobody else.
+        let fd = unsafe { OwnedFd::from_raw_fd(raw_fd) };
+
+        let addr = gen_sockaddr_ll(iface_index, &[0u8; ETH_ALEN]);
+        // SAFETY: The `fd` is a valid AF_PACKET socket. The `addr` is a fully
+        // initialized `sockaddr_ll` living on stack till this call returns,
+        // and the address length is exactly the size of `sockaddr_ll`, so
+        // kernel will not read beyond it.
+        let rc = unsafe {
+            libc::bind(
+                fd.as_raw_fd(),
+                &addr as *const libc::sockaddr_ll as *const libc::sockaddr,
+                std::mem::size_of::<libc::sockaddr_ll>() as libc::socklen_t,
+            )
+        };
+        if rc < 0 {
+            return Err(io_error_to_nipart(
+                std::io::Error::last_os_error(),
+                format!(
+                    "Failed to bind ARP socket to interface index \
+                     {iface_index}"
+                ),
+            ));
+        }
+
+        let fd = AsyncFd::new(fd).map_err(|e| {
+            io_error_to_nipart(e, "Failed to register ARP socket".into())
+        })?;
+
+        Ok(Self {
+            fd,
+            iface_index,
+            mac,
+        })
+    }
+
+    pub(crate) fn mac(&self) -> &[u8; ETH_ALEN] {
+        &self.mac
+    }
+
+    /// Send ARP probe with sender IP address set to `0.0.0.0`.
+    pub(crate) fn send_probe(&self, ip: Ipv4Addr) -> Result<(), NipartError> {
+        self.send_request(Ipv4Addr::UNSPECIFIED, ip)
+    }
+
+    /// Send ARP announcement with both sender and target IP address set to
+    /// specified IP.
+    pub(crate) fn send_announce(
+        &self,
+        ip: Ipv4Addr,
+    ) -> Result<(), NipartError> {
+        self.send_request(ip, ip)
+    }
+
+    fn send_request(
+        &self,
+        sender_ip: Ipv4Addr,
+        target_ip: Ipv4Addr,
+    ) -> Result<(), NipartError> {
+        let mut buf = [0u8; ARP_PKT_LEN];
+        buf[0..2].copy_from_slice(&ARP_HTYPE_ETHER.to_be_bytes());
+        buf[2..4].copy_from_slice(&ARP_PTYPE_IPV4.to_be_bytes());
+        buf[4] = ETH_ALEN as u8;
+        buf[5] = 4;
+        buf[6..8].copy_from_slice(&ARP_OP_REQUEST.to_be_bytes());
+        buf[8..14].copy_from_slice(&self.mac);
+        buf[14..18].copy_from_slice(&sender_ip.octets());
+        // Target MAC is left as all zero
+        buf[24..28].copy_from_slice(&target_ip.octets());
+
+        let addr = gen_sockaddr_ll(self.iface_index, &BROADCAST_MAC);
+        // SAFETY: The socket is kept open by `self.fd`. Kernel reads
+        // `buf.len()` bytes from `buf` which is exactly the size of `buf`.
+        // The `addr` is a fully initialized `sockaddr_ll` living on stack
+        // till this call returns and the address length is exactly the size
+        // of `sockaddr_ll`.
+        let rc = unsafe {
+            libc::sendto(
+                self.fd.get_ref().as_raw_fd(),
+                buf.as_ptr() as *const libc::c_void,
+                buf.len(),
+                0,
+                &addr as *const libc::sockaddr_ll as *const libc::sockaddr,
+                std::mem::size_of::<libc::sockaddr_ll>() as libc::socklen_t,
+            )
+        };
+        if rc < 0 {
+            Err(io_error_to_nipart(
+                std::io::Error::last_os_error(),
+                format!(
+                    "Failed to send ARP request for {target_ip} on interface \
+                     index {}",
+                    self.iface_index
+                ),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Receive next ARP packet, malformed packets are ignored.
+    pub(crate) async fn recv(&self) -> Result<ArpPacket, NipartError> {
+        let mut buf = [0u8; 256];
+        loop {
+            let mut guard = self.fd.readable().await.map_err(|e| {
+                io_error_to_nipart(e, "Failed to wait ARP socket".into())
+            })?;
+            let result = guard.try_io(|fd| {
+                // SAFETY: The socket is kept open by `self.fd`. Kernel writes
+                // at most `buf.len()` bytes into `buf` which is exclusively
+                // borrowed here, and the returned length is checked before
+                // slicing `buf`.
+                let rc = unsafe {
+                    libc::recv(
+                        fd.get_ref().as_raw_fd(),
+                        buf.as_mut_ptr() as *mut libc::c_void,
+                        buf.len(),
+                        0,
+                    )
+                };
+                if rc < 0 {
+                    Err(std::io::Error::last_os_error())
+                } else {
+                    Ok(rc as usize)
+                }
+            });
+            match result {
+                Ok(Ok(len)) => {
+                    if let Some(pkt) = ArpPacket::parse(&buf[..len]) {
+                        return Ok(pkt);
+                    }
+                }
+                Ok(Err(e)) => {
+                    return Err(io_error_to_nipart(
+                        e,
+                        "Failed to receive from ARP socket".into(),
+                    ));
+                }
+                Err(_would_block) => continue,
+            }
+        }
+    }
+
+    /// Wait for conflict of specified IP address till timeout.
+    /// Return true if other host is using or probing this IP address.
+    pub(crate) async fn wait_conflict(
+        &self,
+        ip: Ipv4Addr,
+        timeout: std::time::Duration,
+    ) -> Result<bool, NipartError> {
+        Ok(self.wait_any_conflict(&[ip], timeout).await?.is_some())
+    }
+
+    /// Wait for conflict of any specified IP addresses till timeout.
+    /// Return the conflicting IP address and MAC address of the other host.
+    pub(crate) async fn wait_any_conflict(
+        &self,
+        ips: &[Ipv4Addr],
+        timeout: std::time::Duration,
+    ) -> Result<Option<(Ipv4Addr, [u8; ETH_ALEN])>, NipartError> {
+        let result = tokio::time::timeout(timeout, async {
+            loop {
+                let pkt = self.recv().await?;
+                if let Some(ip) =
+                    ips.iter().find(|ip| pkt.is_conflict(**ip, &self.mac))
+                {
+                    log::debug!(
+                        "Got ARP conflict on {ip} from {}",
+                        mac_to_string(&pkt.sender_mac)
+                    );
+                    return Ok::<_, NipartError>((*ip, pkt.sender_mac));
+                }
+            }
+        })
+        .await;
+        match result {
+            Ok(Ok(conflict)) => Ok(Some(conflict)),
+            Ok(Err(e)) => Err(e),
+            Err(_) => Ok(None),
+        }
+    }
+}
+
+fn gen_sockaddr_ll(
+    iface_index: u32,
+    mac: &[u8; ETH_ALEN],
+) -> libc::sockaddr_ll {
+    let mut sll_addr = [0u8; 8];
+    sll_addr[..ETH_ALEN].copy_from_slice(mac);
+    libc::sockaddr_ll {
+        sll_family: libc::AF_PACKET as u16,
+        sll_protocol: (libc::ETH_P_ARP as u16).to_be(),
+        sll_ifindex: iface_index as i32,
+        sll_hatype: 0,
+        sll_pkttype: 0,
+        sll_halen: ETH_ALEN as u8,
+        sll_addr,
+    }
+}
+
+fn io_error_to_nipart(e: std::io::Error, msg: String) -> NipartError {
+    NipartError::new(
+        if e.kind() == std::io::ErrorKind::PermissionDenied {
+            ErrorKind::PermissionDeny
+        } else {
+            ErrorKind::Bug
+        },
+        format!("{msg}: {e}"),
+    )
+}
+
+pub(crate) fn mac_to_string(mac: &[u8; ETH_ALEN]) -> String {
+    mac.iter()
+        .map(|b| format!("{b:02X}"))
+        .collect::<Vec<String>>()
+        .join(":")
+}
@@ -3,12 +3,15 @@
 use super::{
     iface::nmstate_iface_state_to_nispor,
     ip::{np_ipv4_to_nmstate, np_ipv6_to_nmstate},
+    sysctl::fill_ip_sysctl,
 };
 use crate::{
     NipartError,
     nmstate::{BaseInterface, InterfaceState, InterfaceType},
 };
 
+const SYSFS_NET_DIR: &str = "/sys/class/net";
+
 fn np_iface_type_to_nmstate(
     np_iface_type: &nispor::IfaceType,
 ) -> InterfaceType {
@@ -103,6 +106,8 @@ pub(crate) fn np_iface_to_base_iface(
     }
     base_iface.ipv4 = np_ipv4_to_nmstate(np_iface);
     base_iface.ipv6 = np_ipv6_to_nmstate(np_iface);
+    fill_ip_sysctl(&mut base_iface);
+    fill_device_info(&mut base_iface);
 
     base_iface
 }
@@ -146,3 +151,33 @@ pub(crate) fn apply_base_iface_link_changes(
     }
     Ok(())
 }
+
+// The PCI address is the last path component in the format of
+// `domain:bus:device.function` of `/sys/class/net/<iface>/device` symbolic
+// link, e.g. `/sys/devices/pci0000:00/0000:00:03.0/virtio0`.
+fn fill_device_info(base_iface: &mut BaseInterface) {
+    let dev_path = format!("{SYSFS_NET_DIR}/{}/device", base_iface.name);
+    if let Ok(path) = std::fs::canonicalize(&dev_path) {
+        base_iface.pci_path = path
+            .iter()
+            .filter_map(|p| p.to_str())
+            .filter(|p| is_pci_address(p))
+            .next_back()
+            .map(|p| p.to_string());
+    }
+    if let Ok(path) = std::fs::read_link(format!("{dev_path}/driver")) {
+        base_iface.driver = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .map(|n| n.to_string());
+    }
+}
+
+fn is_pci_address(name: &str) -> bool {
+    name.len() == 12
+        && name.char_indices().all(|(i, c)| match i {
+            4 | 7 => c == ':',
+            10 => c == '.',
+            _ => c.is_ascii_hexdigit(),
+        })
+}
@@ -3,7 +3,10 @@
 use futures_util::{StreamExt, stream::FuturesUnordered};
 use mozim::{DhcpV4Client, DhcpV4Config, DhcpV4Lease, DhcpV4State};
 
-use super::{ip::apply_iface_ip_changes, route::apply_routes};
+use super::{
+    ip::apply_iface_ip_changes, ipv4ll::run_ipv4_link_local,
+    route::apply_routes,
+};
 use crate::{
     ErrorKind, InterfaceIpAddr, InterfaceIpv4, InterfaceType, MergedInterfaces,
     MergedRoutes, NipartError, NipartNoDaemon, NipartstateInterface, RouteEntry, Routes,
@@ -30,10 +33,34 @@ impl NipartNoDaemon {
             get_lease_futures.push(get_lease_future);
         }
 
-        while let Some(result) = get_lease_futures.next().await {
-            // Should fail the whole apply action for any errors of DHCP.
-            let (iface_name, lease) = result?;
-            apply_lease(merged_ifaces, iface_name, lease).await?;
+        while let Some((iface_name, result)) = get_lease_futures.next().await {
+            match result {
+                Ok(lease) => {
+                    apply_lease(merged_ifaces, iface_name, lease).await?;
+                }
+                Err(e) => {
+                    if let Some(merged_iface) =
+                        merged_ifaces.kernel_ifaces.get(iface_name)
+                        && merged_iface
+                            .for_apply
+                            .as_ref()
+                            .and_then(|i| i.base_iface().ipv4.as_ref())
+                            .map(|i| i.is_link_local_fallback())
+                            == Some(true)
+                    {
+                        log::info!(
+                            "DHCPv4 on {iface_name} failed: {e}, falling back \
+                             to IPv4 link-local address"
+                        );
+                        run_ipv4_link_local(merged_iface.merged.base_iface())
+                            .await?;
+                    } else {
+                        // Should fail the whole apply action for any errors
+                        // of DHCP.
+                        return Err(e);
+                    }
+                }
+            }
         }
         Ok(())
     }
@@ -42,7 +69,14 @@ impl NipartNoDaemon {
 async fn get_lease<'a>(
     iface_name: &'a str,
     iface_type: &InterfaceType,
-) -> Result<(&'a str, DhcpV4Lease), NipartError> {
+) -> (&'a str, Result<DhcpV4Lease, NipartError>) {
+    (iface_name, get_lease_result(iface_name, iface_type).await)
+}
+
+async fn get_lease_result(
+    iface_name: &str,
+    iface_type: &InterfaceType,
+) -> Result<DhcpV4Lease, NipartError> {
     let dhcp_config = DhcpV4Config::new(iface_name);
     log::debug!(
         "Waiting link carrier up for interface {}/{} before start DHCP",
@@ -78,7 +112,7 @@ async fn get_lease<'a>(
                 lease.yiaddr,
                 lease.prefix_length()
             );
-            return Ok((iface_name, *lease));
+            return Ok(*lease);
         } else {
             log::info!(
                 "DHCPv4 on interface {iface_name}/{iface_type} reach {state} \
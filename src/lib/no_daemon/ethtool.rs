@@ -0,0 +1,996 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Query and apply ethtool settings via ethtool generic netlink.
+
+use std::collections::{BTreeMap, HashMap};
+
+use ethtool::{
+    EthtoolAttr, EthtoolChannelAttr, EthtoolCmd, EthtoolCoalesceAttr,
+    EthtoolError, EthtoolFeatureAttr, EthtoolFeatureBit, EthtoolFecAttr,
+    EthtoolHandle, EthtoolHeader, EthtoolMessage, EthtoolPauseAttr,
+    EthtoolRingAttr,
+};
+use futures_util::{StreamExt, TryStream, TryStreamExt};
+use netlink_packet_core::{
+    NLM_F_ACK, NLM_F_REQUEST, NetlinkMessage, NetlinkPayload,
+};
+use netlink_packet_generic::GenlMessage;
+use nix::libc;
+
+use crate::{
+    BaseInterface, ErrorKind, EthtoolChannelsConfig, EthtoolCoalesceConfig,
+    EthtoolConfig, EthtoolFecConfig, EthtoolFecMode, EthtoolPauseConfig,
+    EthtoolRingConfig, Interfaces, NipartError, NipartstateInterface,
+};
+
+// Link mode names and bits used by ETHTOOL_A_FEC_MODES and
+// ETHTOOL_A_FEC_ACTIVE
+const FEC_MODES: [(EthtoolFecMode, &str, u32); 4] = [
+    (EthtoolFecMode::Off, "None", 49),
+    (EthtoolFecMode::Rs, "RS", 50),
+    (EthtoolFecMode::Baser, "BASER", 51),
+    (EthtoolFecMode::Llrs, "LLRS", 74),
+];
+
+fn new_ethtool_handle() -> Result<EthtoolHandle, NipartError> {
+    let (conn, handle, _) = ethtool::new_connection().map_err(|e| {
+        NipartError::new(
+            ErrorKind::Bug,
+            format!("Failed to create ethtool netlink connection: {e}"),
+        )
+    })?;
+    tokio::spawn(conn);
+    Ok(handle)
+}
+
+fn ethtool_error(e: EthtoolError, msg: String) -> NipartError {
+    let kind = match &e {
+        EthtoolError::NetlinkError(err) => match -err.raw_code() {
+            libc::EPERM | libc::EACCES => ErrorKind::PermissionDeny,
+            libc::EOPNOTSUPP => ErrorKind::NoSupport,
+            libc::EINVAL | libc::ERANGE => ErrorKind::InvalidArgument,
+            _ => ErrorKind::Bug,
+        },
+        _ => ErrorKind::Bug,
+    };
+    NipartError::new(kind, format!("{msg}: {e}"))
+}
+
+fn iface_name_of_headers(headers: &[EthtoolHeader]) -> Option<String> {
+    headers.iter().find_map(|h| {
+        if let EthtoolHeader::DevName(name) = h {
+            Some(name.to_string())
+        } else {
+            None
+        }
+    })
+}
+
+fn iface_name_of_attr(attr: &EthtoolAttr) -> Option<String> {
+    match attr {
+        EthtoolAttr::Pause(EthtoolPauseAttr::Header(h))
+        | EthtoolAttr::Feature(EthtoolFeatureAttr::Header(h))
+        | EthtoolAttr::Coalesce(EthtoolCoalesceAttr::Header(h))
+        | EthtoolAttr::Ring(EthtoolRingAttr::Header(h))
+        | EthtoolAttr::Channel(EthtoolChannelAttr::Header(h))
+        | EthtoolAttr::Fec(EthtoolFecAttr::Header(h)) => {
+            iface_name_of_headers(h)
+        }
+        _ => None,
+    }
+}
+
+// Collect reply attributes indexed by interface name. Interfaces not
+// supporting this ethtool setting are not included.
+async fn collect_attrs(
+    replies: impl TryStream<Ok = GenlMessage<EthtoolMessage>, Error = EthtoolError>,
+) -> Result<HashMap<String, Vec<EthtoolAttr>>, EthtoolError> {
+    let mut ret = HashMap::new();
+    let mut replies = std::pin::pin!(replies.into_stream());
+    while let Some(reply) = replies.try_next().await? {
+        let attrs = reply.payload.nlas;
+        if let Some(iface_name) = attrs.iter().find_map(iface_name_of_attr) {
+            ret.insert(iface_name, attrs);
+        }
+    }
+    Ok(ret)
+}
+
+fn header(iface_name: &str) -> Vec<EthtoolHeader> {
+    vec![EthtoolHeader::DevName(iface_name.to_string())]
+}
+
+async fn ethtool_set(
+    handle: &mut EthtoolHandle,
+    cmd: EthtoolCmd,
+    nlas: Vec<EthtoolAttr>,
+) -> Result<(), EthtoolError> {
+    let mut nl_msg =
+        NetlinkMessage::from(GenlMessage::from_payload(EthtoolMessage {
+            cmd,
+            nlas,
+        }));
+    nl_msg.header.flags = NLM_F_REQUEST | NLM_F_ACK;
+    let mut replies = handle.request(nl_msg).await?;
+    while let Some(reply) = replies.next().await {
+        if let NetlinkPayload::Error(e) = reply.payload
+            && e.code.is_some()
+        {
+            return Err(EthtoolError::NetlinkError(e));
+        }
+    }
+    Ok(())
+}
+
+/// Fill ethtool settings into `ethtool` section of all kernel interfaces.
+/// Interfaces not supporting ethtool are untouched.
+pub(crate) async fn fill_ethtool(
+    ifaces: &mut Interfaces,
+) -> Result<(), NipartError> {
+    let mut handle = new_ethtool_handle()?;
+    let pause = handle.pause().get(None).execute().await;
+    let pause = collect_attrs(pause).await;
+    let feature = handle.feature().get(None).execute().await;
+    let feature = collect_attrs(feature).await;
+    let coalesce = handle.coalesce().get(None).execute().await;
+    let coalesce = collect_attrs(coalesce).await;
+    let ring = handle.ring().get(None).execute().await;
+    let ring = collect_attrs(ring).await;
+    let channels = handle.channel().get(None).execute().await;
+    let channels = collect_attrs(channels).await;
+    let fec = handle.fec().get(None).execute().await;
+    let fec = collect_attrs(fec).await;
+
+    let dumps = [
+        ("pause", pause),
+        ("feature", feature),
+        ("coalesce", coalesce),
+        ("ring", ring),
+        ("channels", channels),
+        ("fec", fec),
+    ];
+    let dumps: Vec<HashMap<String, Vec<EthtoolAttr>>> = dumps
+        .into_iter()
+        .map(|(name, result)| {
+            result.unwrap_or_else(|e| {
+                // Old kernel or driver might not support some of them
+                log::debug!("Failed to dump ethtool {name} settings: {e}");
+                HashMap::new()
+            })
+        })
+        .collect();
+
+    for iface in ifaces.kernel_ifaces.values_mut() {
+        let attrs: Vec<&EthtoolAttr> = dumps
+            .iter()
+            .filter_map(|d| d.get(iface.name()))
+            .flatten()
+            .collect();
+        let ethtool = ethtool_from_attrs(attrs.as_slice());
+        if !ethtool.is_empty() {
+            iface.base_iface_mut().ethtool = Some(ethtool);
+        }
+    }
+    Ok(())
+}
+
+fn ethtool_from_attrs(attrs: &[&EthtoolAttr]) -> EthtoolConfig {
+    let mut ret = EthtoolConfig::new();
+    ret.pause = pause_from_attrs(attrs);
+    ret.feature = features_from_attrs(attrs)
+        .map(|features| {
+            features
+                .into_iter()
+                .filter_map(|(name, (active, changeable))| {
+                    changeable.then_some((name, active))
+                })
+                .collect::<BTreeMap<String, bool>>()
+        })
+        .filter(|f| !f.is_empty());
+    ret.coalesce = coalesce_from_attrs(attrs);
+    ret.ring = ring_from_attrs(attrs);
+    ret.channels = channels_from_attrs(attrs);
+    ret.fec = fec_from_attrs(attrs);
+    ret
+}
+
+fn pause_from_attrs(attrs: &[&EthtoolAttr]) -> Option<EthtoolPauseConfig> {
+    let mut conf = EthtoolPauseConfig::new();
+    for attr in attrs {
+        match attr {
+            EthtoolAttr::Pause(EthtoolPauseAttr::AutoNeg(v)) => {
+                conf.autoneg = Some(*v)
+            }
+            EthtoolAttr::Pause(EthtoolPauseAttr::Rx(v)) => conf.rx = Some(*v),
+            EthtoolAttr::Pause(EthtoolPauseAttr::Tx(v)) => conf.tx = Some(*v),
+            _ => (),
+        }
+    }
+    (conf != EthtoolPauseConfig::default()).then_some(conf)
+}
+
+// Return feature name indexed (active, changeable)
+fn features_from_attrs(
+    attrs: &[&EthtoolAttr],
+) -> Option<BTreeMap<String, (bool, bool)>> {
+    let mut active: BTreeMap<String, bool> = BTreeMap::new();
+    let mut hw: Vec<&str> = Vec::new();
+    let mut no_change: Vec<&str> = Vec::new();
+    for attr in attrs {
+        match attr {
+            EthtoolAttr::Feature(EthtoolFeatureAttr::Active(bits)) => {
+                for bit in bits {
+                    active.insert(bit.name.to_string(), bit.value);
+                }
+            }
+            EthtoolAttr::Feature(EthtoolFeatureAttr::Hw(bits)) => {
+                hw.extend(
+                    bits.iter().filter(|b| b.value).map(|b| b.name.as_str()),
+                );
+            }
+            EthtoolAttr::Feature(EthtoolFeatureAttr::NoChange(bits)) => {
+                no_change.extend(
+                    bits.iter().filter(|b| b.value).map(|b| b.name.as_str()),
+                );
+            }
+            _ => (),
+        }
+    }
+    if active.is_empty() {
+        return None;
+    }
+    Some(
+        active
+            .into_iter()
+            .map(|(name, value)| {
+                let changeable = hw.contains(&name.as_str())
+                    && !no_change.contains(&name.as_str());
+                (name, (value, changeable))
+            })
+            .collect(),
+    )
+}
+
+fn coalesce_from_attrs(
+    attrs: &[&EthtoolAttr],
+) -> Option<EthtoolCoalesceConfig> {
+    let mut conf = EthtoolCoalesceConfig::new();
+    for attr in attrs {
+        let EthtoolAttr::Coalesce(attr) = attr else {
+            continue;
+        };
+        match attr {
+            EthtoolCoalesceAttr::UseAdaptiveRx(v) => {
+                conf.adaptive_rx = Some(*v)
+            }
+            EthtoolCoalesceAttr::UseAdaptiveTx(v) => {
+                conf.adaptive_tx = Some(*v)
+            }
+            EthtoolCoalesceAttr::PktRateHigh(v) => {
+                conf.pkt_rate_high = Some(*v)
+            }
+            EthtoolCoalesceAttr::PktRateLow(v) => conf.pkt_rate_low = Some(*v),
+            EthtoolCoalesceAttr::RateSampleInterval(v) => {
+                conf.rate_sample_interval = Some(*v)
+            }
+            EthtoolCoalesceAttr::RxMaxFrames(v) => conf.rx_frames = Some(*v),
+            EthtoolCoalesceAttr::RxMaxFramesHigh(v) => {
+                conf.rx_frames_high = Some(*v)
+            }
+            EthtoolCoalesceAttr::RxMaxFramesIrq(v) => {
+                conf.rx_frames_irq = Some(*v)
+            }
+            EthtoolCoalesceAttr::RxMaxFramesLow(v) => {
+                conf.rx_frames_low = Some(*v)
+            }
+            EthtoolCoalesceAttr::RxUsecs(v) => conf.rx_usecs = Some(*v),
+            EthtoolCoalesceAttr::RxUsecsHigh(v) => {
+                conf.rx_usecs_high = Some(*v)
+            }
+            EthtoolCoalesceAttr::RxUsecsIrq(v) => conf.rx_usecs_irq = Some(*v),
+            EthtoolCoalesceAttr::RxUsecsLow(v) => conf.rx_usecs_low = Some(*v),
+            EthtoolCoalesceAttr::StatsBlockUsecs(v) => {
+                conf.stats_block_usecs = Some(*v)
+            }
+            EthtoolCoalesceAttr::TxMaxFrames(v) => conf.tx_frames = Some(*v),
+            EthtoolCoalesceAttr::TxMaxFramesHigh(v) => {
+                conf.tx_frames_high = Some(*v)
+            }
+            EthtoolCoalesceAttr::TxMaxFramesIrq(v) => {
+                conf.tx_frames_irq = Some(*v)
+            }
+            EthtoolCoalesceAttr::TxMaxFramesLow(v) => {
+                conf.tx_frames_low = Some(*v)
+            }
+            EthtoolCoalesceAttr::TxUsecs(v) => conf.tx_usecs = Some(*v),
+            EthtoolCoalesceAttr::TxUsecsHigh(v) => {
+                conf.tx_usecs_high = Some(*v)
+            }
+            EthtoolCoalesceAttr::TxUsecsIrq(v) => conf.tx_usecs_irq = Some(*v),
+            EthtoolCoalesceAttr::TxUsecsLow(v) => conf.tx_usecs_low = Some(*v),
+            _ => (),
+        }
+    }
+    (conf != EthtoolCoalesceConfig::default()).then_some(conf)
+}
+
+fn coalesce_to_attrs(conf: &EthtoolCoalesceConfig) -> Vec<EthtoolCoalesceAttr> {
+    let mut ret = Vec::new();
+    for (value, to_attr) in [
+        (
+            conf.adaptive_rx,
+            EthtoolCoalesceAttr::UseAdaptiveRx as fn(bool) -> _,
+        ),
+        (conf.adaptive_tx, EthtoolCoalesceAttr::UseAdaptiveTx),
+    ] {
+        if let Some(v) = value {
+            ret.push(to_attr(v));
+        }
+    }
+    for (value, to_attr) in [
+        (
+            conf.pkt_rate_high,
+            EthtoolCoalesceAttr::PktRateHigh as fn(u32) -> _,
+        ),
+        (conf.pkt_rate_low, EthtoolCoalesceAttr::PktRateLow),
+        (
+            conf.rate_sample_interval,
+            EthtoolCoalesceAttr::RateSampleInterval,
+        ),
+        (conf.rx_frames, EthtoolCoalesceAttr::RxMaxFrames),
+        (conf.rx_frames_high, EthtoolCoalesceAttr::RxMaxFramesHigh),
+        (conf.rx_frames_irq, EthtoolCoalesceAttr::RxMaxFramesIrq),
+        (conf.rx_frames_low, EthtoolCoalesceAttr::RxMaxFramesLow),
+        (conf.rx_usecs, EthtoolCoalesceAttr::RxUsecs),
+        (conf.rx_usecs_high, EthtoolCoalesceAttr::RxUsecsHigh),
+        (conf.rx_usecs_irq, EthtoolCoalesceAttr::RxUsecsIrq),
+        (conf.rx_usecs_low, EthtoolCoalesceAttr::RxUsecsLow),
+        (conf.stats_block_usecs, EthtoolCoalesceAttr::StatsBlockUsecs),
+        (conf.tx_frames, EthtoolCoalesceAttr::TxMaxFrames),
+        (conf.tx_frames_high, EthtoolCoalesceAttr::TxMaxFramesHigh),
+        (conf.tx_frames_irq, EthtoolCoalesceAttr::TxMaxFramesIrq),
+        (conf.tx_frames_low, EthtoolCoalesceAttr::TxMaxFramesLow),
+        (conf.tx_usecs, EthtoolCoalesceAttr::TxUsecs),
+        (conf.tx_usecs_high, EthtoolCoalesceAttr::TxUsecsHigh),
+        (conf.tx_usecs_irq, EthtoolCoalesceAttr::TxUsecsIrq),
+        (conf.tx_usecs_low, EthtoolCoalesceAttr::TxUsecsLow),
+    ] {
+        if let Some(v) = value {
+            ret.push(to_attr(v));
+        }
+    }
+    ret
+}
+
+// Return ring sizes with maximum allowed values
+fn ring_from_attrs(attrs: &[&EthtoolAttr]) -> Option<EthtoolRingConfig> {
+    ring_and_max_from_attrs(attrs).map(|(conf, _)| conf)
+}
+
+fn ring_and_max_from_attrs(
+    attrs: &[&EthtoolAttr],
+) -> Option<(EthtoolRingConfig, EthtoolRingConfig)> {
+    let mut conf = EthtoolRingConfig::new();
+    let mut max = EthtoolRingConfig::new();
+    for attr in attrs {
+        let EthtoolAttr::Ring(attr) = attr else {
+            continue;
+        };
+        match attr {
+            EthtoolRingAttr::Rx(v) => conf.rx = Some(*v),
+            EthtoolRingAttr::RxJumbo(v) => conf.rx_jumbo = Some(*v),
+            EthtoolRingAttr::RxMini(v) => conf.rx_mini = Some(*v),
+            EthtoolRingAttr::Tx(v) => conf.tx = Some(*v),
+            EthtoolRingAttr::RxMax(v) => max.rx = Some(*v),
+            EthtoolRingAttr::RxJumboMax(v) => max.rx_jumbo = Some(*v),
+            EthtoolRingAttr::RxMiniMax(v) => max.rx_mini = Some(*v),
+            EthtoolRingAttr::TxMax(v) => max.tx = Some(*v),
+            _ => (),
+        }
+    }
+    // Kernel report 0 maximum for unsupported rings
+    if max.rx_jumbo.unwrap_or_default() == 0 {
+        conf.rx_jumbo = None;
+    }
+    if max.rx_mini.unwrap_or_default() == 0 {
+        conf.rx_mini = None;
+    }
+    (conf != EthtoolRingConfig::default()).then_some((conf, max))
+}
+
+fn channels_from_attrs(
+    attrs: &[&EthtoolAttr],
+) -> Option<EthtoolChannelsConfig> {
+    let mut conf = EthtoolChannelsConfig::new();
+    let mut max = EthtoolChannelsConfig::new();
+    for attr in attrs {
+        let EthtoolAttr::Channel(attr) = attr else {
+            continue;
+        };
+        match attr {
+            EthtoolChannelAttr::RxCount(v) => conf.rx = Some(*v),
+            EthtoolChannelAttr::TxCount(v) => conf.tx = Some(*v),
+            EthtoolChannelAttr::OtherCount(v) => conf.other = Some(*v),
+            EthtoolChannelAttr::CombinedCount(v) => conf.combined = Some(*v),
+            EthtoolChannelAttr::RxMax(v) => max.rx = Some(*v),
+            EthtoolChannelAttr::TxMax(v) => max.tx = Some(*v),
+            EthtoolChannelAttr::OtherMax(v) => max.other = Some(*v),
+            EthtoolChannelAttr::CombinedMax(v) => max.combined = Some(*v),
+            _ => (),
+        }
+    }
+    // Channel type with 0 maximum is not supported by driver
+    conf.rx = conf.rx.filter(|_| max.rx.unwrap_or_default() > 0);
+    conf.tx = conf.tx.filter(|_| max.tx.unwrap_or_default() > 0);
+    conf.other = conf.other.filter(|_| max.other.unwrap_or_default() > 0);
+    conf.combined = conf
+        .combined
+        .filter(|_| max.combined.unwrap_or_default() > 0);
+    (conf != EthtoolChannelsConfig::default()).then_some(conf)
+}
+
+fn fec_from_attrs(attrs: &[&EthtoolAttr]) -> Option<EthtoolFecConfig> {
+    let mut conf = EthtoolFecConfig::new();
+    for attr in attrs {
+        match attr {
+            EthtoolAttr::Fec(EthtoolFecAttr::Auto(true)) => {
+                conf.mode = Some(EthtoolFecMode::Auto);
+            }
+            EthtoolAttr::Fec(EthtoolFecAttr::Modes(modes))
+                if conf.mode.is_none() =>
+            {
+                conf.mode = FEC_MODES.iter().find_map(|(mode, name, _)| {
+                    modes.iter().any(|m| m == name).then_some(*mode)
+                });
+            }
+            EthtoolAttr::Fec(EthtoolFecAttr::Active(bit)) => {
+                conf.active = FEC_MODES
+                    .iter()
+                    .find_map(|(mode, _, b)| (b == bit).then_some(*mode));
+            }
+            _ => (),
+        }
+    }
+    (conf != EthtoolFecConfig::default()).then_some(conf)
+}
+
+fn fec_to_attrs(mode: EthtoolFecMode) -> Vec<EthtoolFecAttr> {
+    match mode {
+        EthtoolFecMode::Auto => vec![
+            EthtoolFecAttr::Auto(true),
+            EthtoolFecAttr::Modes(Vec::new()),
+        ],
+        _ => vec![
+            EthtoolFecAttr::Auto(false),
+            EthtoolFecAttr::Modes(
+                FEC_MODES
+                    .iter()
+                    .filter(|(m, _, _)| *m == mode)
+                    .map(|(_, name, _)| name.to_string())
+                    .collect(),
+            ),
+        ],
+    }
+}
+
+/// Apply ethtool settings of specified interface. Current settings are
+/// queried again as interface might be just created.
+pub(crate) async fn apply_iface_ethtool(
+    des_iface: &BaseInterface,
+) -> Result<(), NipartError> {
+    if des_iface.is_absent() {
+        return Ok(());
+    }
+    let Some(des_ethtool) = des_iface.ethtool.as_ref() else {
+        return Ok(());
+    };
+    let iface_name = des_iface.name.as_str();
+    let mut handle = new_ethtool_handle()?;
+
+    if let Some(des_pause) = des_ethtool.pause.as_ref() {
+        apply_pause(&mut handle, iface_name, des_pause).await?;
+    }
+    if let Some(des_features) = des_ethtool.feature.as_ref() {
+        apply_features(&mut handle, iface_name, des_features).await?;
+    }
+    if let Some(des_coalesce) = des_ethtool.coalesce.as_ref() {
+        apply_coalesce(&mut handle, iface_name, des_coalesce).await?;
+    }
+    if let Some(des_ring) = des_ethtool.ring.as_ref() {
+        apply_ring(&mut handle, iface_name, des_ring).await?;
+    }
+    if let Some(des_channels) = des_ethtool.channels.as_ref() {
+        apply_channels(&mut handle, iface_name, des_channels).await?;
+    }
+    if let Some(des_fec) = des_ethtool.fec.as_ref() {
+        apply_fec(&mut handle, iface_name, des_fec).await?;
+    }
+    Ok(())
+}
+
+// Return None if value is already desired
+fn changed<T: PartialEq + Copy>(
+    desired: Option<T>,
+    current: Option<T>,
+) -> Option<T> {
+    desired.filter(|d| Some(*d) != current)
+}
+
+async fn apply_pause(
+    handle: &mut EthtoolHandle,
+    iface_name: &str,
+    des: &EthtoolPauseConfig,
+) -> Result<(), NipartError> {
+    let attrs = collect_attrs(
+        handle.pause().get(Some(iface_name)).execute().await,
+    )
+    .await
+    .map_err(|e| {
+        ethtool_error(
+            e,
+            format!("Failed to query pause settings of interface {iface_name}"),
+        )
+    })?;
+    let attrs: Vec<&EthtoolAttr> = attrs.values().flatten().collect();
+    let cur = pause_from_attrs(attrs.as_slice()).unwrap_or_default();
+
+    let mut nlas = Vec::new();
+    if let Some(v) = changed(des.autoneg, cur.autoneg) {
+        nlas.push(EthtoolAttr::Pause(EthtoolPauseAttr::AutoNeg(v)));
+    }
+    if let Some(v) = changed(des.rx, cur.rx) {
+        nlas.push(EthtoolAttr::Pause(EthtoolPauseAttr::Rx(v)));
+    }
+    if let Some(v) = changed(des.tx, cur.tx) {
+        nlas.push(EthtoolAttr::Pause(EthtoolPauseAttr::Tx(v)));
+    }
+    if nlas.is_empty() {
+        return Ok(());
+    }
+    log::debug!("Setting ethtool pause of interface {iface_name} to {des}");
+    nlas.insert(
+        0,
+        EthtoolAttr::Pause(EthtoolPauseAttr::Header(header(iface_name))),
+    );
+    ethtool_set(handle, EthtoolCmd::PauseSet, nlas)
+        .await
+        .map_err(|e| {
+            ethtool_error(
+                e,
+                format!(
+                    "Failed to set pause settings of interface {iface_name}"
+                ),
+            )
+        })
+}
+
+async fn apply_features(
+    handle: &mut EthtoolHandle,
+    iface_name: &str,
+    des: &BTreeMap<String, bool>,
+) -> Result<(), NipartError> {
+    let attrs = collect_attrs(
+        handle.feature().get(Some(iface_name)).execute().await,
+    )
+    .await
+    .map_err(|e| {
+        ethtool_error(
+            e,
+            format!(
+                "Failed to query ethtool features of interface {iface_name}"
+            ),
+        )
+    })?;
+    let attrs: Vec<&EthtoolAttr> = attrs.values().flatten().collect();
+    let cur_features =
+        features_from_attrs(attrs.as_slice()).unwrap_or_default();
+
+    let mut wanted: Vec<EthtoolFeatureBit> = Vec::new();
+    for (name, value) in des.iter() {
+        let Some((active, changeable)) = cur_features.get(name) else {
+            return Err(NipartError::new(
+                ErrorKind::InvalidArgument,
+                format!(
+                    "Ethtool feature {name} is not supported by interface \
+                     {iface_name}"
+                ),
+            ));
+        };
+        if active == value {
+            continue;
+        }
+        if !changeable {
+            return Err(NipartError::new(
+                ErrorKind::NoSupport,
+                format!(
+                    "Ethtool feature {name} of interface {iface_name} is \
+                     fixed to {active}"
+                ),
+            ));
+        }
+        wanted.push(EthtoolFeatureBit {
+            index: 0,
+            name: name.to_string(),
+            value: *value,
+        });
+    }
+    if wanted.is_empty() {
+        return Ok(());
+    }
+    log::debug!("Setting ethtool features of interface {iface_name}");
+    ethtool_set(
+        handle,
+        EthtoolCmd::FeatureSet,
+        vec![
+            EthtoolAttr::Feature(EthtoolFeatureAttr::Header(header(
+                iface_name,
+            ))),
+            EthtoolAttr::Feature(EthtoolFeatureAttr::Wanted(wanted)),
+        ],
+    )
+    .await
+    .map_err(|e| {
+        ethtool_error(
+            e,
+            format!("Failed to set ethtool features of interface {iface_name}"),
+        )
+    })
+}
+
+async fn apply_coalesce(
+    handle: &mut EthtoolHandle,
+    iface_name: &str,
+    des: &EthtoolCoalesceConfig,
+) -> Result<(), NipartError> {
+    let attrs = collect_attrs(
+        handle.coalesce().get(Some(iface_name)).execute().await,
+    )
+    .await
+    .map_err(|e| {
+        ethtool_error(
+            e,
+            format!(
+                "Failed to query coalesce settings of interface {iface_name}"
+            ),
+        )
+    })?;
+    let attrs: Vec<&EthtoolAttr> = attrs.values().flatten().collect();
+    let cur_attrs = coalesce_from_attrs(attrs.as_slice())
+        .map(|c| coalesce_to_attrs(&c))
+        .unwrap_or_default();
+
+    let mut nlas: Vec<EthtoolAttr> = coalesce_to_attrs(des)
+        .into_iter()
+        .filter(|a| !cur_attrs.contains(a))
+        .map(EthtoolAttr::Coalesce)
+        .collect();
+    if nlas.is_empty() {
+        return Ok(());
+    }
+    log::debug!("Setting ethtool coalesce of interface {iface_name} to {des}");
+    nlas.insert(
+        0,
+        EthtoolAttr::Coalesce(EthtoolCoalesceAttr::Header(header(iface_name))),
+    );
+    ethtool_set(handle, EthtoolCmd::CoalesceSet, nlas)
+        .await
+        .map_err(|e| {
+            ethtool_error(
+                e,
+                format!(
+                    "Failed to set coalesce settings of interface \
+                     {iface_name}"
+                ),
+            )
+        })
+}
+
+async fn apply_ring(
+    handle: &mut EthtoolHandle,
+    iface_name: &str,
+    des: &EthtoolRingConfig,
+) -> Result<(), NipartError> {
+    let attrs = collect_attrs(
+        handle.ring().get(Some(iface_name)).execute().await,
+    )
+    .await
+    .map_err(|e| {
+        ethtool_error(
+            e,
+            format!("Failed to query ring settings of interface {iface_name}"),
+        )
+    })?;
+    let attrs: Vec<&EthtoolAttr> = attrs.values().flatten().collect();
+    let (cur, max) =
+        ring_and_max_from_attrs(attrs.as_slice()).unwrap_or_default();
+
+    let mut nlas = Vec::new();
+    for (name, desired, current, max, to_attr) in [
+        (
+            "rx",
+            des.rx,
+            cur.rx,
+            max.rx,
+            EthtoolRingAttr::Rx as fn(u32) -> EthtoolRingAttr,
+        ),
+        (
+            "rx-jumbo",
+            des.rx_jumbo,
+            cur.rx_jumbo,
+            max.rx_jumbo,
+            EthtoolRingAttr::RxJumbo,
+        ),
+        (
+            "rx-mini",
+            des.rx_mini,
+            cur.rx_mini,
+            max.rx_mini,
+            EthtoolRingAttr::RxMini,
+        ),
+        ("tx", des.tx, cur.tx, max.tx, EthtoolRingAttr::Tx),
+    ] {
+        let Some(desired) = changed(desired, current) else {
+            continue;
+        };
+        let max = max.unwrap_or_default();
+        if desired > max {
+            return Err(NipartError::new(
+                ErrorKind::InvalidArgument,
+                format!(
+                    "Desired ethtool ring {name} size {desired} of interface \
+                     {iface_name} is bigger than maximum allowed {max}"
+                ),
+            ));
+        }
+        nlas.push(EthtoolAttr::Ring(to_attr(desired)));
+    }
+    if nlas.is_empty() {
+        return Ok(());
+    }
+    log::debug!("Setting ethtool ring of interface {iface_name} to {des}");
+    nlas.insert(
+        0,
+        EthtoolAttr::Ring(EthtoolRingAttr::Header(header(iface_name))),
+    );
+    ethtool_set(handle, EthtoolCmd::RingSet, nlas)
+        .await
+        .map_err(|e| {
+            ethtool_error(
+                e,
+                format!(
+                    "Failed to set ring settings of interface {iface_name}"
+                ),
+            )
+        })
+}
+
+async fn apply_channels(
+    handle: &mut EthtoolHandle,
+    iface_name: &str,
+    des: &EthtoolChannelsConfig,
+) -> Result<(), NipartError> {
+    let attrs =
+        collect_attrs(handle.channel().get(Some(iface_name)).execute().await)
+            .await
+            .map_err(|e| {
+                ethtool_error(
+                    e,
+                    format!(
+                        "Failed to query channels of interface {iface_name}"
+                    ),
+                )
+            })?;
+    let attrs: Vec<&EthtoolAttr> = attrs.values().flatten().collect();
+    let cur = channels_from_attrs(attrs.as_slice()).unwrap_or_default();
+
+    let mut nlas = Vec::new();
+    for (desired, current, to_attr) in [
+        (
+            des.rx,
+            cur.rx,
+            EthtoolChannelAttr::RxCount as fn(u32) -> EthtoolChannelAttr,
+        ),
+        (des.tx, cur.tx, EthtoolChannelAttr::TxCount),
+        (des.other, cur.other, EthtoolChannelAttr::OtherCount),
+        (
+            des.combined,
+            cur.combined,
+            EthtoolChannelAttr::CombinedCount,
+        ),
+    ] {
+        if let Some(v) = changed(desired, current) {
+            nlas.push(EthtoolAttr::Channel(to_attr(v)));
+        }
+    }
+    if nlas.is_empty() {
+        return Ok(());
+    }
+    log::debug!("Setting ethtool channels of interface {iface_name} to {des}");
+    nlas.insert(
+        0,
+        EthtoolAttr::Channel(EthtoolChannelAttr::Header(header(iface_name))),
+    );
+    ethtool_set(handle, EthtoolCmd::ChannelSet, nlas)
+        .await
+        .map_err(|e| {
+            ethtool_error(
+                e,
+                format!("Failed to set channels of interface {iface_name}"),
+            )
+        })
+}
+
+async fn apply_fec(
+    handle: &mut EthtoolHandle,
+    iface_name: &str,
+    des: &EthtoolFecConfig,
+) -> Result<(), NipartError> {
+    let Some(des_mode) = des.mode else {
+        return Ok(());
+    };
+    let attrs =
+        collect_attrs(handle.fec().get(Some(iface_name)).execute().await)
+            .await
+            .map_err(|e| {
+                ethtool_error(
+                    e,
+                    format!(
+                        "Failed to query FEC settings of interface {iface_name}"
+                    ),
+                )
+            })?;
+    let attrs: Vec<&EthtoolAttr> = attrs.values().flatten().collect();
+    let cur = fec_from_attrs(attrs.as_slice()).unwrap_or_default();
+    if cur.mode == Some(des_mode) {
+        return Ok(());
+    }
+    log::debug!(
+        "Setting ethtool FEC mode of interface {iface_name} to {des_mode}"
+    );
+    let mut nlas =
+        vec![EthtoolAttr::Fec(EthtoolFecAttr::Header(header(iface_name)))];
+    nlas.extend(fec_to_attrs(des_mode).into_iter().map(EthtoolAttr::Fec));
+    ethtool_set(handle, EthtoolCmd::FecSet, nlas)
+        .await
+        .map_err(|e| {
+            ethtool_error(
+                e,
+                format!("Failed to set FEC mode of interface {iface_name}"),
+            )
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn feature_bits(names: &[(&str, bool)]) -> Vec<EthtoolFeatureBit> {
+        names
+            .iter()
+            .enumerate()
+            .map(|(index, (name, value))| EthtoolFeatureBit {
+                index: index as u32,
+                name: name.to_string(),
+                value: *value,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_ethtool_from_attrs() {
+        let attrs = [
+            EthtoolAttr::Pause(EthtoolPauseAttr::AutoNeg(false)),
+            EthtoolAttr::Pause(EthtoolPauseAttr::Rx(true)),
+            EthtoolAttr::Pause(EthtoolPauseAttr::Tx(false)),
+            EthtoolAttr::Feature(EthtoolFeatureAttr::Active(feature_bits(&[
+                ("rx-gro", true),
+                ("tx-tcp-segmentation", false),
+                ("rx-vlan-filter", true),
+            ]))),
+            EthtoolAttr::Feature(EthtoolFeatureAttr::Hw(feature_bits(&[
+                ("rx-gro", true),
+                ("tx-tcp-segmentation", true),
+                ("rx-vlan-filter", true),
+            ]))),
+            EthtoolAttr::Feature(EthtoolFeatureAttr::NoChange(feature_bits(
+                &[("rx-vlan-filter", true)],
+            ))),
+            EthtoolAttr::Coalesce(EthtoolCoalesceAttr::RxUsecs(50)),
+            EthtoolAttr::Coalesce(EthtoolCoalesceAttr::UseAdaptiveRx(true)),
+            EthtoolAttr::Ring(EthtoolRingAttr::Rx(256)),
+            EthtoolAttr::Ring(EthtoolRingAttr::RxMax(4096)),
+            EthtoolAttr::Ring(EthtoolRingAttr::RxMini(0)),
+            EthtoolAttr::Ring(EthtoolRingAttr::RxMiniMax(0)),
+            EthtoolAttr::Channel(EthtoolChannelAttr::CombinedCount(4)),
+            EthtoolAttr::Channel(EthtoolChannelAttr::CombinedMax(8)),
+            EthtoolAttr::Channel(EthtoolChannelAttr::RxCount(0)),
+            EthtoolAttr::Channel(EthtoolChannelAttr::RxMax(0)),
+            EthtoolAttr::Fec(EthtoolFecAttr::Auto(false)),
+            EthtoolAttr::Fec(EthtoolFecAttr::Modes(vec!["RS".to_string()])),
+            EthtoolAttr::Fec(EthtoolFecAttr::Active(51)),
+        ];
+        let attrs: Vec<&EthtoolAttr> = attrs.iter().collect();
+        let ethtool = ethtool_from_attrs(attrs.as_slice());
+
+        let pause = ethtool.pause.unwrap();
+        assert_eq!(pause.autoneg, Some(false));
+        assert_eq!(pause.rx, Some(true));
+        assert_eq!(pause.tx, Some(false));
+
+        // Fixed features are not included
+        let features = ethtool.feature.unwrap();
+        assert_eq!(features.len(), 2);
+        assert_eq!(features.get("rx-gro"), Some(&true));
+        assert_eq!(features.get("tx-tcp-segmentation"), Some(&false));
+
+        let coalesce = ethtool.coalesce.unwrap();
+        assert_eq!(coalesce.rx_usecs, Some(50));
+        assert_eq!(coalesce.adaptive_rx, Some(true));
+        assert_eq!(coalesce.tx_usecs, None);
+
+        let ring = ethtool.ring.unwrap();
+        assert_eq!(ring.rx, Some(256));
+        assert_eq!(ring.rx_mini, None);
+
+        let channels = ethtool.channels.unwrap();
+        assert_eq!(channels.combined, Some(4));
+        assert_eq!(channels.rx, None);
+
+        let fec = ethtool.fec.unwrap();
+        assert_eq!(fec.mode, Some(EthtoolFecMode::Rs));
+        assert_eq!(fec.active, Some(EthtoolFecMode::Baser));
+    }
+
+    #[test]
+    fn test_ethtool_from_empty_attrs() {
+        assert!(ethtool_from_attrs(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_ethtool_fec_auto() {
+        let attrs = [
+            EthtoolAttr::Fec(EthtoolFecAttr::Auto(true)),
+            EthtoolAttr::Fec(EthtoolFecAttr::Modes(vec!["RS".to_string()])),
+        ];
+        let attrs: Vec<&EthtoolAttr> = attrs.iter().collect();
+        assert_eq!(
+            fec_from_attrs(attrs.as_slice()).unwrap().mode,
+            Some(EthtoolFecMode::Auto)
+        );
+        assert_eq!(
+            fec_to_attrs(EthtoolFecMode::Auto),
+            vec![
+                EthtoolFecAttr::Auto(true),
+                EthtoolFecAttr::Modes(Vec::new())
+            ]
+        );
+        assert_eq!(
+            fec_to_attrs(EthtoolFecMode::Off),
+            vec![
+                EthtoolFecAttr::Auto(false),
+                EthtoolFecAttr::Modes(vec!["None".to_string()])
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ethtool_coalesce_round_trip() {
+        let mut conf = EthtoolCoalesceConfig::new();
+        conf.adaptive_tx = Some(false);
+        conf.tx_frames_irq = Some(8);
+        let attrs: Vec<EthtoolAttr> = coalesce_to_attrs(&conf)
+            .into_iter()
+            .map(EthtoolAttr::Coalesce)
+            .collect();
+        let attrs: Vec<&EthtoolAttr> = attrs.iter().collect();
+        assert_eq!(coalesce_from_attrs(attrs.as_slice()), Some(conf));
+    }
+
+    #[test]
+    fn test_ethtool_changed() {
+        assert_eq!(changed(Some(1u32), Some(1)), None);
+        assert_eq!(changed(Some(2u32), Some(1)), Some(2));
+        assert_eq!(changed(Some(true), None), Some(true));
+        assert_eq!(changed::<bool>(None, Some(true)), None);
+    }
+}
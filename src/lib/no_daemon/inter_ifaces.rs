@@ -1,8 +1,12 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::{
+    ethtool::apply_iface_ethtool,
     iface::{apply_iface_link_changes, nmstate_iface_type_to_nispor},
     ip::apply_iface_ip_changes,
+    ipv4_dad::ipv4_dad,
+    ipv6_token::apply_iface_ipv6_token,
+    sysctl::apply_iface_sysctl,
     wifi::NipartWpaConn,
 };
 use crate::{
@@ -17,8 +21,14 @@ pub(crate) async fn apply_ifaces(
 
     apply_ifaces_link_changes(merged_ifaces).await?;
 
+    apply_ifaces_ethtool(merged_ifaces).await?;
+
     apply_ifaces_ip_changes(merged_ifaces).await?;
 
+    apply_ifaces_sysctl(merged_ifaces)?;
+
+    apply_ifaces_ipv6_token(merged_ifaces).await?;
+
     Ok(())
 }
 
@@ -182,6 +192,22 @@ async fn apply_ifaces_link_changes(
 async fn apply_ifaces_ip_changes(
     merged_ifaces: &MergedInterfaces,
 ) -> Result<(), NipartError> {
+    // Probe all interfaces concurrently before any IP change
+    futures_util::future::try_join_all(
+        merged_ifaces
+            .kernel_ifaces
+            .values()
+            .filter_map(|merged_iface| {
+                merged_iface.for_apply.as_ref().map(|apply_iface| {
+                    ipv4_dad(
+                        apply_iface.base_iface(),
+                        merged_iface.current.as_ref().map(|c| c.base_iface()),
+                    )
+                })
+            }),
+    )
+    .await?;
+
     let mut np_ifaces: Vec<nispor::IfaceConf> = Vec::new();
 
     for merged_iface in merged_ifaces
@@ -218,3 +244,45 @@ async fn apply_ifaces_ip_changes(
 
     Ok(())
 }
+
+/// Ethtool settings should be applied after interface created
+async fn apply_ifaces_ethtool(
+    merged_ifaces: &MergedInterfaces,
+) -> Result<(), NipartError> {
+    for merged_iface in merged_ifaces.kernel_ifaces.values() {
+        if let Some(apply_iface) = merged_iface.for_apply.as_ref() {
+            apply_iface_ethtool(apply_iface.base_iface()).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Sysctl settings should be applied after interface created
+fn apply_ifaces_sysctl(
+    merged_ifaces: &MergedInterfaces,
+) -> Result<(), NipartError> {
+    for merged_iface in merged_ifaces.kernel_ifaces.values() {
+        if let Some(apply_iface) = merged_iface.for_apply.as_ref() {
+            apply_iface_sysctl(
+                apply_iface.base_iface(),
+                merged_iface.current.as_ref().map(|c| c.base_iface()),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+async fn apply_ifaces_ipv6_token(
+    merged_ifaces: &MergedInterfaces,
+) -> Result<(), NipartError> {
+    for merged_iface in merged_ifaces.kernel_ifaces.values() {
+        if let Some(apply_iface) = merged_iface.for_apply.as_ref() {
+            apply_iface_ipv6_token(
+                apply_iface.base_iface(),
+                merged_iface.current.as_ref().map(|c| c.base_iface()),
+            )
+            .await?;
+        }
+    }
+    Ok(())
+}
@@ -103,6 +103,14 @@ pub(crate) fn np_ipv6_to_nmstate(
                         } else {
                             None
                         },
+                        temporary: if np_addr
+                            .flags
+                            .contains(&nispor::Ipv6AddrFlag::Temporary)
+                        {
+                            Some(true)
+                        } else {
+                            None
+                        },
                         ..Default::default()
                     };
                     addresses.push(addr);
@@ -146,6 +154,7 @@ pub(crate) fn apply_iface_ip_changes(
 
     if des_iface.ipv4.as_ref() != cur_iface.ipv4.as_ref()
         && let Some(des_ipv4) = des_iface.ipv4.as_ref()
+        && !is_ipv4_addrs_preserved(des_ipv4, cur_iface.ipv4.as_ref())
     {
         let mut des_addrs: &[InterfaceIpAddr] = &[];
         if des_ipv4.is_enabled()
@@ -154,15 +163,21 @@ pub(crate) fn apply_iface_ip_changes(
             des_addrs = d;
         }
 
-        let mut cur_addrs: &[InterfaceIpAddr] = &[];
+        let mut cur_addrs: Vec<InterfaceIpAddr> = Vec::new();
         if let Some(cur_ipv4) = cur_iface.ipv4.as_ref() {
             if cur_ipv4.is_enabled()
                 && let Some(c) = cur_ipv4.addresses.as_ref()
             {
-                cur_addrs = c;
+                cur_addrs = c.clone();
             }
         }
-        let np_addrs = nmstate_ip_addrs_to_nispor(des_addrs, cur_addrs);
+        // The link-local address is managed by link-local client
+        if des_ipv4.is_link_local_enabled() || des_ipv4.is_link_local_fallback()
+        {
+            cur_addrs.retain(|a| !a.is_ipv4_link_local());
+        }
+        let np_addrs =
+            nmstate_ip_addrs_to_nispor(des_addrs, cur_addrs.as_slice());
 
         if !np_addrs.is_empty() {
             let mut np_ip_conf = nispor::IpConf::default();
@@ -173,6 +188,7 @@ pub(crate) fn apply_iface_ip_changes(
 
     if des_iface.ipv6.as_ref() != cur_iface.ipv6.as_ref()
         && let Some(des_ipv6) = des_iface.ipv6.as_ref()
+        && !is_ipv6_addrs_preserved(des_ipv6, cur_iface.ipv6.as_ref())
     {
         let mut des_addrs: &[InterfaceIpAddr] = &[];
         if des_ipv6.is_enabled()
@@ -205,7 +221,29 @@ pub(crate) fn apply_iface_ip_changes(
     }
 }
 
-fn nmstate_ip_addr_to_nispor(
+// Undefined addresses means preserving current IP addresses, unless IP
+// method changed, e.g. static to DHCP requires flushing static addresses.
+fn is_ipv4_addrs_preserved(
+    des_ipv4: &InterfaceIpv4,
+    cur_ipv4: Option<&InterfaceIpv4>,
+) -> bool {
+    des_ipv4.is_enabled()
+        && des_ipv4.addresses.is_none()
+        && (des_ipv4.dhcp.is_none()
+            || cur_ipv4.is_some_and(|c| c.is_auto()) == des_ipv4.is_auto())
+}
+
+fn is_ipv6_addrs_preserved(
+    des_ipv6: &InterfaceIpv6,
+    cur_ipv6: Option<&InterfaceIpv6>,
+) -> bool {
+    des_ipv6.is_enabled()
+        && des_ipv6.addresses.is_none()
+        && ((des_ipv6.dhcp.is_none() && des_ipv6.autoconf.is_none())
+            || cur_ipv6.is_some_and(|c| c.is_auto()) == des_ipv6.is_auto())
+}
+
+pub(crate) fn nmstate_ip_addr_to_nispor(
     ip_addr: &InterfaceIpAddr,
     remove: bool,
 ) -> nispor::IpAddrConf {
@@ -0,0 +1,100 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// IPv4 address conflict detection defined in RFC 5227.
+
+use std::{
+    net::{IpAddr, Ipv4Addr},
+    time::Duration,
+};
+
+use super::{
+    arp::{ArpSocket, mac_to_string},
+    netlink::get_iface_index_mac,
+};
+use crate::{BaseInterface, ErrorKind, NipartError};
+
+const PROBE_NUM: u32 = 3;
+
+/// Probe new static IPv4 addresses of specified interface when
+/// `ipv4.dad-timeout` is set, fail with [ErrorKind::VerificationError] if any
+/// of them is in use by other host.
+pub(crate) async fn ipv4_dad(
+    des_iface: &BaseInterface,
+    cur_iface: Option<&BaseInterface>,
+) -> Result<(), NipartError> {
+    if des_iface.is_absent() || !des_iface.state.is_up() {
+        return Ok(());
+    }
+    let Some(des_ipv4) = des_iface.ipv4.as_ref().filter(|i| i.is_enabled())
+    else {
+        return Ok(());
+    };
+    let timeout = match des_ipv4.dad_timeout {
+        Some(t) if t > 0 => Duration::from_millis(t.into()),
+        _ => return Ok(()),
+    };
+
+    let cur_ips: Vec<IpAddr> = cur_iface
+        .and_then(|c| c.ipv4.as_ref())
+        .filter(|i| i.is_enabled())
+        .and_then(|i| i.addresses.as_ref())
+        .map(|addrs| addrs.iter().map(|a| a.ip).collect())
+        .unwrap_or_default();
+    let new_ips: Vec<Ipv4Addr> = des_ipv4
+        .addresses
+        .as_deref()
+        .unwrap_or_default()
+        .iter()
+        .filter(|a| !a.is_auto() && !cur_ips.contains(&a.ip))
+        .filter_map(|a| match a.ip {
+            IpAddr::V4(ip) => Some(ip),
+            IpAddr::V6(_) => None,
+        })
+        .collect();
+    if new_ips.is_empty() {
+        return Ok(());
+    }
+
+    let (iface_index, Some(mac)) =
+        get_iface_index_mac(des_iface.name.as_str()).await?
+    else {
+        log::info!(
+            "Skipping IPv4 address conflict detection on interface {} as it \
+             has no ethernet MAC address",
+            des_iface.name
+        );
+        return Ok(());
+    };
+
+    log::info!(
+        "Probing IPv4 addresses {} on interface {}",
+        new_ips
+            .iter()
+            .map(|ip| ip.to_string())
+            .collect::<Vec<String>>()
+            .join(", "),
+        des_iface.name
+    );
+    let socket = ArpSocket::new(iface_index, mac)?;
+    let interval = timeout / PROBE_NUM;
+    for _ in 0..PROBE_NUM {
+        for ip in new_ips.iter() {
+            socket.send_probe(*ip)?;
+        }
+        if let Some((ip, other_mac)) = socket
+            .wait_any_conflict(new_ips.as_slice(), interval)
+            .await?
+        {
+            return Err(NipartError::new(
+                ErrorKind::VerificationError,
+                format!(
+                    "IPv4 address {ip} of interface {} is already in use by \
+                     host {}",
+                    des_iface.name,
+                    mac_to_string(&other_mac)
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,273 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// IPv4 link-local address(169.254.0.0/16) defined in RFC 3927.
+
+use std::{net::Ipv4Addr, time::Duration};
+
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+use super::{
+    arp::ArpSocket, iface::init_np_iface, ip::nmstate_ip_addr_to_nispor,
+    netlink::get_iface_index_mac,
+};
+use crate::{
+    BaseInterface, ErrorKind, InterfaceIpAddr, MergedInterfaces, NipartError,
+    NipartNoDaemon, NipartstateInterface,
+};
+
+const IPV4LL_PREFIX_LEN: u8 = 16;
+// RFC 3927: The first 256 and last 256 addresses are reserved
+const IPV4LL_FIRST_HOST: u32 = 0xa9fe_0100; // 169.254.1.0
+const IPV4LL_LAST_HOST: u32 = 0xa9fe_feff; // 169.254.254.255
+
+const PROBE_WAIT_MS: u64 = 1000;
+const PROBE_NUM: usize = 3;
+const PROBE_MIN_MS: u64 = 1000;
+const PROBE_MAX_MS: u64 = 2000;
+const ANNOUNCE_WAIT: Duration = Duration::from_secs(2);
+const ANNOUNCE_NUM: usize = 2;
+const ANNOUNCE_INTERVAL: Duration = Duration::from_secs(2);
+const MAX_CONFLICTS: usize = 10;
+const RATE_LIMIT_INTERVAL: Duration = Duration::from_secs(60);
+const DEFEND_INTERVAL: Duration = Duration::from_secs(10);
+
+/// IPv4 link-local address(169.254.0.0/16) client implementing RFC 3927
+/// probing, announcing and defending.
+#[derive(Debug)]
+pub struct NipartIpv4LinkLocal {
+    iface_name: String,
+    socket: ArpSocket,
+    rng: StdRng,
+    conflict_count: usize,
+}
+
+impl NipartIpv4LinkLocal {
+    /// Create IPv4 link-local client, the interface index and MAC address
+    /// are queried from kernel as interface might be just created.
+    pub async fn new(iface_name: &str) -> Result<Self, NipartError> {
+        let (iface_index, Some(mac)) = get_iface_index_mac(iface_name).await?
+        else {
+            return Err(NipartError::new(
+                ErrorKind::InvalidArgument,
+                format!(
+                    "Interface {iface_name} has no ethernet MAC address for \
+                     IPv4 link-local"
+                ),
+            ));
+        };
+        // RFC 3927: Use MAC address as seed, so the same host will get the
+        // same address after reboot.
+        let mut seed = [0u8; 8];
+        seed[..mac.len()].copy_from_slice(&mac);
+        Ok(Self {
+            iface_name: iface_name.to_string(),
+            socket: ArpSocket::new(iface_index, mac)?,
+            rng: StdRng::seed_from_u64(u64::from_be_bytes(seed)),
+            conflict_count: 0,
+        })
+    }
+
+    fn pick_addr(&mut self) -> Ipv4Addr {
+        Ipv4Addr::from(
+            self.rng.random_range(IPV4LL_FIRST_HOST..=IPV4LL_LAST_HOST),
+        )
+    }
+
+    /// Probe and announce a free link-local address, return the claimed
+    /// address. This function does not assign the address to interface,
+    /// please use [NipartIpv4LinkLocal::assign()].
+    pub async fn acquire(&mut self) -> Result<Ipv4Addr, NipartError> {
+        loop {
+            let ip = self.pick_addr();
+            if self.conflict_count >= MAX_CONFLICTS {
+                log::info!(
+                    "IPv4 link-local on {} got {} conflicts, rate limiting",
+                    self.iface_name,
+                    self.conflict_count
+                );
+                tokio::time::sleep(RATE_LIMIT_INTERVAL).await;
+            }
+            if self.probe(ip).await? {
+                self.conflict_count += 1;
+                log::info!(
+                    "IPv4 link-local address {ip} is in use on {}",
+                    self.iface_name
+                );
+                continue;
+            }
+            self.announce(ip).await?;
+            log::info!(
+                "IPv4 link-local on {} claimed address {ip}",
+                self.iface_name
+            );
+            return Ok(ip);
+        }
+    }
+
+    /// Return true if other host is using or probing the address.
+    async fn probe(&mut self, ip: Ipv4Addr) -> Result<bool, NipartError> {
+        log::debug!("Probing IPv4 link-local {ip} on {}", self.iface_name);
+        let wait =
+            Duration::from_millis(self.rng.random_range(0..PROBE_WAIT_MS));
+        if self.socket.wait_conflict(ip, wait).await? {
+            return Ok(true);
+        }
+        for i in 0..PROBE_NUM {
+            self.socket.send_probe(ip)?;
+            let interval = if i == PROBE_NUM - 1 {
+                ANNOUNCE_WAIT
+            } else {
+                Duration::from_millis(
+                    self.rng.random_range(PROBE_MIN_MS..=PROBE_MAX_MS),
+                )
+            };
+            if self.socket.wait_conflict(ip, interval).await? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    async fn announce(&self, ip: Ipv4Addr) -> Result<(), NipartError> {
+        for i in 0..ANNOUNCE_NUM {
+            self.socket.send_announce(ip)?;
+            if i != ANNOUNCE_NUM - 1 {
+                tokio::time::sleep(ANNOUNCE_INTERVAL).await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Wait till other host claims specified address.
+    pub async fn wait_conflict(&self, ip: Ipv4Addr) -> Result<(), NipartError> {
+        loop {
+            let pkt = self.socket.recv().await?;
+            if pkt.is_claimed_by_other(ip, self.socket.mac()) {
+                log::info!(
+                    "IPv4 link-local address {ip} on {} is claimed by other \
+                     host",
+                    self.iface_name
+                );
+                return Ok(());
+            }
+        }
+    }
+
+    /// Defend claimed address following RFC 3927 section 2.5: announce the
+    /// address on first conflict, return when another conflict happens
+    /// within 10 seconds, caller should then release the address and
+    /// acquire a new one.
+    pub async fn defend(&self, ip: Ipv4Addr) -> Result<(), NipartError> {
+        let mut last_conflict: Option<tokio::time::Instant> = None;
+        loop {
+            self.wait_conflict(ip).await?;
+            let now = tokio::time::Instant::now();
+            if last_conflict.is_some_and(|t| now - t < DEFEND_INTERVAL) {
+                log::info!(
+                    "IPv4 link-local address {ip} on {} conflicts again \
+                     within {} seconds, giving up",
+                    self.iface_name,
+                    DEFEND_INTERVAL.as_secs()
+                );
+                return Ok(());
+            }
+            log::info!(
+                "Defending IPv4 link-local address {ip} on {}",
+                self.iface_name
+            );
+            self.socket.send_announce(ip)?;
+            last_conflict = Some(now);
+        }
+    }
+
+    /// Add link-local address to interface without touching other IP
+    /// addresses.
+    pub async fn assign(
+        base_iface: &BaseInterface,
+        ip: Ipv4Addr,
+    ) -> Result<(), NipartError> {
+        change_addr(base_iface, ip, false).await
+    }
+
+    /// Remove link-local address from interface.
+    pub async fn release(
+        base_iface: &BaseInterface,
+        ip: Ipv4Addr,
+    ) -> Result<(), NipartError> {
+        change_addr(base_iface, ip, true).await
+    }
+}
+
+impl NipartNoDaemon {
+    /// Acquire and assign IPv4 link-local address for interfaces with
+    /// `link-local: enabled`. The address will not be defended after
+    /// assigned.
+    pub(crate) async fn run_ipv4_link_local_once(
+        merged_ifaces: &MergedInterfaces,
+    ) -> Result<(), NipartError> {
+        for merged_iface in merged_ifaces.kernel_ifaces.values().filter(|i| {
+            i.for_apply
+                .as_ref()
+                .and_then(|i| i.base_iface().ipv4.as_ref())
+                .map(|i| i.is_link_local_enabled())
+                == Some(true)
+        }) {
+            let base_iface = merged_iface.merged.base_iface();
+            // The merged state holds desired addresses, only current state
+            // tells whether link-local address is already assigned.
+            if merged_iface
+                .current
+                .as_ref()
+                .and_then(|i| i.base_iface().ipv4.as_ref())
+                .and_then(|i| i.addresses.as_ref())
+                .map(|addrs| addrs.iter().any(|a| a.is_ipv4_link_local()))
+                == Some(true)
+            {
+                log::debug!(
+                    "Interface {} already has IPv4 link-local address",
+                    base_iface.name
+                );
+                continue;
+            }
+            run_ipv4_link_local(base_iface).await?;
+        }
+        Ok(())
+    }
+}
+
+pub(crate) async fn run_ipv4_link_local(
+    base_iface: &BaseInterface,
+) -> Result<(), NipartError> {
+    NipartNoDaemon::wait_link_carrier_up(base_iface.name.as_str()).await?;
+    let mut ipv4ll = NipartIpv4LinkLocal::new(base_iface.name.as_str()).await?;
+    let ip = ipv4ll.acquire().await?;
+    NipartIpv4LinkLocal::assign(base_iface, ip).await
+}
+
+async fn change_addr(
+    base_iface: &BaseInterface,
+    ip: Ipv4Addr,
+    remove: bool,
+) -> Result<(), NipartError> {
+    let mut np_iface = init_np_iface(&base_iface.clone_name_type_only());
+    let mut np_ip_conf = nispor::IpConf::default();
+    np_ip_conf.addresses = vec![nmstate_ip_addr_to_nispor(
+        &InterfaceIpAddr::new(ip.into(), IPV4LL_PREFIX_LEN),
+        remove,
+    )];
+    np_iface.ipv4 = Some(np_ip_conf);
+
+    let mut net_conf = nispor::NetConf::default();
+    net_conf.ifaces = Some(vec![np_iface]);
+    net_conf.apply_async().await.map_err(|e| {
+        NipartError::new(
+            ErrorKind::Bug,
+            format!(
+                "Failed to {} IPv4 link-local address {ip} on interface {}: \
+                 {e}",
+                if remove { "remove" } else { "add" },
+                base_iface.name
+            ),
+        )
+    })
+}
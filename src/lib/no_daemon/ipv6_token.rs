@@ -0,0 +1,112 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{collections::HashMap, net::Ipv6Addr};
+
+use futures_util::stream::TryStreamExt;
+use rtnetlink::{
+    LinkUnspec,
+    packet_route::link::{AfSpecInet6, AfSpecUnspec, LinkAttribute},
+};
+
+use super::netlink::new_netlink_handle;
+use crate::{
+    ErrorKind, Interfaces, NipartError, NipartstateInterface,
+    nmstate::BaseInterface,
+};
+
+async fn get_ipv6_tokens() -> Result<HashMap<String, Ipv6Addr>, NipartError> {
+    let handle = new_netlink_handle()?;
+    let mut ret = HashMap::new();
+    let mut links = handle.link().get().execute();
+    while let Some(link_msg) = links.try_next().await.map_err(|e| {
+        NipartError::new(
+            ErrorKind::Bug,
+            format!("Failed to query IPv6 token from netlink: {e}"),
+        )
+    })? {
+        let mut iface_name = None;
+        let mut token = None;
+        for attr in link_msg.attributes {
+            match attr {
+                LinkAttribute::IfName(n) => iface_name = Some(n),
+                LinkAttribute::AfSpecUnspec(af_specs) => {
+                    for af_spec in af_specs {
+                        if let AfSpecUnspec::Inet6(inet6_attrs) = af_spec {
+                            for inet6_attr in inet6_attrs {
+                                if let AfSpecInet6::Token(t) = inet6_attr {
+                                    token = Some(t);
+                                }
+                            }
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+        // Kernel use `::` for token not set
+        if let (Some(iface_name), Some(token)) = (iface_name, token)
+            && !token.is_unspecified()
+        {
+            ret.insert(iface_name, token);
+        }
+    }
+    Ok(ret)
+}
+
+pub(crate) async fn fill_ipv6_token(
+    ifaces: &mut Interfaces,
+) -> Result<(), NipartError> {
+    let tokens = get_ipv6_tokens().await?;
+    for iface in ifaces.kernel_ifaces.values_mut() {
+        if let Some(token) = tokens.get(iface.name())
+            && let Some(ipv6) = iface.base_iface_mut().ipv6.as_mut()
+        {
+            ipv6.token = Some(*token);
+        }
+    }
+    Ok(())
+}
+
+pub(crate) async fn apply_iface_ipv6_token(
+    des_iface: &BaseInterface,
+    cur_iface: Option<&BaseInterface>,
+) -> Result<(), NipartError> {
+    if des_iface.is_absent() {
+        return Ok(());
+    }
+    let Some(des_token) = des_iface
+        .ipv6
+        .as_ref()
+        .filter(|i| i.is_enabled())
+        .and_then(|i| i.token)
+    else {
+        return Ok(());
+    };
+    let cur_token = cur_iface
+        .and_then(|c| c.ipv6.as_ref())
+        .and_then(|i| i.token)
+        .unwrap_or(Ipv6Addr::UNSPECIFIED);
+    if des_token == cur_token {
+        return Ok(());
+    }
+
+    log::debug!(
+        "Setting IPv6 token of interface {} to {des_token}",
+        des_iface.name
+    );
+    let handle = new_netlink_handle()?;
+    let mut link_msg =
+        LinkUnspec::new_with_name(des_iface.name.as_str()).build();
+    link_msg.attributes.push(LinkAttribute::AfSpecUnspec(vec![
+        AfSpecUnspec::Inet6(vec![AfSpecInet6::Token(des_token)]),
+    ]));
+    handle.link().set(link_msg).execute().await.map_err(|e| {
+        NipartError::new(
+            ErrorKind::Bug,
+            format!(
+                "Failed to set IPv6 token {des_token} on interface {}: {e}",
+                des_iface.name
+            ),
+        )
+    })
+}
@@ -1,21 +1,30 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod apply;
+mod arp;
 mod base_iface;
 mod bond;
 mod dhcp;
 mod ethernet;
+mod ethtool;
 mod iface;
 mod inter_ifaces;
 mod ip;
+mod ipv4_dad;
+mod ipv4ll;
+mod ipv6_token;
 mod linux_bridge;
 mod linux_bridge_vlan;
+mod netlink;
 mod ovs;
 mod query;
 mod route;
+mod sysctl;
 mod vlan;
 mod watcher;
 mod wifi;
 
+pub use self::ipv4ll::NipartIpv4LinkLocal;
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub struct NipartNoDaemon {}
@@ -0,0 +1,75 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Shared helpers for talking to kernel via rtnetlink directly for things
+// nispor does not cover.
+
+use futures_util::stream::TryStreamExt;
+use rtnetlink::{new_connection, packet_route::link::LinkAttribute};
+
+use super::arp::mac_to_string;
+use crate::{ErrorKind, NipartError, NipartNoDaemon};
+
+pub(crate) const ETH_ALEN: usize = 6;
+
+pub(crate) fn new_netlink_handle() -> Result<rtnetlink::Handle, NipartError> {
+    let (conn, handle, _) = new_connection().map_err(|e| {
+        NipartError::new(
+            ErrorKind::Bug,
+            format!("Failed to create netlink connection: {e}"),
+        )
+    })?;
+    tokio::spawn(conn);
+    Ok(handle)
+}
+
+/// Return interface index and ethernet MAC address.
+/// The MAC address is None if interface has no ethernet MAC address.
+pub(crate) async fn get_iface_index_mac(
+    iface_name: &str,
+) -> Result<(u32, Option<[u8; ETH_ALEN]>), NipartError> {
+    let handle = new_netlink_handle()?;
+    let mut links = handle
+        .link()
+        .get()
+        .match_name(iface_name.to_string())
+        .execute();
+    let link_msg = match links.try_next().await {
+        Ok(Some(l)) => l,
+        Ok(None) => {
+            return Err(NipartError::new(
+                ErrorKind::Bug,
+                format!("Interface {iface_name} not found"),
+            ));
+        }
+        Err(e) => {
+            return Err(NipartError::new(
+                ErrorKind::Bug,
+                format!("Failed to query interface {iface_name}: {e}"),
+            ));
+        }
+    };
+    for attr in link_msg.attributes {
+        if let LinkAttribute::Address(addr) = attr
+            && addr.len() == ETH_ALEN
+            && addr.iter().any(|b| *b != 0)
+        {
+            let mut mac = [0u8; ETH_ALEN];
+            mac.copy_from_slice(addr.as_slice());
+            return Ok((link_msg.header.index, Some(mac)));
+        }
+    }
+    Ok((link_msg.header.index, None))
+}
+
+impl NipartNoDaemon {
+    /// Query interface index and ethernet MAC address from kernel.
+    /// The MAC address is None if interface has no ethernet MAC address.
+    /// Useful for interfaces created in the same apply, their merged state
+    /// holds neither.
+    pub async fn query_iface_index_mac(
+        iface_name: &str,
+    ) -> Result<(u32, Option<String>), NipartError> {
+        let (iface_index, mac) = get_iface_index_mac(iface_name).await?;
+        Ok((iface_index, mac.as_ref().map(mac_to_string)))
+    }
+}
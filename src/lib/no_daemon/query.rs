@@ -1,8 +1,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::{
-    base_iface::np_iface_to_base_iface, ovs::NipartOvsDb, route::get_routes,
-    wifi::NipartWpaConn,
+    base_iface::np_iface_to_base_iface, ethtool::fill_ethtool,
+    ipv6_token::fill_ipv6_token, ovs::NipartOvsDb, route::get_routes,
+    sysctl::get_ip_forwarding, wifi::NipartWpaConn,
 };
 use crate::{
     BondInterface, DummyInterface, ErrorKind, EthernetInterface, Interface,
@@ -126,6 +127,16 @@ impl NipartNoDaemon {
             NipartOvsDb::fill_ovs_cfg(&mut net_state).await?;
         }
 
+        // Optional sections, failure should not fail the whole query
+        if let Err(e) = fill_ipv6_token(&mut net_state.ifaces).await {
+            log::warn!("Failed to query IPv6 token: {e}");
+        }
+        if let Err(e) = fill_ethtool(&mut net_state.ifaces).await {
+            log::warn!("Failed to query ethtool settings: {e}");
+        }
+
+        net_state.ip_forwarding = Some(get_ip_forwarding());
+
         net_state.routes = get_routes(&net_state.ifaces).await;
 
         net_state
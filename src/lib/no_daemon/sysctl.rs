@@ -0,0 +1,240 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{net::Ipv6Addr, str::FromStr};
+
+use crate::{
+    ErrorKind, IpForwarding, NipartError,
+    nmstate::{
+        BaseInterface, InterfaceIpv4, InterfaceIpv6, Ipv6AcceptRa,
+        Ipv6AddrGenMode, Ipv6UseTempaddr, RpFilterMode,
+    },
+};
+
+const SYSCTL_IPV4_CONF_DIR: &str = "/proc/sys/net/ipv4/conf";
+const SYSCTL_IPV6_CONF_DIR: &str = "/proc/sys/net/ipv6/conf";
+const SYSCTL_IPV4_FORWARD: &str = "/proc/sys/net/ipv4/ip_forward";
+const SYSCTL_IPV6_FORWARD: &str = "/proc/sys/net/ipv6/conf/all/forwarding";
+
+fn read_sysctl(path: &str) -> Option<i64> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => match content.trim().parse::<i64>() {
+            Ok(v) => Some(v),
+            Err(e) => {
+                log::trace!("Invalid sysctl value in {path}: {e}");
+                None
+            }
+        },
+        Err(e) => {
+            log::trace!("Failed to read {path}: {e}");
+            None
+        }
+    }
+}
+
+fn write_sysctl(path: &str, value: i64) -> Result<(), NipartError> {
+    log::debug!("Setting sysctl {path} to {value}");
+    std::fs::write(path, format!("{value}\n")).map_err(|e| {
+        NipartError::new(
+            if e.kind() == std::io::ErrorKind::PermissionDenied {
+                ErrorKind::PermissionDeny
+            } else {
+                ErrorKind::Bug
+            },
+            format!("Failed to write {value} to {path}: {e}"),
+        )
+    })
+}
+
+fn read_sysctl_ipv6_addr(path: &str) -> Option<Ipv6Addr> {
+    // Kernel fail with EIO when reading unset `stable_secret`
+    let content = match std::fs::read_to_string(path) {
+        Ok(c) => c,
+        Err(e) => {
+            log::trace!("Failed to read {path}: {e}");
+            return None;
+        }
+    };
+    match Ipv6Addr::from_str(content.trim()) {
+        Ok(ip) => Some(ip),
+        Err(e) => {
+            log::debug!("Ignoring {path}: {e}");
+            None
+        }
+    }
+}
+
+fn write_sysctl_str(path: &str, value: &str) -> Result<(), NipartError> {
+    std::fs::write(path, format!("{value}\n")).map_err(|e| {
+        NipartError::new(
+            if e.kind() == std::io::ErrorKind::PermissionDenied {
+                ErrorKind::PermissionDeny
+            } else {
+                ErrorKind::Bug
+            },
+            format!("Failed to write {path}: {e}"),
+        )
+    })
+}
+
+fn read_sysctl_enum<T>(path: &str) -> Option<T>
+where
+    T: TryFrom<i64, Error = NipartError>,
+{
+    let v = read_sysctl(path)?;
+    match T::try_from(v) {
+        Ok(v) => Some(v),
+        Err(e) => {
+            log::debug!("Ignoring {path}: {e}");
+            None
+        }
+    }
+}
+
+fn read_sysctl_u8(path: &str) -> Option<u8> {
+    read_sysctl(path).and_then(|v| u8::try_from(v).ok())
+}
+
+fn read_sysctl_bool(path: &str) -> Option<bool> {
+    read_sysctl(path).map(|v| v > 0)
+}
+
+/// Fill sysctl based IP settings into `ipv4` and `ipv6` sections.
+pub(crate) fn fill_ip_sysctl(base_iface: &mut BaseInterface) {
+    let iface_name = base_iface.name.as_str();
+    if let Some(ipv4) = base_iface.ipv4.as_mut() {
+        let dir = format!("{SYSCTL_IPV4_CONF_DIR}/{iface_name}");
+        ipv4.forwarding = read_sysctl_bool(&format!("{dir}/forwarding"));
+        ipv4.rp_filter = read_sysctl_enum(&format!("{dir}/rp_filter"));
+        ipv4.arp_ignore = read_sysctl_u8(&format!("{dir}/arp_ignore"));
+        ipv4.arp_announce = read_sysctl_u8(&format!("{dir}/arp_announce"));
+    }
+    if let Some(ipv6) = base_iface.ipv6.as_mut() {
+        let dir = format!("{SYSCTL_IPV6_CONF_DIR}/{iface_name}");
+        ipv6.forwarding = read_sysctl_bool(&format!("{dir}/forwarding"));
+        ipv6.accept_ra = read_sysctl_enum(&format!("{dir}/accept_ra"));
+        ipv6.use_tempaddr = read_sysctl_enum(&format!("{dir}/use_tempaddr"));
+        ipv6.addr_gen_mode = read_sysctl_enum(&format!("{dir}/addr_gen_mode"));
+        ipv6.stable_secret =
+            read_sysctl_ipv6_addr(&format!("{dir}/stable_secret"))
+                .map(|ip| ip.to_string());
+    }
+}
+
+/// Write changed sysctl based IP settings of specified interface.
+pub(crate) fn apply_iface_sysctl(
+    des_iface: &BaseInterface,
+    cur_iface: Option<&BaseInterface>,
+) -> Result<(), NipartError> {
+    if des_iface.is_absent() {
+        return Ok(());
+    }
+    let iface_name = des_iface.name.as_str();
+
+    if let Some(des_ipv4) = des_iface.ipv4.as_ref() {
+        apply_ipv4_sysctl(
+            iface_name,
+            des_ipv4,
+            cur_iface.and_then(|c| c.ipv4.as_ref()),
+        )?;
+    }
+    if let Some(des_ipv6) = des_iface.ipv6.as_ref() {
+        apply_ipv6_sysctl(
+            iface_name,
+            des_ipv6,
+            cur_iface.and_then(|c| c.ipv6.as_ref()),
+        )?;
+    }
+    Ok(())
+}
+
+fn apply_ipv4_sysctl(
+    iface_name: &str,
+    des: &InterfaceIpv4,
+    cur: Option<&InterfaceIpv4>,
+) -> Result<(), NipartError> {
+    let dir = format!("{SYSCTL_IPV4_CONF_DIR}/{iface_name}");
+    if let Some(v) = des.forwarding
+        && cur.and_then(|c| c.forwarding) != Some(v)
+    {
+        write_sysctl(&format!("{dir}/forwarding"), v.into())?;
+    }
+    if let Some(v) = des.rp_filter
+        && cur.and_then(|c| c.rp_filter) != Some(v)
+    {
+        write_sysctl(&format!("{dir}/rp_filter"), u8::from(v).into())?;
+    }
+    if let Some(v) = des.arp_ignore
+        && cur.and_then(|c| c.arp_ignore) != Some(v)
+    {
+        write_sysctl(&format!("{dir}/arp_ignore"), v.into())?;
+    }
+    if let Some(v) = des.arp_announce
+        && cur.and_then(|c| c.arp_announce) != Some(v)
+    {
+        write_sysctl(&format!("{dir}/arp_announce"), v.into())?;
+    }
+    Ok(())
+}
+
+fn apply_ipv6_sysctl(
+    iface_name: &str,
+    des: &InterfaceIpv6,
+    cur: Option<&InterfaceIpv6>,
+) -> Result<(), NipartError> {
+    let dir = format!("{SYSCTL_IPV6_CONF_DIR}/{iface_name}");
+    if let Some(v) = des.forwarding
+        && cur.and_then(|c| c.forwarding) != Some(v)
+    {
+        write_sysctl(&format!("{dir}/forwarding"), v.into())?;
+    }
+    if let Some(v) = des.accept_ra
+        && cur.and_then(|c| c.accept_ra) != Some(v)
+    {
+        write_sysctl(&format!("{dir}/accept_ra"), u8::from(v).into())?;
+    }
+    if let Some(v) = des.use_tempaddr
+        && cur.and_then(|c| c.use_tempaddr) != Some(v)
+    {
+        write_sysctl(&format!("{dir}/use_tempaddr"), u8::from(v).into())?;
+    }
+    if let Some(v) = des.addr_gen_mode
+        && cur.and_then(|c| c.addr_gen_mode) != Some(v)
+    {
+        write_sysctl(&format!("{dir}/addr_gen_mode"), u8::from(v).into())?;
+    }
+    if let Some(v) = des.stable_secret.as_deref()
+        && v != crate::NetworkState::HIDE_PASSWORD_STR
+        && cur
+            .and_then(|c| c.stable_secret.as_deref())
+            .and_then(|c| Ipv6Addr::from_str(c).ok())
+            != Ipv6Addr::from_str(v).ok()
+    {
+        log::debug!("Setting sysctl {dir}/stable_secret");
+        write_sysctl_str(&format!("{dir}/stable_secret"), v)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn get_ip_forwarding() -> IpForwarding {
+    IpForwarding {
+        ipv4: read_sysctl_bool(SYSCTL_IPV4_FORWARD),
+        ipv6: read_sysctl_bool(SYSCTL_IPV6_FORWARD),
+    }
+}
+
+pub(crate) fn apply_ip_forwarding(
+    desired: &IpForwarding,
+) -> Result<(), NipartError> {
+    let current = get_ip_forwarding();
+    if let Some(v) = desired.ipv4
+        && current.ipv4 != Some(v)
+    {
+        write_sysctl(SYSCTL_IPV4_FORWARD, v.into())?;
+    }
+    if let Some(v) = desired.ipv6
+        && current.ipv6 != Some(v)
+    {
+        write_sysctl(SYSCTL_IPV6_FORWARD, v.into())?;
+    }
+    Ok(())
+}
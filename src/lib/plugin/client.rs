@@ -1,11 +1,12 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::UnboundedSender;
 
 use crate::{
     JsonDisplayHideSecrets, NetworkState, NipartCanIpc, NipartError,
-    NipartIpcConnection, NipartPluginInfo, NipartstateApplyOption,
-    NipartstateQueryOption,
+    NipartEvent, NipartIpcConnection, NipartLogEntry, NipartPluginInfo,
+    NipartstateApplyOption, NipartstateQueryOption,
 };
 
 #[derive(Debug)]
@@ -25,7 +26,19 @@ pub enum NipartPluginCmd {
     /// Query network state, should reply with [NetworkState]
     QueryNetworkState(Box<NipartstateQueryOption>),
     ApplyNetworkState(Box<(NetworkState, NipartstateApplyOption)>),
+    /// Validate desired state before daemon merging it, should reply with
+    /// `()` or error of [crate::ErrorKind::InvalidArgument] to reject it.
+    ValidateNetworkState(Box<NetworkState>),
+    /// Merged state about to apply, should reply with `()` or error of
+    /// [crate::ErrorKind::InvalidArgument] to reject it.
+    PreApply(Box<NetworkState>),
+    /// Merged state applied with its result, should reply with `()`.
+    PostApply(Box<(NetworkState, Result<(), NipartError>)>),
+    /// Request plugin to quit, no reply expected
     Quit,
+    /// Notify plugin on daemon event, no reply expected. Only sent to
+    /// plugin with [crate::NipartPluginCapabilities::events] set.
+    Event(Box<NipartEvent>),
 }
 
 impl NipartCanIpc for NipartPluginCmd {
@@ -34,15 +47,26 @@ impl NipartCanIpc for NipartPluginCmd {
             Self::QueryPluginInfo => "query-plugin-info".to_string(),
             Self::QueryNetworkState(_) => "query-network-state".to_string(),
             Self::ApplyNetworkState(_) => "apply-network-state".to_string(),
+            Self::ValidateNetworkState(_) => {
+                "validate-network-state".to_string()
+            }
+            Self::PreApply(_) => "pre-apply".to_string(),
+            Self::PostApply(_) => "post-apply".to_string(),
             Self::Quit => "quit".to_string(),
+            Self::Event(_) => "event".to_string(),
         }
     }
 }
 
 impl NipartPluginCmd {
     pub fn hide_secrets(&mut self) {
-        if let Self::ApplyNetworkState(cmd) = self {
-            cmd.0.hide_secrets();
+        match self {
+            Self::ApplyNetworkState(cmd) => cmd.0.hide_secrets(),
+            Self::ValidateNetworkState(state) | Self::PreApply(state) => {
+                state.hide_secrets()
+            }
+            Self::PostApply(cmd) => cmd.0.hide_secrets(),
+            _ => (),
         }
     }
 }
@@ -50,6 +74,9 @@ impl NipartPluginCmd {
 impl NipartPluginClient {
     pub const DEFAULT_SOCKET_DIR: &'static str =
         "/var/run/nipart/sockets/plugin";
+    /// Environment variable set by daemon when starting plugin to override
+    /// [NipartPluginClient::DEFAULT_SOCKET_DIR].
+    pub const SOCKET_DIR_ENV: &'static str = "NIPART_PLUGIN_SOCKET_DIR";
 
     /// Create IPC connect from daemon to plugin
     pub async fn new(socket_path: &str) -> Result<Self, NipartError> {
@@ -67,6 +94,19 @@ impl NipartPluginClient {
         })
     }
 
+    /// Set timeout in milliseconds on waiting reply from plugin.
+    pub fn set_timeout(&mut self, timeout_ms: u32) {
+        self.ipc.set_timeout(timeout_ms);
+    }
+
+    /// Forward log entries sent by plugin to specified sender.
+    pub fn set_log_forwarder(
+        &mut self,
+        sender: Option<UnboundedSender<NipartLogEntry>>,
+    ) {
+        self.ipc.set_log_forwarder(sender);
+    }
+
     pub async fn query_plugin_info(
         &mut self,
     ) -> Result<NipartPluginInfo, NipartError> {
@@ -98,6 +138,57 @@ impl NipartPluginClient {
         self.ipc.recv::<()>().await
     }
 
+    pub async fn validate_network_state(
+        &mut self,
+        desired_state: NetworkState,
+    ) -> Result<(), NipartError> {
+        self.ipc
+            .send(Ok(NipartPluginCmd::ValidateNetworkState(Box::new(
+                desired_state,
+            ))))
+            .await?;
+        self.ipc.recv::<()>().await
+    }
+
+    pub async fn pre_apply(
+        &mut self,
+        merged_state: NetworkState,
+    ) -> Result<(), NipartError> {
+        self.ipc
+            .send(Ok(NipartPluginCmd::PreApply(Box::new(merged_state))))
+            .await?;
+        self.ipc.recv::<()>().await
+    }
+
+    pub async fn post_apply(
+        &mut self,
+        applied_state: NetworkState,
+        result: Result<(), NipartError>,
+    ) -> Result<(), NipartError> {
+        self.ipc
+            .send(Ok(NipartPluginCmd::PostApply(Box::new((
+                applied_state,
+                result,
+            )))))
+            .await?;
+        self.ipc.recv::<()>().await
+    }
+
+    /// Request plugin to quit without waiting reply
+    pub async fn quit(&mut self) -> Result<(), NipartError> {
+        self.ipc.send(Ok(NipartPluginCmd::Quit)).await
+    }
+
+    /// Notify plugin on daemon event without waiting reply
+    pub async fn notify_event(
+        &mut self,
+        event: NipartEvent,
+    ) -> Result<(), NipartError> {
+        self.ipc
+            .send(Ok(NipartPluginCmd::Event(Box::new(event))))
+            .await
+    }
+
     pub async fn send<T>(
         &mut self,
         data: Result<T, NipartError>,
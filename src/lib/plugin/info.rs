@@ -11,9 +11,25 @@ pub struct NipartPluginInfo {
     pub name: String,
     pub version: String,
     pub iface_types: Vec<InterfaceType>,
+    /// Plugins are queried and merged in ascending order of priority, then
+    /// name. State of later plugin overrides earlier ones. Default to 0.
+    #[serde(default)]
+    pub priority: u32,
+    /// Plugin protocol version implemented by plugin, daemon refuses plugin
+    /// not in range of [NipartPluginInfo::MIN_PROTOCOL_VERSION] and
+    /// [NipartPluginInfo::PROTOCOL_VERSION].
+    #[serde(default)]
+    pub protocol_version: u32,
+    #[serde(default)]
+    pub capabilities: NipartPluginCapabilities,
 }
 
 impl NipartPluginInfo {
+    /// Plugin protocol version implemented by this library.
+    pub const PROTOCOL_VERSION: u32 = 1;
+    /// Minimum plugin protocol version supported by daemon.
+    pub const MIN_PROTOCOL_VERSION: u32 = 1;
+
     pub fn new(
         name: String,
         version: String,
@@ -23,8 +39,16 @@ impl NipartPluginInfo {
             name,
             version,
             iface_types,
+            priority: 0,
+            protocol_version: Self::PROTOCOL_VERSION,
+            capabilities: Default::default(),
         }
     }
+
+    pub fn is_protocol_supported(&self) -> bool {
+        (Self::MIN_PROTOCOL_VERSION..=Self::PROTOCOL_VERSION)
+            .contains(&self.protocol_version)
+    }
 }
 
 impl NipartCanIpc for NipartPluginInfo {
@@ -32,3 +56,54 @@ impl NipartCanIpc for NipartPluginInfo {
         "nm-plugin-info".to_string()
     }
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case", default)]
+#[non_exhaustive]
+pub struct NipartPluginCapabilities {
+    /// Commands supported by plugin besides `query-plugin-info` and `quit`.
+    /// Default to `query-network-state` and `apply-network-state`.
+    pub commands: Vec<NipartPluginCmdKind>,
+    /// Top level sections of network state owned by plugin, besides the
+    /// interfaces of [NipartPluginInfo::iface_types].
+    pub sections: Vec<NipartstateSection>,
+    /// Whether plugin wants to be notified on daemon events via
+    /// [crate::NipartPlugin::on_event()].
+    pub events: bool,
+}
+
+impl Default for NipartPluginCapabilities {
+    fn default() -> Self {
+        Self {
+            commands: vec![
+                NipartPluginCmdKind::QueryNetworkState,
+                NipartPluginCmdKind::ApplyNetworkState,
+            ],
+            sections: Vec::new(),
+            events: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum NipartPluginCmdKind {
+    QueryNetworkState,
+    ApplyNetworkState,
+    /// Opt-in for [crate::NipartPlugin::validate_network_state()]
+    ValidateNetworkState,
+    /// Opt-in for [crate::NipartPlugin::pre_apply()]
+    PreApply,
+    /// Opt-in for [crate::NipartPlugin::post_apply()]
+    PostApply,
+}
+
+/// Top level sections of network state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum NipartstateSection {
+    Routes,
+    IpForwarding,
+}
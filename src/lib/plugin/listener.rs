@@ -46,6 +46,29 @@ impl NipartIpcListener {
         })
     }
 
+    /// Create from listening socket inherited from others, e.g. systemd
+    /// socket activation.
+    pub fn from_std(
+        path: &str,
+        listener: std::os::unix::net::UnixListener,
+    ) -> Result<Self, NipartError> {
+        listener.set_nonblocking(true).map_err(|e| {
+            NipartError::new(
+                ErrorKind::IpcFailure,
+                format!("Failed to set {path} as non-blocking: {e}"),
+            )
+        })?;
+        Ok(Self {
+            path: path.to_string(),
+            socket: UnixListener::from_std(listener).map_err(|e| {
+                NipartError::new(
+                    ErrorKind::IpcFailure,
+                    format!("Failed to use inherited socket {path}: {e}"),
+                )
+            })?,
+        })
+    }
+
     pub async fn accept(&self) -> Result<NipartIpcConnection, NipartError> {
         let (stream, _) = self.socket.accept().await.map_err(|e| {
             NipartError::new(
@@ -7,7 +7,10 @@ mod plugin_trait;
 
 pub use self::{
     client::{NipartPluginClient, NipartPluginCmd},
-    info::NipartPluginInfo,
+    info::{
+        NipartPluginCapabilities, NipartPluginCmdKind, NipartPluginInfo,
+        NipartstateSection,
+    },
     listener::NipartIpcListener,
     plugin_trait::NipartPlugin,
 };
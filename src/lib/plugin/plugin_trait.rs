@@ -3,7 +3,7 @@
 use std::sync::Arc;
 
 use crate::{
-    ErrorKind, NetworkState, NipartError, NipartIpcConnection,
+    ErrorKind, NetworkState, NipartError, NipartEvent, NipartIpcConnection,
     NipartIpcListener, NipartPluginClient, NipartPluginCmd, NipartPluginInfo,
     NipartstateApplyOption, NipartstateQueryOption,
 };
@@ -40,11 +40,11 @@ pub trait NipartPlugin: Send + Sync + Sized + 'static {
         async {
             let plugin = Arc::new(Self::init().await?);
 
-            let socket_path = format!(
-                "{}/{}",
-                NipartPluginClient::DEFAULT_SOCKET_DIR,
-                Self::PLUGIN_NAME
-            );
+            let socket_dir = std::env::var(NipartPluginClient::SOCKET_DIR_ENV)
+                .unwrap_or_else(|_| {
+                    NipartPluginClient::DEFAULT_SOCKET_DIR.to_string()
+                });
+            let socket_path = format!("{socket_dir}/{}", Self::PLUGIN_NAME);
             let ipc = NipartIpcListener::new(&socket_path)?;
             log::debug!("Listening on {socket_path}");
 
@@ -75,6 +75,9 @@ pub trait NipartPlugin: Send + Sync + Sized + 'static {
                     NipartPluginCmd::Quit => {
                         Self::quit(&plugin).await;
                     }
+                    NipartPluginCmd::Event(event) => {
+                        Self::on_event(&plugin, *event).await;
+                    }
                     NipartPluginCmd::QueryNetworkState(opt) => {
                         let result =
                             Self::query_network_state(&plugin, *opt, &mut conn)
@@ -92,6 +95,32 @@ pub trait NipartPlugin: Send + Sync + Sized + 'static {
                         .await;
                         conn.send(result).await?
                     }
+                    NipartPluginCmd::ValidateNetworkState(desired_state) => {
+                        let result = Self::validate_network_state(
+                            &plugin,
+                            *desired_state,
+                            &mut conn,
+                        )
+                        .await;
+                        conn.send(result).await?
+                    }
+                    NipartPluginCmd::PreApply(merged_state) => {
+                        let result =
+                            Self::pre_apply(&plugin, *merged_state, &mut conn)
+                                .await;
+                        conn.send(result).await?
+                    }
+                    NipartPluginCmd::PostApply(v) => {
+                        let (applied_state, apply_result) = *v;
+                        let result = Self::post_apply(
+                            &plugin,
+                            applied_state,
+                            apply_result,
+                            &mut conn,
+                        )
+                        .await;
+                        conn.send(result).await?
+                    }
                 }
             }
         }
@@ -134,4 +163,56 @@ pub trait NipartPlugin: Send + Sync + Sized + 'static {
             ))
         }
     }
+
+    /// Validate full desired state before daemon merging it with saved state.
+    /// Return error of [ErrorKind::InvalidArgument] to reject the desired
+    /// state before any change happens.
+    /// Only invoked when [crate::NipartPluginCmdKind::ValidateNetworkState]
+    /// is included in [crate::NipartPluginCapabilities::commands].
+    /// Default implementation is accepting any state.
+    fn validate_network_state(
+        _plugin: &Arc<Self>,
+        _desired_state: NetworkState,
+        _conn: &mut NipartIpcConnection,
+    ) -> impl Future<Output = Result<(), NipartError>> + Send {
+        async { Ok(()) }
+    }
+
+    /// Invoked with full merged state right before daemon applying it.
+    /// Return error of [ErrorKind::InvalidArgument] to reject the merged
+    /// state before any change happens.
+    /// Only invoked when [crate::NipartPluginCmdKind::PreApply] is included
+    /// in [crate::NipartPluginCapabilities::commands].
+    /// Default implementation is doing nothing.
+    fn pre_apply(
+        _plugin: &Arc<Self>,
+        _merged_state: NetworkState,
+        _conn: &mut NipartIpcConnection,
+    ) -> impl Future<Output = Result<(), NipartError>> + Send {
+        async { Ok(()) }
+    }
+
+    /// Invoked with full merged state and the apply result after daemon
+    /// applied it (or rolled back on failure). Error returned is only logged.
+    /// Only invoked when [crate::NipartPluginCmdKind::PostApply] is included
+    /// in [crate::NipartPluginCapabilities::commands].
+    /// Default implementation is doing nothing.
+    fn post_apply(
+        _plugin: &Arc<Self>,
+        _applied_state: NetworkState,
+        _result: Result<(), NipartError>,
+        _conn: &mut NipartIpcConnection,
+    ) -> impl Future<Output = Result<(), NipartError>> + Send {
+        async { Ok(()) }
+    }
+
+    /// Invoked on daemon event when [crate::NipartPluginCapabilities::events]
+    /// is set in [NipartPlugin::plugin_info()].
+    /// Default implementation is doing nothing.
+    fn on_event(
+        _plugin: &Arc<Self>,
+        _event: NipartEvent,
+    ) -> impl Future<Output = ()> + Send {
+        async {}
+    }
 }
@@ -0,0 +1,90 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use serde::{Deserialize, Serialize};
+
+use crate::JsonDisplay;
+
+/// Option for [crate::NipartClient::wait_online()]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonDisplay)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub struct NipartWaitOnlineOption {
+    /// Condition to consider network as online, default:
+    /// [NipartWaitOnlineCondition::AllSaved]
+    #[serde(default)]
+    pub condition: NipartWaitOnlineCondition,
+    /// Seconds to wait before failing with
+    /// [crate::ErrorKind::Timeout], default:
+    /// [NipartWaitOnlineOption::DEFAULT_TIMEOUT]
+    #[serde(default = "default_timeout")]
+    pub timeout: u32,
+}
+
+fn default_timeout() -> u32 {
+    NipartWaitOnlineOption::DEFAULT_TIMEOUT
+}
+
+impl Default for NipartWaitOnlineOption {
+    fn default() -> Self {
+        Self {
+            condition: NipartWaitOnlineCondition::default(),
+            timeout: Self::DEFAULT_TIMEOUT,
+        }
+    }
+}
+
+impl NipartWaitOnlineOption {
+    pub const DEFAULT_TIMEOUT: u32 = 30;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn condition(mut self, condition: NipartWaitOnlineCondition) -> Self {
+        self.condition = condition;
+        self
+    }
+
+    pub fn timeout(mut self, seconds: u32) -> Self {
+        self.timeout = seconds;
+        self
+    }
+}
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize, JsonDisplay,
+)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum NipartWaitOnlineCondition {
+    /// All interfaces in saved state with `state: up` have link carrier and
+    /// got IP addresses for enabled IP stacks.
+    #[default]
+    AllSaved,
+    /// Any interface has IPv4 or IPv6 default route.
+    DefaultRoute,
+    /// All specified interfaces have link carrier and got required IP
+    /// addresses.
+    Interfaces(Vec<NipartWaitOnlineIface>),
+}
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize, JsonDisplay,
+)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub struct NipartWaitOnlineIface {
+    pub name: String,
+    /// Require IPv4 address, default to false.
+    #[serde(default)]
+    pub ipv4: bool,
+    /// Require IPv6 address other than link-local, default to false.
+    #[serde(default)]
+    pub ipv6: bool,
+}
+
+impl NipartWaitOnlineIface {
+    pub fn new(name: String, ipv4: bool, ipv6: bool) -> Self {
+        Self { name, ipv4, ipv6 }
+    }
+}
@@ -5,8 +5,16 @@ import struct
 import socket
 
 from .cmd import NipartCmdApplyNetworkState
+from .cmd import NipartCmdCommit
 from .cmd import NipartCmdPing
+from .cmd import NipartCmdQueryDaemonConf
+from .cmd import NipartCmdQueryDrift
+from .cmd import NipartCmdQueryHistory
 from .cmd import NipartCmdQueryNetworkState
+from .cmd import NipartCmdRestoreGeneration
+from .cmd import NipartCmdRollback
+from .cmd import NipartCmdSetLogLevel
+from .cmd import NipartCmdWaitOnline
 from .error import NipartError
 from .log import NipartLogEntry
 from .nmstate.state_option import NipartstateApplyOption
@@ -68,3 +76,57 @@ class NipartClient:
         if not opt:
             opt = NipartstateApplyOption()
         return self._conn.exec(NipartCmdApplyNetworkState(desired_state, opt))
+
+    # Apply the specified generation of saved state, return the changed
+    # state.
+    def restore_generation(self, generation):
+        return self._conn.exec(NipartCmdRestoreGeneration(generation))
+
+    def commit(self):
+        return self._conn.exec(NipartCmdCommit())
+
+    def rollback(self):
+        return self._conn.exec(NipartCmdRollback())
+
+    def query_daemon_conf(self):
+        return self._conn.exec(NipartCmdQueryDaemonConf())
+
+    # Return how running network state drifted from saved state with
+    # `missing`, `unmanaged`, `unmanaged-routes` and `changed` sections.
+    def query_drift(self):
+        return self._conn.exec(NipartCmdQueryDrift())
+
+    # Return apply transactions oldest first, or only the one matching
+    # `transaction_id` in a list.
+    def query_history(self, transaction_id=None):
+        return self._conn.exec(NipartCmdQueryHistory(transaction_id))
+
+    # Set maximum level(off, error, warn, info, debug or trace) of log entries
+    # daemon and plugins send back to this client.
+    def set_log_level(self, level):
+        return self._conn.exec(NipartCmdSetLogLevel(level))
+
+    # Wait all interfaces in saved state to be online by default.
+    # When `default_route` is True, wait any default route.
+    # When `ifaces` is a list of interface names, wait these interfaces to be
+    # online with IPv4/IPv6 address if `ipv4`/`ipv6` is True.
+    def wait_online(
+        self,
+        ifaces=None,
+        ipv4=False,
+        ipv6=False,
+        default_route=False,
+        timeout=None,
+    ):
+        if default_route:
+            condition = "default-route"
+        elif ifaces:
+            condition = {
+                "interfaces": [
+                    {"name": iface, "ipv4": ipv4, "ipv6": ipv6}
+                    for iface in ifaces
+                ]
+            }
+        else:
+            condition = "all-saved"
+        return self._conn.exec(NipartCmdWaitOnline(condition, timeout))
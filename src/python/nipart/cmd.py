@@ -52,3 +52,115 @@ class NipartCmdApplyNetworkState:
                 },
             }
         )
+
+
+class NipartCmdCommit:
+    IPC_KIND = "commit"
+
+    def to_json(self):
+        return json.dumps(
+            {
+                "kind": NipartCmdCommit.IPC_KIND,
+                "data": NipartCmdCommit.IPC_KIND,
+            }
+        )
+
+
+class NipartCmdRollback:
+    IPC_KIND = "rollback"
+
+    def to_json(self):
+        return json.dumps(
+            {
+                "kind": NipartCmdRollback.IPC_KIND,
+                "data": NipartCmdRollback.IPC_KIND,
+            }
+        )
+
+
+class NipartCmdQueryDaemonConf:
+    IPC_KIND = "query-daemon-conf"
+
+    def to_json(self):
+        return json.dumps(
+            {
+                "kind": NipartCmdQueryDaemonConf.IPC_KIND,
+                "data": NipartCmdQueryDaemonConf.IPC_KIND,
+            }
+        )
+
+
+class NipartCmdWaitOnline:
+    IPC_KIND = "wait-online"
+
+    def __init__(self, condition, timeout=None):
+        self.condition = condition
+        self.timeout = timeout
+
+    def to_json(self):
+        opt = {"condition": self.condition}
+        if self.timeout is not None:
+            opt["timeout"] = self.timeout
+        return json.dumps(
+            {
+                "kind": NipartCmdWaitOnline.IPC_KIND,
+                "data": {NipartCmdWaitOnline.IPC_KIND: opt},
+            }
+        )
+
+
+class NipartCmdSetLogLevel:
+    IPC_KIND = "set-log-level"
+
+    def __init__(self, level):
+        self.level = level
+
+    def to_json(self):
+        return json.dumps(
+            {
+                "kind": NipartCmdSetLogLevel.IPC_KIND,
+                "data": {NipartCmdSetLogLevel.IPC_KIND: self.level},
+            }
+        )
+
+
+class NipartCmdQueryHistory:
+    IPC_KIND = "query-history"
+
+    def __init__(self, transaction_id=None):
+        self.transaction_id = transaction_id
+
+    def to_json(self):
+        return json.dumps(
+            {
+                "kind": NipartCmdQueryHistory.IPC_KIND,
+                "data": {NipartCmdQueryHistory.IPC_KIND: self.transaction_id},
+            }
+        )
+
+
+class NipartCmdRestoreGeneration:
+    IPC_KIND = "restore-generation"
+
+    def __init__(self, generation):
+        self.generation = generation
+
+    def to_json(self):
+        return json.dumps(
+            {
+                "kind": NipartCmdRestoreGeneration.IPC_KIND,
+                "data": {NipartCmdRestoreGeneration.IPC_KIND: self.generation},
+            }
+        )
+
+
+class NipartCmdQueryDrift:
+    IPC_KIND = "query-drift"
+
+    def to_json(self):
+        return json.dumps(
+            {
+                "kind": NipartCmdQueryDrift.IPC_KIND,
+                "data": NipartCmdQueryDrift.IPC_KIND,
+            }
+        )
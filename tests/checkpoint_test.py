@@ -0,0 +1,123 @@
+# SPDX-License-Identifier: Apache-2.0
+
+import os
+import time
+
+import pytest
+import yaml
+
+from nipart import NipartClient
+from nipart import NipartError
+from nipart import NipartstateApplyOption
+
+from .testlib.cmdlib import exec_cmd
+from .testlib.retry import retry_till_true_or_timeout
+from .testlib.statelib import load_yaml
+from .testlib.statelib import show_only
+from .testlib.statelib import show_saved_only
+
+TEST_IFACE = "dummy1"
+TEST_IFACE2 = "dummy2"
+APPLIED_STATE_PATH = "/etc/nipart/states/internal/applied.yml"
+
+
+@pytest.fixture
+def cleanup_dummy1():
+    yield
+    exec_cmd(f"ip link del {TEST_IFACE}".split(), check=False)
+
+
+@pytest.fixture
+def cleanup_dummy1_dummy2(cleanup_dummy1):
+    yield
+    NipartClient().apply_network_state(
+        load_yaml(
+            f"""---
+            interfaces:
+              - name: {TEST_IFACE}
+                type: dummy
+                state: absent
+              - name: {TEST_IFACE2}
+                type: dummy
+                state: absent
+            """
+        ),
+        NipartstateApplyOption(),
+    )
+
+
+def _apply_dummy1_no_commit(timeout=None):
+    cli = NipartClient()
+    cli.apply_network_state(
+        load_yaml(
+            f"""---
+            interfaces:
+              - name: {TEST_IFACE}
+                type: dummy
+                state: up
+            """
+        ),
+        NipartstateApplyOption(commit=False, rollback_timeout=timeout),
+    )
+
+
+def test_checkpoint_commit(cleanup_dummy1):
+    _apply_dummy1_no_commit()
+    assert show_only(TEST_IFACE)
+    assert not show_saved_only(TEST_IFACE)
+
+    NipartClient().commit()
+    assert show_saved_only(TEST_IFACE)
+
+
+def test_checkpoint_rollback(cleanup_dummy1):
+    _apply_dummy1_no_commit()
+    assert show_only(TEST_IFACE)
+
+    NipartClient().rollback()
+    assert not show_only(TEST_IFACE)
+    assert not show_saved_only(TEST_IFACE)
+
+
+def test_checkpoint_auto_rollback(cleanup_dummy1):
+    _apply_dummy1_no_commit(timeout=2)
+    assert show_only(TEST_IFACE)
+
+    time.sleep(5)
+    assert not show_only(TEST_IFACE)
+    with pytest.raises(NipartError):
+        NipartClient().commit()
+
+
+def test_checkpoint_refuse_apply_when_pending(cleanup_dummy1):
+    _apply_dummy1_no_commit()
+    with pytest.raises(NipartError):
+        _apply_dummy1_no_commit()
+    NipartClient().rollback()
+
+
+def _save_dummy2_internally():
+    state = {}
+    if os.path.exists(APPLIED_STATE_PATH):
+        with open(APPLIED_STATE_PATH) as fd:
+            state = load_yaml(fd.read()) or {}
+    state.setdefault("interfaces", []).append(
+        {"name": TEST_IFACE2, "type": "dummy", "state": "up"}
+    )
+    with open(APPLIED_STATE_PATH, "w") as fd:
+        fd.write(yaml.dump(state))
+    # SIGHUP reloads saved state and applies it without client peer
+    exec_cmd("pkill -HUP -x nipartd".split())
+    assert retry_till_true_or_timeout(10, show_only, TEST_IFACE2)
+
+
+def test_checkpoint_commit_keep_state_saved_after_checkpoint(
+    cleanup_dummy1_dummy2,
+):
+    _apply_dummy1_no_commit()
+    _save_dummy2_internally()
+    assert show_saved_only(TEST_IFACE2)
+
+    NipartClient().commit()
+    assert show_saved_only(TEST_IFACE)
+    assert show_saved_only(TEST_IFACE2)
@@ -1,5 +1,6 @@
 # SPDX-License-Identifier: Apache-2.0
 
+import os
 import pathlib
 import subprocess
 import sys
@@ -8,6 +9,12 @@ import time
 import pytest
 
 from .testlib.cmdlib import exec_cmd
+from .testlib.dbus import DBUS_CONF_PATH
+from .testlib.dbus import DBUS_SOCKET_PATH
+from .testlib.dbus import dbus_env
+from .testlib.dbus import disable_dbus_in_daemon_conf
+from .testlib.dbus import enable_dbus_in_daemon_conf
+from .testlib.dbus import has_dbus_daemon
 from .testlib.retry import retry_till_true_or_timeout
 
 project_dir = pathlib.Path(__file__).parent.parent.resolve()
@@ -25,10 +32,13 @@ def test_env_setup(run_daemon):
 
 
 @pytest.fixture(scope="session")
-def run_daemon():
+def run_daemon(private_dbus):
     bin_path = pathlib.Path(f"{project_dir}/target/debug/nipartd").resolve()
     process = subprocess.Popen(
-        bin_path, stdout=sys.stdout, stderr=open(DAEMON_LOG, "w")
+        bin_path,
+        stdout=sys.stdout,
+        stderr=open(DAEMON_LOG, "w"),
+        env=dbus_env() if private_dbus else None,
     )
     # Wait daemon to start up
     time.sleep(1)
@@ -38,6 +48,25 @@ def run_daemon():
         process.terminate()
 
 
+# Private session bus for D-Bus API tests, not started if `dbus-daemon` not
+# installed.
+@pytest.fixture(scope="session")
+def private_dbus():
+    if not has_dbus_daemon():
+        yield False
+        return
+    if os.path.exists(DBUS_SOCKET_PATH):
+        os.unlink(DBUS_SOCKET_PATH)
+    process = subprocess.Popen(
+        ["dbus-daemon", f"--config-file={DBUS_CONF_PATH}", "--nofork"]
+    )
+    retry_till_true_or_timeout(10, lambda: os.path.exists(DBUS_SOCKET_PATH))
+    enable_dbus_in_daemon_conf()
+    yield True
+    disable_dbus_in_daemon_conf()
+    process.terminate()
+
+
 def check_daemon_connection():
     try:
         client = NipartClient()
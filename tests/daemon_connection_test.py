@@ -1,8 +1,32 @@
 # SPDX-License-Identifier: Apache-2.0
 
+import logging
+
 from nipart import NipartClient
 
 
 def test_daemon_conn_ping():
     client = NipartClient()
     assert client.ping() == "pong"
+
+
+def test_daemon_conn_query_daemon_conf():
+    conf = NipartClient().query_daemon_conf()
+    assert conf["api"]["socket-path"] == "/var/run/nipart/sockets/daemon"
+    assert conf["bootup"]["nic-check-max-count"] > 0
+
+
+def test_daemon_conn_set_log_level(caplog):
+    client = NipartClient()
+    client.set_log_level("off")
+    with caplog.at_level(logging.DEBUG, logger="libnmstate"):
+        client.query_network_state()
+    assert not caplog.records
+
+    client.set_log_level("debug")
+    with caplog.at_level(logging.DEBUG, logger="libnmstate"):
+        client.query_network_state()
+    assert any(
+        "querying network state" in record.message
+        for record in caplog.records
+    )
@@ -0,0 +1,95 @@
+# SPDX-License-Identifier: Apache-2.0
+
+import json
+import os
+
+import pytest
+
+from .testlib.cmdlib import exec_cmd
+from .testlib.dbus import NOBODY_UID
+from .testlib.dbus import dbus_call
+from .testlib.dbus import disable_dbus_in_daemon_conf
+from .testlib.dbus import enable_dbus_in_daemon_conf
+from .testlib.dbus import has_dbus_daemon
+from .testlib.retry import retry_till_true_or_timeout
+
+pytestmark = pytest.mark.skipif(
+    not has_dbus_daemon(), reason="dbus-daemon not installed"
+)
+
+
+TEST_IFACE = "dummy1"
+
+
+def test_dbus_ping():
+    assert dbus_call("Ping")[1] == "pong"
+
+
+def test_dbus_ping_non_root():
+    assert dbus_call("Ping", uid=NOBODY_UID)[1] == "pong"
+
+
+DUMMY_STATE = json.dumps(
+    {"interfaces": [{"name": TEST_IFACE, "type": "dummy", "state": "up"}]}
+)
+
+
+def test_dbus_non_root_dry_run_apply_refused():
+    rc, _, _ = dbus_call(
+        "ApplyNetworkState",
+        "ss",
+        [DUMMY_STATE, json.dumps({"dry-run": True})],
+        uid=NOBODY_UID,
+        check=False,
+    )
+    assert rc != 0
+    assert not os.path.exists(f"/sys/class/net/{TEST_IFACE}")
+
+
+def test_dbus_non_root_plan():
+    plan = json.loads(
+        dbus_call("PlanNetworkState", "ss", [DUMMY_STATE, ""], uid=NOBODY_UID)[
+            1
+        ]
+    )
+    assert plan["interfaces"][0]["name"] == TEST_IFACE
+    assert plan["interfaces"][0]["action"] == "create"
+    assert not os.path.exists(f"/sys/class/net/{TEST_IFACE}")
+
+
+def test_dbus_non_root_query_history_refused():
+    rc, _, _ = dbus_call(
+        "QueryHistory", "s", [""], uid=NOBODY_UID, check=False
+    )
+    assert rc != 0
+
+
+def test_dbus_non_root_wifi_scan_refused():
+    rc, _, stderr = dbus_call(
+        "WifiScan", "s", [""], uid=NOBODY_UID, check=False
+    )
+    assert rc != 0
+    assert "root permission" in stderr
+
+
+def test_dbus_wifi_scan_non_wifi_iface():
+    rc, _, stderr = dbus_call("WifiScan", "s", ["lo"], check=False)
+    assert rc != 0
+    assert "root permission" not in stderr
+
+
+def is_dbus_service_up():
+    return dbus_call("Ping", check=False)[0] == 0
+
+
+def test_dbus_service_follow_daemon_conf_reload():
+    try:
+        disable_dbus_in_daemon_conf()
+        exec_cmd("pkill -HUP -x nipartd".split())
+        assert retry_till_true_or_timeout(
+            10, lambda: not is_dbus_service_up()
+        )
+    finally:
+        enable_dbus_in_daemon_conf()
+        exec_cmd("pkill -HUP -x nipartd".split())
+    assert retry_till_true_or_timeout(10, is_dbus_service_up)
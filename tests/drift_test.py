@@ -0,0 +1,68 @@
+# SPDX-License-Identifier: Apache-2.0
+
+import pytest
+
+import nipart
+from nipart import NipartClient
+
+from .testlib.cmdlib import exec_cmd
+from .testlib.statelib import load_yaml
+
+
+TEST_NIC = "dummy1"
+TEST_UNMANAGED_NIC = "dummy2"
+
+
+@pytest.fixture
+def dummy1_with_ip():
+    nipart.apply(
+        load_yaml(
+            f"""---
+            interfaces:
+              - name: {TEST_NIC}
+                type: dummy
+                state: up
+                ipv4:
+                  enabled: true
+                  dhcp: false
+                  address:
+                  - ip: 192.0.2.1
+                    prefix-length: 24
+            """
+        )
+    )
+    yield
+    exec_cmd(f"ip link del {TEST_UNMANAGED_NIC}".split(), check=False)
+    nipart.apply(
+        load_yaml(
+            f"""---
+            interfaces:
+              - name: {TEST_NIC}
+                type: dummy
+                state: absent
+            """
+        )
+    )
+
+
+def _changed_iface(report, iface_name):
+    for iface in report.get("changed", {}).get("interfaces", []):
+        if iface["name"] == iface_name:
+            return iface
+
+
+def test_drift_state(dummy1_with_ip):
+    exec_cmd(f"ip addr del 192.0.2.1/24 dev {TEST_NIC}".split())
+    exec_cmd(f"ip link add {TEST_UNMANAGED_NIC} type dummy".split())
+
+    report = NipartClient().query_drift()
+    iface = _changed_iface(report, TEST_NIC)
+    assert iface["ipv4"]["address"][0]["ip"] == "192.0.2.1"
+    unmanaged = {"name": TEST_UNMANAGED_NIC, "type": "dummy"}
+    assert unmanaged in report["unmanaged"]
+    assert "lo" not in [i["name"] for i in report["unmanaged"]]
+
+    exec_cmd(f"ip link del {TEST_NIC}".split())
+    report = NipartClient().query_drift()
+    assert {"name": TEST_NIC, "type": "dummy"} in report["missing"]
+    assert not _changed_iface(report, TEST_NIC)
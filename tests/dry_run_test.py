@@ -0,0 +1,47 @@
+# SPDX-License-Identifier: Apache-2.0
+
+import pytest
+
+from nipart import NipartClient
+from nipart import NipartstateApplyOption
+
+from .testlib.cmdlib import exec_cmd
+from .testlib.env import npt_path
+from .testlib.statelib import load_yaml
+from .testlib.statelib import show_only
+
+TEST_IFACE = "dummy1"
+
+DUMMY1_STATE = f"""---
+interfaces:
+  - name: {TEST_IFACE}
+    type: dummy
+    state: up
+"""
+
+
+@pytest.fixture
+def cleanup_dummy1():
+    yield
+    exec_cmd(f"ip link del {TEST_IFACE}".split(), check=False)
+
+
+def test_dry_run_create_iface(cleanup_dummy1):
+    plan = NipartClient().apply_network_state(
+        load_yaml(DUMMY1_STATE),
+        NipartstateApplyOption(dry_run=True),
+    )
+
+    assert plan["interfaces"][0]["name"] == TEST_IFACE
+    assert plan["interfaces"][0]["action"] == "create"
+    assert not show_only(TEST_IFACE)
+
+
+def test_cli_dry_run(cleanup_dummy1, tmp_path):
+    state_file = tmp_path / "dummy1.yml"
+    state_file.write_text(DUMMY1_STATE)
+
+    output = exec_cmd(f"{npt_path()} apply --dry-run {state_file}".split())[1]
+
+    assert "action: create" in output
+    assert not show_only(TEST_IFACE)
@@ -0,0 +1,111 @@
+# SPDX-License-Identifier: Apache-2.0
+
+import os
+
+import pytest
+
+from nipart import NipartClient
+from nipart import NipartError
+
+from .testlib.cmdlib import exec_cmd
+from .testlib.env import npt_path
+from .testlib.statelib import load_yaml
+
+TEST_IFACE = "dummy1"
+STABLE_SECRET = "2001:db8:1:2:3:4:5:6"
+
+
+@pytest.fixture
+def cleanup_dummy1():
+    yield
+    exec_cmd(f"ip link del {TEST_IFACE}".split(), check=False)
+
+
+def _apply_dummy1(state):
+    NipartClient().apply_network_state(
+        load_yaml(
+            f"""---
+            interfaces:
+              - name: {TEST_IFACE}
+                type: dummy
+                state: {state}
+            """
+        )
+    )
+
+
+def test_history_record_apply(cleanup_dummy1):
+    _apply_dummy1("up")
+
+    entry = NipartClient().query_history()[-1]
+
+    assert entry["outcome"] == "succeeded"
+    assert entry["peer-pid"] == os.getpid()
+    assert entry["peer-uid"] == os.getuid()
+    assert entry["desired-state"]["interfaces"][0]["name"] == TEST_IFACE
+    assert entry["diff-state"]["interfaces"][0]["name"] == TEST_IFACE
+
+    shown = NipartClient().query_history(entry["id"])
+    assert shown == [entry]
+
+
+def test_history_record_failure():
+    with pytest.raises(NipartError):
+        NipartClient().apply_network_state(
+            load_yaml(
+                """---
+                interfaces:
+                  - name: eth1.101
+                    type: vlan
+                    state: up
+                    vlan:
+                      base-iface: not-exist
+                      id: 101
+                """
+            )
+        )
+
+    entry = NipartClient().query_history()[-1]
+    assert entry["outcome"] == "failed"
+    assert "error" in entry
+    assert "diff-state" not in entry
+
+
+def test_history_failure_with_secret_root_only():
+    with pytest.raises(NipartError):
+        NipartClient().apply_network_state(
+            load_yaml(
+                f"""---
+                interfaces:
+                  - name: eth1.101
+                    type: vlan
+                    state: up
+                    vlan:
+                      base-iface: not-exist
+                      id: 101
+                    ipv6:
+                      enabled: true
+                      stable-secret: {STABLE_SECRET}
+                """
+            )
+        )
+
+    entry = NipartClient().query_history()[-1]
+    assert entry["outcome"] == "failed"
+    assert STABLE_SECRET not in str(entry["desired-state"])
+
+    state_dir = NipartClient().query_daemon_conf()["state-dir"]
+    mode = os.stat(f"{state_dir}/history/{entry['id']}.yml").st_mode
+    assert mode & 0o777 == 0o600
+
+
+def test_cli_history(cleanup_dummy1):
+    _apply_dummy1("up")
+    transaction_id = NipartClient().query_history()[-1]["id"]
+
+    output = exec_cmd(f"{npt_path()} history".split())[1]
+    assert transaction_id in output
+
+    output = exec_cmd(f"{npt_path()} history show {transaction_id}".split())[1]
+    assert f"id: {transaction_id}" in output
+    assert TEST_IFACE in output
@@ -0,0 +1,101 @@
+# SPDX-License-Identifier: Apache-2.0
+
+import pytest
+
+from nipart import NipartClient
+
+from .testlib.cmdlib import exec_cmd
+from .testlib.statelib import load_yaml
+
+TEST_IFACE = "dummy1"
+IPV4_ADDR = "192.0.2.251"
+IPV6_ADDR = "2001:db8:1::251"
+
+
+@pytest.fixture
+def static_dummy1():
+    NipartClient().apply_network_state(
+        load_yaml(
+            f"""---
+            interfaces:
+              - name: {TEST_IFACE}
+                type: dummy
+                state: up
+                ipv4:
+                  enabled: true
+                  dhcp: false
+                  address:
+                    - ip: {IPV4_ADDR}
+                      prefix-length: 24
+                ipv6:
+                  enabled: true
+                  dhcp: false
+                  autoconf: false
+                  address:
+                    - ip: {IPV6_ADDR}
+                      prefix-length: 64
+            """
+        )
+    )
+    yield
+    NipartClient().apply_network_state(
+        load_yaml(
+            f"""---
+            interfaces:
+              - name: {TEST_IFACE}
+                type: dummy
+                state: absent
+            """
+        )
+    )
+
+
+def _kernel_addrs():
+    return exec_cmd(f"ip addr show dev {TEST_IFACE}".split())[1]
+
+
+def test_static_to_dhcp_flush_static_addresses(static_dummy1):
+    NipartClient().apply_network_state(
+        load_yaml(
+            f"""---
+            interfaces:
+              - name: {TEST_IFACE}
+                type: dummy
+                state: up
+                ipv4:
+                  enabled: true
+                  dhcp: true
+                ipv6:
+                  enabled: true
+                  dhcp: true
+                  autoconf: true
+            """
+        )
+    )
+
+    output = _kernel_addrs()
+    assert IPV4_ADDR not in output
+    assert IPV6_ADDR not in output
+
+
+def test_sysctl_only_change_preserve_static_addresses(static_dummy1):
+    NipartClient().apply_network_state(
+        load_yaml(
+            f"""---
+            interfaces:
+              - name: {TEST_IFACE}
+                type: dummy
+                state: up
+                ipv4:
+                  enabled: true
+                  forwarding: true
+                ipv6:
+                  enabled: true
+                  forwarding: true
+            """
+        )
+    )
+
+    output = _kernel_addrs()
+    assert IPV4_ADDR in output
+    assert IPV6_ADDR in output
@@ -0,0 +1,41 @@
+# SPDX-License-Identifier: Apache-2.0
+
+import pytest
+
+from nipart import NipartClient
+
+from .testlib.cmdlib import exec_cmd
+from .testlib.retry import retry_till_true_or_timeout
+from .testlib.statelib import load_yaml
+
+TEST_IFACE = "dummy1"
+
+
+@pytest.fixture
+def cleanup_dummy1():
+    yield
+    exec_cmd(f"ip link del {TEST_IFACE}".split(), check=False)
+
+
+def _has_link_local_addr():
+    output = exec_cmd(f"ip -4 addr show dev {TEST_IFACE}".split())[1]
+    return "inet 169.254." in output
+
+
+def test_link_local_on_newly_created_iface(cleanup_dummy1):
+    NipartClient().apply_network_state(
+        load_yaml(
+            f"""---
+            interfaces:
+              - name: {TEST_IFACE}
+                type: dummy
+                state: up
+                ipv4:
+                  enabled: true
+                  dhcp: false
+                  link-local: enabled
+            """
+        )
+    )
+
+    assert retry_till_true_or_timeout(30, _has_link_local_addr)
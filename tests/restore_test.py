@@ -0,0 +1,86 @@
+# SPDX-License-Identifier: Apache-2.0
+
+import os
+import time
+
+import pytest
+
+from nipart import NipartClient
+
+from .testlib.cmdlib import exec_cmd
+from .testlib.env import npt_path
+from .testlib.statelib import load_yaml
+from .testlib.statelib import show_only
+from .testlib.statelib import show_saved_only
+
+TEST_IFACE1 = "dummy1"
+TEST_IFACE2 = "dummy2"
+
+
+@pytest.fixture
+def cleanup_dummies():
+    yield
+    for iface in (TEST_IFACE1, TEST_IFACE2):
+        exec_cmd(f"ip link del {iface}".split(), check=False)
+
+
+def _apply_dummy(iface_name):
+    NipartClient().apply_network_state(
+        load_yaml(
+            f"""---
+            interfaces:
+              - name: {iface_name}
+                type: dummy
+                state: up
+            """
+        )
+    )
+
+
+def _last_generation():
+    state_dir = NipartClient().query_daemon_conf()["state-dir"]
+    return max(
+        int(file_name.removesuffix(".yml"))
+        for file_name in os.listdir(f"{state_dir}/generations")
+        if not file_name.endswith(".secrets.yml")
+    )
+
+
+def test_restore_generation(cleanup_dummies):
+    _apply_dummy(TEST_IFACE1)
+    generation = _last_generation()
+    _apply_dummy(TEST_IFACE2)
+    assert show_saved_only(TEST_IFACE2)
+
+    NipartClient().restore_generation(generation)
+
+    assert show_only(TEST_IFACE1)
+    assert not show_only(TEST_IFACE2)
+    assert show_saved_only(TEST_IFACE1)
+    assert not show_saved_only(TEST_IFACE2)
+    assert _last_generation() == generation + 2
+
+
+def test_cli_restore(cleanup_dummies):
+    _apply_dummy(TEST_IFACE1)
+    generation = _last_generation()
+    _apply_dummy(TEST_IFACE2)
+
+    exec_cmd(f"{npt_path()} restore {generation}".split())
+
+    assert not show_only(TEST_IFACE2)
+    assert not show_saved_only(TEST_IFACE2)
+
+
+def test_unchanged_saved_state_create_no_generation(cleanup_dummies):
+    _apply_dummy(TEST_IFACE1)
+    generation = _last_generation()
+
+    # Reapplying identical state
+    _apply_dummy(TEST_IFACE1)
+    assert _last_generation() == generation
+
+    # SIGHUP reloads config and reapplies saved state
+    exec_cmd("pkill -HUP -x nipartd".split())
+    time.sleep(2)
+    assert _last_generation() == generation
@@ -0,0 +1,75 @@
+# SPDX-License-Identifier: Apache-2.0
+
+import json
+import os
+import pathlib
+import shutil
+
+from .cmdlib import exec_cmd
+
+DBUS_CONF_PATH = f"{pathlib.Path(__file__).parent.resolve()}/dbus-session.conf"
+DBUS_SOCKET_PATH = "/tmp/nipart_test_dbus.sock"
+DBUS_ADDRESS = f"unix:path={DBUS_SOCKET_PATH}"
+DBUS_NAME = "org.nipart.Nipart1"
+DBUS_PATH = "/org/nipart/Nipart1"
+
+NOBODY_UID = 65534
+
+DAEMON_CONF_DROP_IN_DIR = "/etc/nipart/nipartd.conf.d"
+DBUS_DAEMON_CONF_PATH = f"{DAEMON_CONF_DROP_IN_DIR}/99-nipart-test-dbus.conf"
+DBUS_DAEMON_CONF = """
+dbus:
+  enabled: true
+  bus: session
+"""
+
+
+def has_dbus_daemon():
+    return shutil.which("dbus-daemon") is not None
+
+
+def dbus_call(method, signature=None, args=(), uid=None, check=True):
+    """
+    Invoke method of daemon on private session bus via `busctl`, run as
+    specified UID if defined. Return (rc, data, stderr).
+    """
+    cmd = []
+    if uid is not None:
+        cmd += [
+            "setpriv",
+            f"--reuid={uid}",
+            f"--regid={uid}",
+            "--clear-groups",
+        ]
+    cmd += [
+        "busctl",
+        f"--address={DBUS_ADDRESS}",
+        "--json=short",
+        "call",
+        DBUS_NAME,
+        DBUS_PATH,
+        DBUS_NAME,
+        method,
+    ]
+    if signature:
+        cmd += [signature, *args]
+    rc, output, stderr = exec_cmd(cmd, check=check)
+    data = json.loads(output)["data"][0] if rc == 0 and output else None
+    return (rc, data, stderr)
+
+
+def dbus_env():
+    env = os.environ.copy()
+    env["DBUS_SESSION_BUS_ADDRESS"] = DBUS_ADDRESS
+    return env
+
+
+def enable_dbus_in_daemon_conf():
+    os.makedirs(DAEMON_CONF_DROP_IN_DIR, exist_ok=True)
+    with open(DBUS_DAEMON_CONF_PATH, "w") as fd:
+        fd.write(DBUS_DAEMON_CONF)
+
+
+def disable_dbus_in_daemon_conf():
+    if os.path.exists(DBUS_DAEMON_CONF_PATH):
+        os.unlink(DBUS_DAEMON_CONF_PATH)
@@ -0,0 +1,52 @@
+# SPDX-License-Identifier: Apache-2.0
+
+import pytest
+
+from nipart import NipartClient
+from nipart import NipartError
+
+from .testlib.apply import nipart_apply
+
+TEST_IFACE = "dummy1"
+
+
+@pytest.fixture
+def dummy1_with_ipv4():
+    nipart_apply(
+        f"""---
+        interfaces:
+          - name: {TEST_IFACE}
+            type: dummy
+            state: up
+            ipv4:
+              enabled: true
+              address:
+                - ip: 192.0.2.251
+                  prefix-length: 24
+        """
+    )
+    yield
+    nipart_apply(
+        f"""---
+        interfaces:
+          - name: {TEST_IFACE}
+            type: dummy
+            state: absent
+        """
+    )
+
+
+def test_wait_online_iface_with_ipv4(dummy1_with_ipv4):
+    NipartClient().wait_online(ifaces=[TEST_IFACE], ipv4=True, timeout=5)
+
+
+def test_wait_online_iface_without_ipv6(dummy1_with_ipv4):
+    with pytest.raises(NipartError) as e:
+        NipartClient().wait_online(ifaces=[TEST_IFACE], ipv6=True, timeout=2)
+    assert e.value.kind == "timeout"
+
+
+def test_wait_online_iface_not_exist():
+    with pytest.raises(NipartError) as e:
+        NipartClient().wait_online(ifaces=["not_exist0"], timeout=2)
+    assert e.value.kind == "timeout"